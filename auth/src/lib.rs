@@ -28,7 +28,7 @@ pub enum AuthError {
 /// Result type for the auth lib.
 pub type AuthResult<T> = Result<T, AuthError>;
 
-const MINECRAFT_AUTH_URI: &'static str = "https://sessionserver.mojang.com/session/minecraft/join";
+const MINECRAFT_AUTH_URI: &str = "https://sessionserver.mojang.com/session/minecraft/join";
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -102,13 +102,11 @@ fn get_sha1(input: &str) -> String {
     let output = hasher.finalize();
 
     let bigint = BigInt::from_signed_bytes_be(&output);
-    let output = if bigint.sign() == Sign::Minus {
+    if bigint.sign() == Sign::Minus {
         format!("-{:x}", (-bigint))
     } else {
         format!("{:x}", bigint)
-    };
-
-    output
+    }
 }
 
 #[cfg(test)]
@@ -4,6 +4,7 @@ use mc_coms::{
     messages::McPacketError,
     packet_reader::PacketReadError,
     packet_writer::PacketWriteError,
+    protocol_version::ProtocolVersion,
     ser::{ReadingError, WritingError},
 };
 
@@ -20,11 +21,12 @@ pub enum ClientError {
     #[error("Failed to write packet to output `{0}`")]
     PacketWriteError(#[from] PacketWriteError),
     #[error(
-        "Invalid minecraft protocol version received {received_version}, supported version is {supported_version}"
+        "Invalid minecraft protocol version received {received_version}, supported versions are {oldest} to {latest}"
     )]
     InvalidProtocolVersion {
-        received_version: usize,
-        supported_version: usize,
+        received_version: i32,
+        oldest: ProtocolVersion,
+        latest: ProtocolVersion,
     },
     #[error("Invalid packet ID {packet_id} for state {state:?}")]
     UnsupportedPacketId { packet_id: i32, state: ClientState },
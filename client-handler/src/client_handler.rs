@@ -2,34 +2,26 @@ use std::str::FromStr;
 
 use log::{error, info};
 use mc_coms::{
-    SUPPORTED_MINECRAFT_PROTOCOL_VERSION,
     client_state::ClientState,
-    codec::prefixed_array::PrefixedArray,
+    codec::{prefixed_array::PrefixedArray, var_int::VarInt},
     key_store::KeyStore,
     messages::{
-        McPacketRead,
+        McPacketError,
         clientbound::{
             configuration::finish_configuration::FinishConfiguration,
             login::{
                 encryption_request::EncryptionRequest,
                 login_success::{GameProfile, LoginSuccess},
+                set_compression::SetCompression,
             },
             status::{pong_response::PongResponse, status_response::StatusResponse},
         },
-        serverbound::{
-            configuration::{
-                client_information::ClientInformation,
-                serverbound_plugin_message::ServerboundPluginMessage,
-            },
-            handshaking::handshake::Handshake,
-            login::{encryption_response::EncryptionResponse, login_start::LoginStart},
-            status::ping_request::PingRequest,
-        },
+        registry::{ClientboundPacket, ServerboundPacket},
     },
-    packet_reader::{NetworkReader, PacketReadError, RawPacket},
+    packet_reader::{NetworkReader, PacketReadError},
     packet_writer::NetworkWriter,
+    protocol_version::ProtocolVersion,
 };
-use serde::Deserialize;
 use tokio::{
     io::{BufReader, BufWriter},
     net::{
@@ -41,9 +33,13 @@ use uuid::Uuid;
 
 use crate::client_error::ClientError;
 
+/// Packets of at least this many bytes are compressed once the login has enabled compression.
+const COMPRESSION_THRESHOLD: i32 = 256;
+
 /// Handles communication between the server and a specific Minecraft client.
 pub struct ClientHandler<'key> {
     state: ClientState,
+    protocol_version: ProtocolVersion,
     key_store: &'key KeyStore,
     network_writer: NetworkWriter<BufWriter<OwnedWriteHalf>>,
     network_reader: NetworkReader<BufReader<OwnedReadHalf>>,
@@ -61,6 +57,8 @@ impl<'key> ClientHandler<'key> {
         Self {
             key_store,
             state: ClientState::Handshaking,
+            // The handshake is the same in every version.
+            protocol_version: ProtocolVersion::LATEST,
             network_writer: writer,
             network_reader: reader,
         }
@@ -77,6 +75,21 @@ impl<'key> ClientHandler<'key> {
 
             info!("Got new packet (state: {:?}): {packet:02x?}", self.state);
 
+            let packet = match ServerboundPacket::decode_versioned(
+                self.protocol_version,
+                self.state,
+                packet,
+            ) {
+                Ok(packet) => packet,
+                Err(McPacketError::UnknownPacket { state, id, .. }) => {
+                    return Err(ClientError::UnsupportedPacketId {
+                        packet_id: id,
+                        state,
+                    });
+                }
+                Err(err) => return Err(err.into()),
+            };
+
             match self.state {
                 ClientState::Handshaking => self.handle_handshake_packet(packet)?,
                 ClientState::Status => self.handle_status_packet(packet).await?,
@@ -84,39 +97,66 @@ impl<'key> ClientHandler<'key> {
                 ClientState::Configuration => self.handle_configuration_packet(packet).await?,
                 ClientState::Play => self.handle_play_packet(packet).await?,
             }
+
+            // Everything sent in response to a packet goes out together.
+            self.network_writer.flush().await?;
         }
     }
 
-    fn handle_handshake_packet(&mut self, packet: RawPacket) -> Result<(), ClientError> {
-        match packet.id {
-            0x0 => {
-                let handshake = Handshake::deserialize(&mut packet.get_deserializer())?;
+    /// Move the connection to [state], packets are read according to the new state from now on.
+    fn set_state(&mut self, state: ClientState) {
+        self.state = state;
+        self.network_reader.set_state(state);
+    }
+
+    /// Encode the packet for the protocol version of the client & send it.
+    async fn send_packet(
+        &mut self,
+        packet: impl Into<ClientboundPacket>,
+    ) -> Result<(), ClientError> {
+        let packet = packet.into().encode_versioned(self.protocol_version)?;
+        self.network_writer
+            .write_raw_packet(packet.id, &packet.data)
+            .await?;
 
+        Ok(())
+    }
+
+    fn handle_handshake_packet(&mut self, packet: ServerboundPacket) -> Result<(), ClientError> {
+        match packet {
+            ServerboundPacket::Handshake(handshake) => {
                 info!("Received handshake request: {handshake:?}");
 
-                if handshake.protocol_version.0 as usize != SUPPORTED_MINECRAFT_PROTOCOL_VERSION {
-                    println!(
-                        "Unsupported protocol version {}",
-                        handshake.protocol_version.0
-                    );
-                    return Err(ClientError::InvalidProtocolVersion {
-                        received_version: handshake.protocol_version.0 as usize,
-                        supported_version: SUPPORTED_MINECRAFT_PROTOCOL_VERSION,
-                    });
+                match ProtocolVersion::from_protocol(handshake.protocol_version.0) {
+                    Some(version) => self.protocol_version = version,
+                    // Still answer status requests, the server list then shows the version mismatch.
+                    None if handshake.intent.0 == 1 => {}
+                    None => {
+                        println!(
+                            "Unsupported protocol version {}",
+                            handshake.protocol_version.0
+                        );
+                        return Err(ClientError::InvalidProtocolVersion {
+                            received_version: handshake.protocol_version.0,
+                            oldest: ProtocolVersion::OLDEST,
+                            latest: ProtocolVersion::LATEST,
+                        });
+                    }
                 }
+                info!("Using protocol version {}", self.protocol_version);
 
-                self.state = match handshake.intent.0 {
+                self.set_state(match handshake.intent.0 {
                     1 => ClientState::Status,
                     2 => ClientState::Login,
                     3 => unimplemented!("ClientState::Transfer?"),
                     s => panic!("Illegal client state requested {s}"),
-                };
+                });
                 info!("New server state {:?}", self.state);
             }
-            id => {
+            packet => {
                 return Err(ClientError::UnsupportedPacketId {
-                    packet_id: id,
-                    state: ClientState::Handshaking,
+                    packet_id: packet.id(),
+                    state: packet.state(),
                 });
             }
         }
@@ -124,44 +164,41 @@ impl<'key> ClientHandler<'key> {
         Ok(())
     }
 
-    async fn handle_status_packet(&mut self, packet: RawPacket) -> Result<(), ClientError> {
-        match packet.id {
-            0x0 => {
+    async fn handle_status_packet(&mut self, packet: ServerboundPacket) -> Result<(), ClientError> {
+        match packet {
+            ServerboundPacket::StatusRequest(_) => {
                 info!("Got status request");
-                let status_response = StatusResponse::default();
+                let status_response = StatusResponse::new(self.protocol_version);
 
                 info!("Status response: {status_response:?}");
 
-                self.network_writer.write_packet(status_response).await?;
+                self.send_packet(status_response).await?;
 
                 info!("Responded to status request");
             }
-            0x1 => {
-                let ping_request = PingRequest::deserialize(&mut packet.get_deserializer())?;
-
+            ServerboundPacket::PingRequest(ping_request) => {
                 info!("Got status ping with request: {ping_request:?}");
 
                 let pong_response: PongResponse = ping_request.into();
 
-                self.network_writer.write_packet(pong_response).await?;
+                self.send_packet(pong_response).await?;
 
                 info!("Responded to ping request")
             }
-            id => {
+            packet => {
                 return Err(ClientError::UnsupportedPacketId {
-                    packet_id: id,
-                    state: ClientState::Status,
+                    packet_id: packet.id(),
+                    state: packet.state(),
                 });
             }
         }
         Ok(())
     }
 
-    async fn handle_login_packet(&mut self, packet: RawPacket) -> Result<(), ClientError> {
-        match packet.id {
-            0x0 => {
+    async fn handle_login_packet(&mut self, packet: ServerboundPacket) -> Result<(), ClientError> {
+        match packet {
+            ServerboundPacket::LoginStart(login_start) => {
                 info!("Got login start request");
-                let login_start = LoginStart::deserialize(&mut packet.get_deserializer())?;
                 info!("Login start message: {login_start:?}");
 
                 info!("Creating encryption request");
@@ -170,16 +207,13 @@ impl<'key> ClientHandler<'key> {
 
                 info!("Verify token: {:02x?}", encryption_request.verify_token);
 
-                self.network_writer.write_packet(encryption_request).await?;
+                self.send_packet(encryption_request).await?;
 
                 info!("Responded to encryption request");
             }
-            0x1 => {
+            ServerboundPacket::EncryptionResponse(encryption_response) => {
                 info!("Got encryption response");
 
-                let encryption_response =
-                    EncryptionResponse::deserialize(&mut packet.get_deserializer())?;
-
                 info!("Encryption response: {encryption_response:02x?}");
 
                 let shared_secret = self
@@ -204,6 +238,16 @@ impl<'key> ClientHandler<'key> {
                 self.network_writer.enable_encryption(&shared_secret)?;
                 self.network_reader.enable_encryption(&shared_secret)?;
 
+                info!("Enabling compression with threshold {COMPRESSION_THRESHOLD}");
+                let set_compression = SetCompression {
+                    threshold: VarInt(COMPRESSION_THRESHOLD),
+                };
+                self.send_packet(set_compression).await?;
+                self.network_writer
+                    .enable_compression(COMPRESSION_THRESHOLD);
+                self.network_reader
+                    .enable_compression(COMPRESSION_THRESHOLD);
+
                 let id = Uuid::from_str("00002a4a-0000-1000-8000-00805f9b34fb")?;
 
                 let login_success = LoginSuccess {
@@ -216,44 +260,41 @@ impl<'key> ClientHandler<'key> {
 
                 info!("Responding with login success");
 
-                self.network_writer.write_packet(login_success).await?;
+                self.send_packet(login_success).await?;
             }
-            0x3 => {
+            ServerboundPacket::LoginAcknowledged(_) => {
                 info!("Login acknowledged received");
-                self.state = ClientState::Configuration;
+                self.set_state(ClientState::Configuration);
             }
-            id => {
+            packet => {
                 return Err(ClientError::UnsupportedPacketId {
-                    packet_id: id,
-                    state: ClientState::Login,
+                    packet_id: packet.id(),
+                    state: packet.state(),
                 });
             }
         }
         Ok(())
     }
 
-    async fn handle_configuration_packet(&mut self, packet: RawPacket) -> Result<(), ClientError> {
-        match packet.id {
-            0x0 => {
+    async fn handle_configuration_packet(
+        &mut self,
+        packet: ServerboundPacket,
+    ) -> Result<(), ClientError> {
+        match packet {
+            ServerboundPacket::ClientInformation(client_info) => {
                 info!("Client configuration message");
 
-                let client_info = ClientInformation::deserialize(&mut packet.get_deserializer())?;
-
                 // TODO: Store this in the client_data probably.
 
                 info!("Client info: {client_info:?}");
 
                 info!("Responding with configuration completed");
 
-                self.network_writer
-                    .write_packet(FinishConfiguration)
-                    .await?;
+                self.send_packet(FinishConfiguration).await?;
             }
-            0x2 => {
+            ServerboundPacket::ServerboundPluginMessage(plugin_message) => {
                 info!("Received plugin message");
 
-                let plugin_message = ServerboundPluginMessage::read(packet)?;
-
                 // TODO: Store this in the client_data probably.
 
                 info!(
@@ -262,16 +303,16 @@ impl<'key> ClientHandler<'key> {
                     String::from_utf8_lossy(plugin_message.data.as_slice())
                 );
             }
-            0x3 => {
+            ServerboundPacket::AcknowledgeFinishConfiguration(_) => {
                 info!("Received acknowledge for finish configuration, changing to state play");
-                self.state = ClientState::Play;
+                self.set_state(ClientState::Play);
 
                 // TODO: Probably do something more here?
             }
-            id => {
+            packet => {
                 return Err(ClientError::UnsupportedPacketId {
-                    packet_id: id,
-                    state: ClientState::Configuration,
+                    packet_id: packet.id(),
+                    state: packet.state(),
                 });
             }
         }
@@ -280,12 +321,12 @@ impl<'key> ClientHandler<'key> {
     }
 
     #[allow(unreachable_code, clippy::match_single_binding)] // TODO: remove when we have implemented stuff.
-    async fn handle_play_packet(&mut self, packet: RawPacket) -> Result<(), ClientError> {
-        match packet.id {
-            id => {
+    async fn handle_play_packet(&mut self, packet: ServerboundPacket) -> Result<(), ClientError> {
+        match packet {
+            packet => {
                 return Err(ClientError::UnsupportedPacketId {
-                    packet_id: id,
-                    state: ClientState::Configuration,
+                    packet_id: packet.id(),
+                    state: packet.state(),
                 });
             }
         }
@@ -0,0 +1,85 @@
+//! Compares the in place stream encryption with the byte-by-byte encryption the network reader &
+//! writer used before, on packets the size of a typical chunk packet. Run with
+//! `cargo bench -p mc-coms --bench encryption`.
+
+use std::hint::black_box;
+
+use aes::{
+    Aes128,
+    cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit, generic_array::GenericArray},
+};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use mc_coms::encryption::{StreamDecryptor, StreamEncryptor};
+
+const KEY: [u8; 16] = [0x42; 16];
+
+/// Chunk data packets are usually somewhere between a couple & a few dozen KiB.
+const PACKET_SIZES: [usize; 3] = [4 * 1024, 16 * 1024, 64 * 1024];
+
+/// How the writer used to encrypt, one block at a time into a freshly allocated buffer.
+fn encrypt_byte_by_byte(cipher: &mut cfb8::Encryptor<Aes128>, data: &[u8]) -> Vec<u8> {
+    let mut encrypted = Vec::new();
+    for block in data.chunks(1) {
+        let mut out = [0u8];
+        let out_block = GenericArray::from_mut_slice(&mut out);
+        cipher.encrypt_block_b2b_mut(block.into(), out_block);
+        encrypted.push(out_block[0]);
+    }
+    encrypted
+}
+
+/// How the reader used to decrypt, in place but one block at a time.
+fn decrypt_byte_by_byte(cipher: &mut cfb8::Decryptor<Aes128>, data: &mut [u8]) {
+    for block in data.chunks_mut(1) {
+        cipher.decrypt_block_mut(block.into());
+    }
+}
+
+fn encryption_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encrypt");
+    for size in PACKET_SIZES {
+        let data = (0..size).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        group.throughput(Throughput::Bytes(size as u64));
+
+        group.bench_with_input(BenchmarkId::new("byte_by_byte", size), &data, |b, data| {
+            let mut cipher = cfb8::Encryptor::<Aes128>::new(&KEY.into(), &KEY.into());
+            b.iter(|| black_box(encrypt_byte_by_byte(&mut cipher, data)))
+        });
+        group.bench_with_input(BenchmarkId::new("in_place", size), &data, |b, data| {
+            let mut encryptor = StreamEncryptor::new(&KEY);
+            let mut buffer = data.clone();
+            b.iter(|| {
+                encryptor.encrypt(&mut buffer);
+                black_box(&buffer);
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("decrypt");
+    for size in PACKET_SIZES {
+        let data = (0..size).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        group.throughput(Throughput::Bytes(size as u64));
+
+        group.bench_with_input(BenchmarkId::new("byte_by_byte", size), &data, |b, data| {
+            let mut cipher = cfb8::Decryptor::<Aes128>::new(&KEY.into(), &KEY.into());
+            let mut buffer = data.clone();
+            b.iter(|| {
+                decrypt_byte_by_byte(&mut cipher, &mut buffer);
+                black_box(&buffer);
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("in_place", size), &data, |b, data| {
+            let mut decryptor = StreamDecryptor::new(&KEY);
+            let mut buffer = data.clone();
+            b.iter(|| {
+                decryptor.decrypt(&mut buffer);
+                black_box(&buffer);
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, encryption_benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,91 @@
+//! Generates packet ID constants from the vanilla packet report (`make generate`), which are used
+//! to verify the hand-written packet IDs in `messages::registry`. Without a report the IDs aren't
+//! verified & the build only warns, unless `MC_REQUIRE_PACKET_REPORT` is set (as it is in CI).
+
+use std::{env, fmt::Write, fs, path::PathBuf};
+
+use serde_json::Value;
+
+const REPORT_ENV: &str = "MC_PACKET_REPORT";
+const REQUIRE_ENV: &str = "MC_REQUIRE_PACKET_REPORT";
+
+fn main() {
+    println!("cargo::rustc-check-cfg=cfg(packet_report)");
+    println!("cargo::rerun-if-env-changed={REPORT_ENV}");
+    println!("cargo::rerun-if-env-changed={REQUIRE_ENV}");
+
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let report_path = env::var(REPORT_ENV).map(PathBuf::from).unwrap_or_else(|_| {
+        manifest_dir
+            .join("..")
+            .join("generated")
+            .join("reports")
+            .join("packets.json")
+    });
+    println!("cargo::rerun-if-changed={}", report_path.display());
+
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("packet_ids.rs");
+
+    let report = match fs::read_to_string(&report_path) {
+        Ok(report) => report,
+        Err(err) if env::var_os(REQUIRE_ENV).is_some() => panic!(
+            "No packet report at {}: {err}. Run `make generate` or point {REPORT_ENV} at a report",
+            report_path.display()
+        ),
+        Err(_) => {
+            println!(
+                "cargo::warning=No packet report at {}, packet IDs are not verified. Run `make generate` to verify them",
+                report_path.display()
+            );
+            fs::write(out_path, "").unwrap();
+            return;
+        }
+    };
+
+    let report: Value = serde_json::from_str(&report)
+        .unwrap_or_else(|err| panic!("Invalid packet report {}: {err}", report_path.display()));
+
+    fs::write(out_path, generate(&report)).unwrap();
+    println!("cargo::rustc-cfg=packet_report");
+}
+
+/// Turn the report (`{state: {direction: {"minecraft:name": {"protocol_id": id}}}}`) into nested
+/// `state::direction::NAME` modules.
+fn generate(report: &Value) -> String {
+    let mut out = String::new();
+
+    for (state, directions) in as_object(report, "report") {
+        writeln!(out, "/// Packets in the `{state}` state.").unwrap();
+        writeln!(out, "pub mod {state} {{").unwrap();
+
+        for (direction, packets) in as_object(directions, state) {
+            writeln!(out, "    /// {direction} packets.").unwrap();
+            writeln!(out, "    pub mod {direction} {{").unwrap();
+
+            for (name, packet) in as_object(packets, direction) {
+                let id = packet["protocol_id"]
+                    .as_i64()
+                    .unwrap_or_else(|| panic!("Packet {name} is missing its protocol_id"));
+                let const_name = name
+                    .trim_start_matches("minecraft:")
+                    .to_uppercase()
+                    .replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+
+                writeln!(out, "        /// `{name}`").unwrap();
+                writeln!(out, "        pub const {const_name}: i32 = {id:#04x};").unwrap();
+            }
+
+            writeln!(out, "    }}").unwrap();
+        }
+
+        writeln!(out, "}}").unwrap();
+    }
+
+    out
+}
+
+fn as_object<'a>(value: &'a Value, what: &str) -> &'a serde_json::Map<String, Value> {
+    value
+        .as_object()
+        .unwrap_or_else(|| panic!("Expected an object for {what} in the packet report"))
+}
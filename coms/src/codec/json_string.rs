@@ -15,6 +15,12 @@ impl<T, const MAX_SIZE: usize> JsonString<T, MAX_SIZE> {
     }
 }
 
+impl<T, const MAX_SIZE: usize> From<T> for JsonString<T, MAX_SIZE> {
+    fn from(inner: T) -> Self {
+        Self { inner }
+    }
+}
+
 impl<T: Serialize, const MAX_SIZE: usize> Serialize for JsonString<T, MAX_SIZE> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
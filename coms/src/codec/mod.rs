@@ -4,6 +4,8 @@ pub mod identifier;
 pub mod json_string;
 /// UUID type with support for the minecraft serialization/deserialization.
 pub mod mc_uuid;
+/// NBT as sent over the network, i.e. without a name for the root tag.
+pub mod network_nbt;
 /// Implementation of the minecraft Prefixed Array
 pub mod prefixed_array;
 /// Implementation of the minecraft Prefixed Optional
@@ -0,0 +1,144 @@
+use std::fmt;
+
+use nbt::{nbt_value::value::NbtValue, tag_type::NbtTagType};
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, Visitor},
+};
+
+/// NBT as sent over the network since 1.20.2, i.e. a root tag without a name.
+/// A root of TAG_End is used by the protocol to signal that no NBT is present.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkNbt(pub NbtTagType);
+
+impl NetworkNbt {
+    /// Newtype name the packet (de)serializer uses to recognize network NBT fields.
+    pub const SERDE_NAME: &str = "NetworkNbt";
+}
+
+/// Already encoded network NBT, written as-is without a length prefix.
+struct EncodedNbt(Vec<u8>);
+
+impl Serialize for EncodedNbt {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl Serialize for NetworkNbt {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut encoded = Vec::new();
+        self.0
+            .write_network(&mut encoded)
+            .map_err(serde::ser::Error::custom)?;
+
+        serializer.serialize_newtype_struct(Self::SERDE_NAME, &EncodedNbt(encoded))
+    }
+}
+
+struct NetworkNbtVisitor;
+
+impl<'de> Visitor<'de> for NetworkNbtVisitor {
+    type Value = NetworkNbt;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("network NBT")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        // A root of TAG_End, which no NbtValue stands for.
+        Ok(NetworkNbt(NbtTagType::TagEnd))
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        // Through NbtValue so arrays don't turn into lists.
+        NbtValue::deserialize(deserializer).map(|value| NetworkNbt(value.into()))
+    }
+}
+
+impl<'de> Deserialize<'de> for NetworkNbt {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(Self::SERDE_NAME, NetworkNbtVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use nbt::{
+        error::NbtError,
+        limits::NbtLimits,
+        nbt_named_tag::NbtNamedTag,
+        nbt_types::{NbtCompound, NbtInt, NbtIntArray, NbtString},
+    };
+
+    use super::*;
+    use crate::ser::{ReadingError, deserializer, serializer};
+
+    #[test]
+    fn test_network_nbt_round_trip() {
+        let nbt = NetworkNbt(NbtTagType::TagCompound(NbtCompound(vec![NbtNamedTag {
+            name: NbtString("ids".to_string()),
+            payload: NbtTagType::TagIntArray(NbtIntArray(vec![NbtInt(1), NbtInt(2)])),
+        }])));
+        let mut expected = vec![1];
+        nbt.0.write_network(&mut expected).unwrap();
+        expected.push(7);
+
+        let value = (Some(nbt), 7u8);
+        let mut written = Vec::new();
+        value
+            .serialize(&mut serializer::Serializer::new(&mut written))
+            .unwrap();
+        assert_eq!(written, expected);
+
+        let read = <(Option<NetworkNbt>, u8)>::deserialize(&mut deserializer::Deserializer::new(
+            Cursor::new(written),
+        ))
+        .unwrap();
+        assert_eq!(read, value);
+
+        // A root of TAG_End for no NBT at all.
+        let read =
+            NetworkNbt::deserialize(&mut deserializer::Deserializer::new(Cursor::new(vec![0])))
+                .unwrap();
+        assert_eq!(read, NetworkNbt(NbtTagType::TagEnd));
+    }
+
+    #[test]
+    fn test_network_nbt_limits() {
+        // TAG_Lists of TAG_Lists nested [depth] levels deep.
+        let nested = |depth| {
+            let mut bytes = vec![9];
+            for _ in 0..depth {
+                bytes.extend([9, 0, 0, 0, 1]);
+            }
+            bytes.extend([0, 0, 0, 0, 0]);
+            Cursor::new(bytes)
+        };
+        let exceeds_limit = |read: Result<NetworkNbt, ReadingError>| {
+            matches!(
+                read,
+                Err(ReadingError::NbtParseError(NbtError::LimitExceeded { .. }))
+            )
+        };
+
+        let read = NetworkNbt::deserialize(&mut deserializer::Deserializer::new(nested(600)));
+        assert!(exceeds_limit(read));
+
+        let read = NetworkNbt::deserialize(&mut deserializer::Deserializer::new(nested(10)));
+        assert!(read.is_ok());
+
+        let read = NetworkNbt::deserialize(
+            &mut deserializer::Deserializer::new(nested(10)).with_nbt_limits(NbtLimits {
+                max_depth: 5,
+                ..NbtLimits::NETWORK
+            }),
+        );
+        assert!(exceeds_limit(read));
+    }
+}
@@ -5,6 +5,21 @@ use std::fmt::{self, Debug};
 
 use crate::codec::var_int::VarInt;
 
+/// The most items preallocated for a length read from the network. Longer arrays still decode, they
+/// just grow as their items are read, so a bogus length can't allocate gigabytes up front.
+const MAX_PREALLOCATED_ITEMS: usize = 1024;
+
+/// An empty [Vec] for [length] items read from the network, errors if [length] is negative.
+pub(crate) fn vec_for_length<T, E: de::Error>(length: VarInt) -> Result<Vec<T>, E> {
+    let capacity = usize::try_from(length.0).map_err(|_| {
+        E::invalid_value(
+            de::Unexpected::Signed(length.0.into()),
+            &"a non-negative length",
+        )
+    })?;
+    Ok(Vec::with_capacity(capacity.min(MAX_PREALLOCATED_ITEMS)))
+}
+
 /// An array with a varint length to be parsed
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct PrefixedArray<T>(Vec<T>);
@@ -66,7 +81,7 @@ where
                     .next_element()?
                     .ok_or_else(|| de::Error::invalid_length(0, &self))?;
 
-                let mut items = Vec::with_capacity(length.0 as usize);
+                let mut items = vec_for_length(length)?;
 
                 // let mut items = Vec::new();
 
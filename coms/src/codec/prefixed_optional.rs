@@ -1,5 +1,6 @@
 use serde::Deserialize;
 use serde::Deserializer;
+use serde::Serialize;
 use serde::de;
 use serde::de::SeqAccess;
 use serde::de::Visitor;
@@ -42,25 +43,18 @@ where
                     .next_element()?
                     .ok_or_else(|| de::Error::invalid_length(0, &self))?;
 
-                let Some(v) = seq.next_element()? else {
-                    if !present {
-                        return Ok(PrefixedOptional(None));
-                    }
-
-                    return Err(de::Error::custom(
-                        "Expected element, should exist because of prefix boolean but data not present",
-                    ));
-                };
-
+                // Only read the value if it's present, anything after it belongs to the next field.
                 if !present {
-                    return Err(de::Error::custom(
-                        "Did not expect an element, but found one.",
-                    ));
+                    return Ok(PrefixedOptional(None));
                 }
 
-                // TODO: maybe check for extra elements lol :3
+                let v = seq.next_element()?.ok_or_else(|| {
+                    de::Error::custom(
+                        "Expected element, should exist because of prefix boolean but data not present",
+                    )
+                })?;
 
-                Ok(PrefixedOptional(v))
+                Ok(PrefixedOptional(Some(v)))
             }
         }
 
@@ -69,3 +63,13 @@ where
         })
     }
 }
+
+impl<T: Serialize> Serialize for PrefixedOptional<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        // Same layout as an Option, a boolean followed by the value if present.
+        self.0.serialize(serializer)
+    }
+}
@@ -0,0 +1,129 @@
+use aes::{
+    Aes128, Block,
+    cipher::{BlockBackend, BlockClosure, BlockEncrypt, BlockSizeUser, KeyInit, consts::U16},
+};
+
+/// How many bytes are decrypted at once, plenty to keep all parallel AES-NI lanes busy.
+const DECRYPT_CHUNK_SIZE: usize = 64;
+
+/// Encrypts everything sent on a connection once encryption is enabled, using AES-128 in CFB8
+/// mode with the shared secret as both the key & the IV.
+pub struct StreamEncryptor {
+    cipher: Aes128,
+    iv: [u8; 16],
+}
+
+impl StreamEncryptor {
+    /// Creates a [StreamEncryptor] from the shared secret of the connection.
+    pub fn new(shared_secret: &[u8; 16]) -> Self {
+        Self {
+            cipher: Aes128::new(shared_secret.into()),
+            iv: *shared_secret,
+        }
+    }
+
+    /// Encrypt [data] in place, continuing the stream where the previous call left off.
+    pub fn encrypt(&mut self, data: &mut [u8]) {
+        // Every byte depends on the ciphertext of the one before it, so this is one block at a
+        // time, but at least the cipher backend is only set up once for the whole buffer.
+        self.cipher.encrypt_with_backend(EncryptClosure {
+            iv: &mut self.iv,
+            data,
+        });
+    }
+}
+
+struct EncryptClosure<'a> {
+    iv: &'a mut [u8; 16],
+    data: &'a mut [u8],
+}
+
+impl BlockSizeUser for EncryptClosure<'_> {
+    type BlockSize = U16;
+}
+
+impl BlockClosure for EncryptClosure<'_> {
+    fn call<B: BlockBackend<BlockSize = U16>>(self, backend: &mut B) {
+        for byte in self.data.iter_mut() {
+            let mut keystream = Block::from(*self.iv);
+            backend.proc_block((&mut keystream).into());
+            *byte ^= keystream[0];
+
+            self.iv.copy_within(1.., 0);
+            self.iv[15] = *byte;
+        }
+    }
+}
+
+/// Decrypts everything received on a connection once encryption is enabled, the counterpart of
+/// [StreamEncryptor].
+pub struct StreamDecryptor {
+    cipher: Aes128,
+    /// The last 16 bytes of ciphertext.
+    iv: [u8; 16],
+}
+
+impl StreamDecryptor {
+    /// Creates a [StreamDecryptor] from the shared secret of the connection.
+    pub fn new(shared_secret: &[u8; 16]) -> Self {
+        Self {
+            cipher: Aes128::new(shared_secret.into()),
+            iv: *shared_secret,
+        }
+    }
+
+    /// Decrypt [data] in place, continuing the stream where the previous call left off.
+    pub fn decrypt(&mut self, data: &mut [u8]) {
+        let mut ciphertext = [0u8; 16 + DECRYPT_CHUNK_SIZE];
+        let mut keystream = [Block::default(); DECRYPT_CHUNK_SIZE];
+
+        for chunk in data.chunks_mut(DECRYPT_CHUNK_SIZE) {
+            // Unlike encryption the keystream of a byte only depends on the ciphertext before it,
+            // which we already have, so the blocks of a whole chunk are encrypted in parallel.
+            ciphertext[..16].copy_from_slice(&self.iv);
+            ciphertext[16..16 + chunk.len()].copy_from_slice(chunk);
+
+            let keystream = &mut keystream[..chunk.len()];
+            for (i, block) in keystream.iter_mut().enumerate() {
+                block.copy_from_slice(&ciphertext[i..i + 16]);
+            }
+            self.cipher.encrypt_blocks(keystream);
+
+            for (byte, block) in chunk.iter_mut().zip(keystream.iter()) {
+                *byte ^= block[0];
+            }
+            self.iv
+                .copy_from_slice(&ciphertext[chunk.len()..chunk.len() + 16]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aes::cipher::{AsyncStreamCipher, KeyIvInit};
+
+    use super::*;
+
+    #[test]
+    fn test_matches_cfb8() {
+        let key = [42u8; 16];
+        let data = (0..1000).map(|i| (i * 31 % 256) as u8).collect::<Vec<_>>();
+
+        let mut expected = data.clone();
+        cfb8::Encryptor::<Aes128>::new(&key.into(), &key.into()).encrypt(&mut expected);
+
+        // Split up unevenly to check the stream continues between calls.
+        let mut encrypted = data.clone();
+        let mut encryptor = StreamEncryptor::new(&key);
+        let (start, end) = encrypted.split_at_mut(333);
+        encryptor.encrypt(start);
+        encryptor.encrypt(end);
+        assert_eq!(encrypted, expected);
+
+        let mut decryptor = StreamDecryptor::new(&key);
+        decryptor.decrypt(&mut encrypted[..1]);
+        decryptor.decrypt(&mut encrypted[1..100]);
+        decryptor.decrypt(&mut encrypted[100..]);
+        assert_eq!(encrypted, data);
+    }
+}
@@ -53,7 +53,7 @@ impl KeyStore {
     /// Decrypt the provided data.
     pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, EncryptionError> {
         self.private_key
-            .decrypt(Pkcs1v15Encrypt, &data)
-            .map_err(|err| EncryptionError::DecryptFailure(err))
+            .decrypt(Pkcs1v15Encrypt, data)
+            .map_err(EncryptionError::DecryptFailure)
     }
 }
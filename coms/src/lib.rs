@@ -15,23 +15,27 @@ use serde::{Serialize, de::DeserializeOwned};
 pub mod client_state;
 /// Special special minecraft types e.g. VarInt etc.
 pub mod codec;
+/// AES/CFB8 encryption of the data sent over a connection.
+pub mod encryption;
 /// Keystore for storing encryption keys.
 pub mod key_store;
 /// Minecraft packet definitions.
 pub mod messages;
+/// A tokio codec for framing mc_packets, e.g. for use with `Framed`.
+pub mod packet_codec;
+/// Packet IDs per state & direction from the vanilla packet report (`make generate`).
+pub mod packet_ids;
 /// Implements support for reading mc_packets correctly.
 pub mod packet_reader;
 /// Implements support for writing mc_packets correctly.
 pub mod packet_writer;
+/// The supported Minecraft protocol versions & how their packets differ.
+pub mod protocol_version;
 /// Network coms for sending / receiving MC Packets.
 pub mod ser;
 /// Reading / writing (a bit unclear tbh).
 pub mod serial;
 
-/// Which minecraft protocol version this implementation supports.
-/// 773 ~= 1.21.9, 1.21.10 etc.
-pub const SUPPORTED_MINECRAFT_PROTOCOL_VERSION: usize = 773;
-
 /// A client-bound packet.
 pub trait ClientPacket: McPacket {
     /// Write the data of client-bound packet to the provided [write].
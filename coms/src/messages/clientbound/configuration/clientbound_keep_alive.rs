@@ -1,9 +1,9 @@
 use crate::McPacket;
 use mc_packet_macros::mc_packet;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Clientbound keep alive request.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[mc_packet(0x04)]
 pub struct ClientboundKeepAlive {
     keep_alive_id: i64,
@@ -1,4 +1,4 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     McPacket, codec::prefixed_array::PrefixedArray, messages::models::data_pack::DataPack,
@@ -6,7 +6,7 @@ use crate::{
 use mc_packet_macros::mc_packet;
 
 /// Known packs requests.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[mc_packet(0x0E)]
 pub struct ClientboundKnownPacks {
     known_packs: PrefixedArray<DataPack>,
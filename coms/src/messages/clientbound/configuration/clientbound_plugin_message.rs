@@ -5,7 +5,8 @@ use mc_packet_macros::mc_packet;
 use crate::{
     McPacket,
     codec::{identifier::Identifier, var_int::VarInt},
-    messages::{McPacketError, McPacketRead},
+    messages::{McPacketError, McPacketRead, McPacketWrite},
+    ser::NetworkWriteExt,
 };
 use serde::Deserialize;
 
@@ -31,3 +32,13 @@ impl McPacketRead for ClientboundPluginMessage {
         Ok(Self { channel, data })
     }
 }
+
+impl McPacketWrite for ClientboundPluginMessage {
+    fn write(&self) -> Result<Vec<u8>, McPacketError> {
+        let mut data = Vec::new();
+        data.write_string(&self.channel.0)?;
+        data.extend_from_slice(&self.data);
+
+        Ok(data)
+    }
+}
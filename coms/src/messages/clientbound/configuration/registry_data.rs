@@ -1,13 +1,12 @@
 use crate::{
     McPacket,
-    codec::{identifier::Identifier, prefixed_array::PrefixedArray},
+    codec::{identifier::Identifier, network_nbt::NetworkNbt, prefixed_array::PrefixedArray},
 };
 use mc_packet_macros::mc_packet;
-use nbt::{nbt_named_tag::NbtNamedTag, nbt_value::value::NbtValue};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Registry data packet.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[mc_packet(0x07)]
 pub struct RegistryData {
     registry_id: Identifier,
@@ -15,8 +14,8 @@ pub struct RegistryData {
 }
 
 /// A specific registry data entry.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct RegistryEntry {
     entry_id: Identifier,
-    data: Option<NbtValue>,
+    data: Option<NetworkNbt>,
 }
@@ -1,5 +1,5 @@
 use mc_packet_macros::mc_packet;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     McPacket,
@@ -7,7 +7,7 @@ use crate::{
 };
 
 /// Update the tags registry of the client.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[mc_packet(0x0D)]
 pub struct UpdateTags {
     /// A list of tagged registries, each containing a list of tags and their values.
@@ -15,7 +15,7 @@ pub struct UpdateTags {
 }
 
 /// New tags for a specific registry.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct TaggedRegistry {
     /// The name of the registry.
     pub registry: String,
@@ -24,7 +24,7 @@ pub struct TaggedRegistry {
 }
 
 /// A list of block ids belonging to the specified tag (e.g. minecraft:climbable).
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Tag {
     /// The name of the tag.
     pub name: String,
@@ -1,9 +1,9 @@
 use crate::McPacket;
 use mc_packet_macros::mc_packet;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// This is the cookie request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[mc_packet(0x05)]
 pub struct CookieRequest {
     key: String,
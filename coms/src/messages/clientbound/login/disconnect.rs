@@ -1,9 +1,9 @@
 use crate::McPacket;
 use mc_packet_macros::mc_packet;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Disconnect message, gives a reason as a json object
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[mc_packet(0x00)]
 pub struct Disconnect {
     reason: String, // json
@@ -1,13 +1,48 @@
-use crate::McPacket;
-use crate::codec::var_int::VarInt;
+use std::io::{Cursor, Read};
+
+use crate::{
+    McPacket,
+    codec::{identifier::Identifier, var_int::VarInt},
+    messages::{McPacketError, McPacketRead, McPacketWrite},
+    ser::NetworkWriteExt,
+};
 use mc_packet_macros::mc_packet;
-use serde::Deserialize;
 
 /// Login packet request message
-#[derive(Debug, Deserialize)]
+#[derive(Debug)]
 #[mc_packet(0x04)]
 pub struct LoginPluginRequest {
     message_id: VarInt,
     channel: String,
     data: Vec<u8>, // length must be infered from the packet length
 }
+
+impl McPacketRead for LoginPluginRequest {
+    type Output = Self;
+
+    fn read(raw_packet: crate::packet_reader::RawPacket) -> Result<Self::Output, McPacketError> {
+        let mut cursor = Cursor::new(raw_packet.data);
+        let message_id = VarInt::decode(&mut cursor)?;
+        let channel = Identifier::decode(&mut cursor)?.0;
+
+        let mut data = Vec::new();
+        cursor.read_to_end(&mut data)?;
+
+        Ok(Self {
+            message_id,
+            channel,
+            data,
+        })
+    }
+}
+
+impl McPacketWrite for LoginPluginRequest {
+    fn write(&self) -> Result<Vec<u8>, McPacketError> {
+        let mut data = Vec::new();
+        data.write_var_int(&self.message_id)?;
+        data.write_string(&self.channel)?;
+        data.extend_from_slice(&self.data);
+
+        Ok(data)
+    }
+}
@@ -1,11 +1,12 @@
 use crate::McPacket;
 use crate::codec::var_int::VarInt;
 use mc_packet_macros::mc_packet;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Set compression message
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[mc_packet(0x03)]
 pub struct SetCompression {
-    threshold: VarInt,
+    /// Minimum size of a packet before it is compressed, negative disables compression.
+    pub threshold: VarInt,
 }
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use crate::messages::models::difficulty::Difficulty;
 
 /// Clientbound change difficulty packet.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[mc_packet(0x0A)]
 pub struct ChangeDifficulty {
     /// The new difficulty level.
@@ -10,7 +10,7 @@ use mc_packet_macros::mc_packet;
 use serde::{Deserialize, Serialize};
 
 /// Clientbound login packet.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[mc_packet(0x30)]
 pub struct Login {
     /// The entity ID of the player.
@@ -56,7 +56,7 @@ pub struct Login {
 }
 
 /// The location of a player's death.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct DeathLocation {
     /// The name of the dimension the player died in.
     pub dimension_name: Identifier,
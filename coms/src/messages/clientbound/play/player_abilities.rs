@@ -10,7 +10,7 @@ use mc_packet_macros::mc_packet;
 use serde::{Deserialize, Serialize};
 
 /// Clientbound login packet.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[mc_packet(0x3E)]
 pub struct PlayerAbilities {
     /// The flags of the player's abilities.
@@ -54,7 +54,7 @@ impl Serialize for PlayerAbilityFlags {
     where
         S: serde::Serializer,
     {
-        let mut flags = 0;
+        let mut flags = 0u8;
         if self.invulnerable {
             flags |= 0x01;
         }
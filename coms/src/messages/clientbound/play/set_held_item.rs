@@ -3,7 +3,7 @@ use mc_packet_macros::mc_packet;
 use serde::{Deserialize, Serialize};
 
 /// Clientbound set held item packet.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[mc_packet(0x67)]
 pub struct SetHeldItem {
     /// The slot that the player is now holding (0-8).
@@ -9,7 +9,7 @@ use mc_packet_macros::mc_packet;
 use serde::{Deserialize, Serialize};
 
 /// Clientbound update recipes packet.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[mc_packet(0x83)]
 pub struct UpdateRecipes {
     /// The list of property sets to update.
@@ -19,7 +19,7 @@ pub struct UpdateRecipes {
 }
 
 /// A property set to update in the update recipes packet.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct PropertySet {
     /// The ID of the property set to update.
     pub property_set_id: Identifier,
@@ -28,7 +28,7 @@ pub struct PropertySet {
 }
 
 /// A stonecutter recipe to update in the update recipes packet.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct StonecutterRecipe {
     /// The ingredients of the stonecutter recipe.
     pub ingredients: IdSet,
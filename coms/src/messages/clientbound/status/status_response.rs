@@ -1,4 +1,4 @@
-use crate::{McPacket, codec::json_string::JsonString};
+use crate::{McPacket, codec::json_string::JsonString, protocol_version::ProtocolVersion};
 use mc_packet_macros::mc_packet;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -8,6 +8,13 @@ use uuid::Uuid;
 #[mc_packet(0x00)]
 pub struct StatusResponse(JsonString<ServerStatus, 27512>);
 
+impl StatusResponse {
+    /// Creates a [StatusResponse] for a client using the provided [ProtocolVersion].
+    pub fn new(version: ProtocolVersion) -> Self {
+        Self(JsonString::from(ServerStatus::new(version)))
+    }
+}
+
 /// Server status inner response.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -22,12 +29,14 @@ pub struct ServerStatus {
     enforces_secure_chat: Option<bool>,
 }
 
-impl Default for ServerStatus {
-    fn default() -> Self {
+impl ServerStatus {
+    /// Creates the status of this server as reported to a client using the provided
+    /// [ProtocolVersion].
+    pub fn new(version: ProtocolVersion) -> Self {
         Self {
             version: ServerStatusVersion {
-                name: "1.28.10".to_string(),
-                protocol: 773,
+                name: version.name().to_string(),
+                protocol: version.protocol() as u32,
             },
             players: Some(ServerStatusPlayers {
                 max: 20,
@@ -41,6 +50,12 @@ impl Default for ServerStatus {
     }
 }
 
+impl Default for ServerStatus {
+    fn default() -> Self {
+        Self::new(ProtocolVersion::LATEST)
+    }
+}
+
 /// The Minecraft version of this server implementation.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ServerStatusVersion {
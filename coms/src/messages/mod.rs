@@ -1,8 +1,14 @@
 use std::string::FromUtf8Error;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::{codec::var_int::VarInt, packet_reader::RawPacket, ser::ReadingError};
+use crate::{
+    client_state::ClientState,
+    codec::var_int::VarInt,
+    packet_reader::RawPacket,
+    protocol_version::ProtocolVersion,
+    ser::{ReadingError, WritingError, serializer::Serializer},
+};
 
 // TODO: remove when implemented all parsers
 
@@ -11,6 +17,8 @@ use crate::{codec::var_int::VarInt, packet_reader::RawPacket, ser::ReadingError}
 pub mod clientbound;
 /// Common models between server/client-bound packages.
 pub mod models;
+/// Registry of every known packet, keyed by state, direction & ID.
+pub mod registry;
 /// Server-bound packages.
 #[allow(unused)]
 pub mod serverbound;
@@ -36,6 +44,14 @@ pub enum McPacketError {
     IoError(#[from] std::io::Error),
     #[error("Failed to parse utf8, err: `{0}`")]
     Utf8Error(#[from] FromUtf8Error),
+    #[error("Failed to write this packet, err: {0}")]
+    WritingError(#[from] WritingError),
+    #[error("Unknown {direction:?} packet ID {id:#04x} in state {state:?}")]
+    UnknownPacket {
+        state: ClientState,
+        direction: registry::PacketDirection,
+        id: i32,
+    },
 }
 
 /// Something that can read a Minecraft packet.
@@ -45,6 +61,15 @@ pub trait McPacketRead {
 
     /// Read a packet from the provided raw_packet.
     fn read(raw_packet: RawPacket) -> Result<Self::Output, McPacketError>;
+
+    /// Read a packet sent using the provided [ProtocolVersion], packets with a layout that differs
+    /// between versions override this.
+    fn read_versioned(
+        raw_packet: RawPacket,
+        _version: ProtocolVersion,
+    ) -> Result<Self::Output, McPacketError> {
+        Self::read(raw_packet)
+    }
 }
 
 impl<'de, T> McPacketRead for T
@@ -59,3 +84,27 @@ where
         Ok(v)
     }
 }
+
+/// Something that can write a Minecraft packet.
+pub trait McPacketWrite {
+    /// Write the payload of this packet (excluding its ID).
+    fn write(&self) -> Result<Vec<u8>, McPacketError>;
+
+    /// Write the payload of this packet for the provided [ProtocolVersion], packets with a layout
+    /// that differs between versions override this.
+    fn write_versioned(&self, _version: ProtocolVersion) -> Result<Vec<u8>, McPacketError> {
+        self.write()
+    }
+}
+
+impl<T> McPacketWrite for T
+where
+    T: Serialize,
+{
+    fn write(&self) -> Result<Vec<u8>, McPacketError> {
+        let mut data = Vec::new();
+        self.serialize(&mut Serializer::new(&mut data))?;
+
+        Ok(data)
+    }
+}
@@ -1,9 +1,12 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// A datapack.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct DataPack {
-    namespace: String,
-    id: String,
-    version: String,
+    /// Namespace of the pack, e.g. minecraft.
+    pub namespace: String,
+    /// ID of the pack, e.g. core.
+    pub id: String,
+    /// Version of the pack, e.g. 1.21.10.
+    pub version: String,
 }
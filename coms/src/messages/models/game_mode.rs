@@ -78,3 +78,15 @@ impl<'de> Deserialize<'de> for PreviousGameMode {
         }
     }
 }
+
+impl Serialize for PreviousGameMode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            PreviousGameMode::Undefined => (-1i8).serialize(serializer),
+            PreviousGameMode::Mode(game_mode) => game_mode.serialize(serializer),
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use serde::{Deserialize, Serialize, de::Visitor, ser::SerializeSeq};
+use serde::{Deserialize, Serialize, de::Visitor, ser::SerializeTuple};
 
-use crate::codec::{identifier::Identifier, var_int::VarInt};
+use crate::codec::{identifier::Identifier, prefixed_array::vec_for_length, var_int::VarInt};
 
 /// A minecraft registry ID set.
 #[derive(Debug)]
@@ -38,9 +38,10 @@ impl<'de> Deserialize<'de> for IdSet {
                         .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
                     Ok(IdSet::TagName(tag_name))
                 } else {
-                    let ids_len = id_set_type.0 as usize - 1;
-                    let mut ids = Vec::with_capacity(ids_len);
-                    for _ in 0..ids_len {
+                    // The type is the number of IDs + 1
+                    let ids_len = VarInt(id_set_type.0.saturating_sub(1));
+                    let mut ids = vec_for_length(ids_len)?;
+                    for _ in 0..ids_len.0 {
                         let id: VarInt = seq.next_element()?.ok_or_else(|| {
                             serde::de::Error::invalid_length(ids.len() + 1, &self)
                         })?;
@@ -60,15 +61,16 @@ impl Serialize for IdSet {
     where
         S: serde::Serializer,
     {
+        // A tuple, the length is part of the type prefix rather than a separate sequence length.
         match self {
             IdSet::TagName(tag_name) => {
-                let mut seq = serializer.serialize_seq(Some(2))?;
+                let mut seq = serializer.serialize_tuple(2)?;
                 seq.serialize_element(&VarInt(0))?;
                 seq.serialize_element(&tag_name.0)?;
                 seq.end()
             }
             IdSet::IDs(ids) => {
-                let mut seq = serializer.serialize_seq(Some(ids.len() + 1))?;
+                let mut seq = serializer.serialize_tuple(ids.len() + 1)?;
                 seq.serialize_element(&VarInt(ids.len() as i32 + 1))?;
                 for id in ids {
                     seq.serialize_element(id)?;
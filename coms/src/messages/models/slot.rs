@@ -1,7 +1,10 @@
-use nbt::nbt_types::NbtCompound;
+use nbt::{nbt_types::NbtCompound, tag_type::NbtTagType};
 use serde::{Deserialize, Serialize};
 
-use crate::{codec::var_int::VarInt, messages::models::text_component::TextComponent};
+use crate::{
+    codec::{network_nbt::NetworkNbt, prefixed_array::vec_for_length, var_int::VarInt},
+    messages::models::text_component::TextComponent,
+};
 
 /// A minecraft slot. Defines how an item is represented in an inventory of any kind.
 #[derive(Debug)]
@@ -56,7 +59,7 @@ impl<'de> Deserialize<'de> for Slot {
                     .next_element()?
                     .ok_or_else(|| serde::de::Error::custom("missing component-to-remove count"))?;
 
-                let mut components_to_add = Vec::with_capacity(num_to_add.0 as usize);
+                let mut components_to_add = vec_for_length(num_to_add)?;
                 for _ in 0..num_to_add.0 {
                     let component = seq
                         .next_element()?
@@ -64,7 +67,7 @@ impl<'de> Deserialize<'de> for Slot {
                     components_to_add.push(component);
                 }
 
-                let mut components_to_remove = Vec::with_capacity(num_to_remove.0 as usize);
+                let mut components_to_remove = vec_for_length(num_to_remove)?;
                 for _ in 0..num_to_remove.0 {
                     let component = seq
                         .next_element()?
@@ -161,7 +164,7 @@ pub enum ComponentData {
     /// 7: minecraft:item_model — Identifier
     ItemModel(String),
     /// 8: minecraft:lore — Prefixed Array of Text Component
-    Lore, // TODO
+    Lore(Vec<TextComponent>),
     /// 9: minecraft:rarity — VarInt Enum (0: common, 1: uncommon, 2: rare, 3: epic)
     Rarity(VarInt),
     /// 10: minecraft:enchantments
@@ -214,8 +217,8 @@ pub enum ComponentData {
     BlocksAttacks, // TODO
     /// 34: minecraft:stored_enchantments
     StoredEnchantments, // TODO
-    /// 35: minecraft:dyed_color
-    DyedColor, // TODO
+    /// 35: minecraft:dyed_color — Int (big-endian i32 RGB)
+    DyedColor(i32),
     /// 36: minecraft:map_color — Int (big-endian i32)
     MapColor(i32),
     /// 37: minecraft:map_id
@@ -371,19 +374,22 @@ impl<'de> Deserialize<'de> for ComponentData {
                         )))
                     };
                 }
-                macro_rules! nbt_de {
-                    ($name:literal) => {
-                        Err(serde::de::Error::custom(concat!(
-                            "ComponentData::",
-                            $name,
-                            " requires NBT deserialization, not yet implemented"
-                        )))
-                    };
+                macro_rules! nbt_compound {
+                    ($variant:expr) => {{
+                        let NetworkNbt(tag) = next!();
+                        match tag {
+                            NbtTagType::TagCompound(compound) => Ok($variant(compound)),
+                            tag => Err(serde::de::Error::custom(format!(
+                                "Expected an NBT compound for component data, got tag {}",
+                                tag.get_tag_id()
+                            ))),
+                        }
+                    }};
                 }
                 macro_rules! prefixed_slots {
                     ($variant:expr) => {{
                         let count: VarInt = next!();
-                        let mut slots = Vec::with_capacity(count.0 as usize);
+                        let mut slots = vec_for_length(count)?;
                         for _ in 0..count.0 {
                             slots.push(next!());
                         }
@@ -392,15 +398,22 @@ impl<'de> Deserialize<'de> for ComponentData {
                 }
 
                 match discriminant.0 {
-                    0 => nbt_de!("CustomData"),
+                    0 => nbt_compound!(ComponentData::CustomData),
                     1 => Ok(ComponentData::MaxStackSize(next!())),
                     2 => Ok(ComponentData::MaxDamage(next!())),
                     3 => Ok(ComponentData::Damage(next!())),
                     4 => Ok(ComponentData::Unbreakable),
-                    5 => todo_de!("CustomName"),
-                    6 => todo_de!("ItemName"),
+                    5 => Ok(ComponentData::CustomName(next!())),
+                    6 => Ok(ComponentData::ItemName(next!())),
                     7 => Ok(ComponentData::ItemModel(next!())),
-                    8 => todo_de!("Lore"),
+                    8 => {
+                        let count: VarInt = next!();
+                        let mut lines = vec_for_length(count)?;
+                        for _ in 0..count.0 {
+                            lines.push(next!());
+                        }
+                        Ok(ComponentData::Lore(lines))
+                    }
                     9 => Ok(ComponentData::Rarity(next!())),
                     10 => todo_de!("Enchantments"),
                     11 => todo_de!("CanPlaceOn"),
@@ -427,10 +440,10 @@ impl<'de> Deserialize<'de> for ComponentData {
                     32 => todo_de!("DeathProtection"),
                     33 => todo_de!("BlocksAttacks"),
                     34 => todo_de!("StoredEnchantments"),
-                    35 => todo_de!("DyedColor"),
+                    35 => Ok(ComponentData::DyedColor(next!())),
                     36 => Ok(ComponentData::MapColor(next!())),
                     37 => Ok(ComponentData::MapId(next!())),
-                    38 => nbt_de!("MapDecorations"),
+                    38 => nbt_compound!(ComponentData::MapDecorations),
                     39 => Ok(ComponentData::MapPostProcessing(next!())),
                     40 => prefixed_slots!(ComponentData::ChargedProjectiles),
                     41 => prefixed_slots!(ComponentData::BundleContents),
@@ -440,16 +453,16 @@ impl<'de> Deserialize<'de> for ComponentData {
                     45 => todo_de!("WritableBookContent"),
                     46 => todo_de!("WrittenBookContent"),
                     47 => todo_de!("Trim"),
-                    48 => nbt_de!("DebugStickState"),
-                    49 => nbt_de!("EntityData"),
-                    50 => nbt_de!("BucketEntityData"),
-                    51 => nbt_de!("BlockEntityData"),
+                    48 => nbt_compound!(ComponentData::DebugStickState),
+                    49 => nbt_compound!(ComponentData::EntityData),
+                    50 => nbt_compound!(ComponentData::BucketEntityData),
+                    51 => nbt_compound!(ComponentData::BlockEntityData),
                     52 => todo_de!("Instrument"),
                     53 => todo_de!("ProvidesTrimMaterial"),
                     54 => Ok(ComponentData::OminousBottleAmplifier(next!())),
                     55 => todo_de!("JukeboxPlayable"),
                     56 => Ok(ComponentData::ProvidesBannerPatterns(next!())),
-                    57 => nbt_de!("Recipes"),
+                    57 => nbt_compound!(ComponentData::Recipes),
                     58 => todo_de!("LodestoneTracker"),
                     59 => todo_de!("FireworkExplosion"),
                     60 => todo_de!("Fireworks"),
@@ -459,7 +472,7 @@ impl<'de> Deserialize<'de> for ComponentData {
                     64 => Ok(ComponentData::BaseColor(next!())),
                     65 => {
                         let count: VarInt = next!();
-                        let mut items = Vec::with_capacity(count.0 as usize);
+                        let mut items = vec_for_length(count)?;
                         for _ in 0..count.0 {
                             items.push(next!());
                         }
@@ -532,26 +545,29 @@ impl Serialize for ComponentData {
                 )))
             }};
         }
-        macro_rules! nbt_ser {
-            ($name:literal) => {{
-                Err(serde::ser::Error::custom(concat!(
-                    "ComponentData::",
-                    $name,
-                    " requires NBT serialization, not yet implemented"
-                )))
-            }};
+        // Serialize discriminant + an NBT compound payload.
+        macro_rules! nbt_compound {
+            ($disc:literal, $val:expr) => {{ one!($disc, &NetworkNbt(NbtTagType::TagCompound($val.clone()))) }};
         }
 
         match self {
-            ComponentData::CustomData(_) => nbt_ser!("CustomData"),
+            ComponentData::CustomData(v) => nbt_compound!(0, v),
             ComponentData::MaxStackSize(v) => one!(1, v),
             ComponentData::MaxDamage(v) => one!(2, v),
             ComponentData::Damage(v) => one!(3, v),
             ComponentData::Unbreakable => unit!(4),
-            ComponentData::CustomName(_) => todo_ser!("CustomName"),
-            ComponentData::ItemName(_) => todo_ser!("ItemName"),
+            ComponentData::CustomName(v) => one!(5, v),
+            ComponentData::ItemName(v) => one!(6, v),
             ComponentData::ItemModel(v) => one!(7, v),
-            ComponentData::Lore => todo_ser!("Lore"),
+            ComponentData::Lore(lines) => {
+                let mut t = serializer.serialize_tuple(2 + lines.len())?;
+                t.serialize_element(&VarInt(8))?;
+                t.serialize_element(&VarInt(lines.len() as i32))?;
+                for line in lines {
+                    t.serialize_element(line)?;
+                }
+                t.end()
+            }
             ComponentData::Rarity(v) => one!(9, v),
             ComponentData::Enchantments => todo_ser!("Enchantments"),
             ComponentData::CanPlaceOn => todo_ser!("CanPlaceOn"),
@@ -578,10 +594,10 @@ impl Serialize for ComponentData {
             ComponentData::DeathProtection => todo_ser!("DeathProtection"),
             ComponentData::BlocksAttacks => todo_ser!("BlocksAttacks"),
             ComponentData::StoredEnchantments => todo_ser!("StoredEnchantments"),
-            ComponentData::DyedColor => todo_ser!("DyedColor"),
+            ComponentData::DyedColor(v) => one!(35, v),
             ComponentData::MapColor(v) => one!(36, v),
             ComponentData::MapId(v) => one!(37, v),
-            ComponentData::MapDecorations(_) => nbt_ser!("MapDecorations"),
+            ComponentData::MapDecorations(v) => nbt_compound!(38, v),
             ComponentData::MapPostProcessing(v) => one!(39, v),
             ComponentData::ChargedProjectiles(slots) => {
                 let mut t = serializer.serialize_tuple(2 + slots.len())?;
@@ -607,16 +623,16 @@ impl Serialize for ComponentData {
             ComponentData::WritableBookContent => todo_ser!("WritableBookContent"),
             ComponentData::WrittenBookContent => todo_ser!("WrittenBookContent"),
             ComponentData::Trim => todo_ser!("Trim"),
-            ComponentData::DebugStickState(_) => nbt_ser!("DebugStickState"),
-            ComponentData::EntityData(_) => nbt_ser!("EntityData"),
-            ComponentData::BucketEntityData(_) => nbt_ser!("BucketEntityData"),
-            ComponentData::BlockEntityData(_) => nbt_ser!("BlockEntityData"),
+            ComponentData::DebugStickState(v) => nbt_compound!(48, v),
+            ComponentData::EntityData(v) => nbt_compound!(49, v),
+            ComponentData::BucketEntityData(v) => nbt_compound!(50, v),
+            ComponentData::BlockEntityData(v) => nbt_compound!(51, v),
             ComponentData::Instrument => todo_ser!("Instrument"),
             ComponentData::ProvidesTrimMaterial => todo_ser!("ProvidesTrimMaterial"),
             ComponentData::OminousBottleAmplifier(v) => one!(54, v),
             ComponentData::JukeboxPlayable => todo_ser!("JukeboxPlayable"),
             ComponentData::ProvidesBannerPatterns(v) => one!(56, v),
-            ComponentData::Recipes(_) => nbt_ser!("Recipes"),
+            ComponentData::Recipes(v) => nbt_compound!(57, v),
             ComponentData::LodestoneTracker => todo_ser!("LodestoneTracker"),
             ComponentData::FireworkExplosion => todo_ser!("FireworkExplosion"),
             ComponentData::Fireworks => todo_ser!("Fireworks"),
@@ -674,3 +690,29 @@ impl Serialize for ComponentData {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::ser::deserializer::Deserializer;
+
+    fn deserialize(data: &[u8]) -> Result<Slot, crate::ser::ReadingError> {
+        Slot::deserialize(&mut Deserializer::new(Cursor::new(data.to_vec())))
+    }
+
+    #[test]
+    fn test_untrusted_counts() {
+        // One stone with a single Lore component, followed by the lore line count
+        let lore = |count: &[u8]| [&[0x01, 0x01, 0x01, 0x00, 0x08][..], count].concat();
+
+        // A negative count is rejected instead of overflowing the capacity
+        assert!(deserialize(&lore(&[0xFF, 0xFF, 0xFF, 0xFF, 0x0F])).is_err());
+        // A huge count runs out of data instead of allocating gigabytes up front
+        assert!(deserialize(&lore(&[0xFF, 0xFF, 0xFF, 0xFF, 0x07])).is_err());
+
+        // Negative component counts are rejected as well
+        assert!(deserialize(&[0x01, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 0x00]).is_err());
+    }
+}
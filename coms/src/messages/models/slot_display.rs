@@ -7,7 +7,7 @@ use serde::{
 };
 
 use crate::{
-    codec::{identifier::Identifier, var_int::VarInt},
+    codec::{identifier::Identifier, prefixed_array::vec_for_length, var_int::VarInt},
     messages::models::slot::Slot,
 };
 
@@ -151,7 +151,7 @@ impl<'de> Visitor<'de> for SlotDisplayVisitor {
                 let count: VarInt = seq.next_element()?.ok_or_else(|| {
                     de::Error::custom("missing option count for minecraft:composite")
                 })?;
-                let mut options = Vec::with_capacity(count.0 as usize);
+                let mut options = vec_for_length(count)?;
                 for _ in 0..count.0 {
                     let option: SlotDisplay = seq.next_element()?.ok_or_else(|| {
                         de::Error::custom("missing option for minecraft:composite")
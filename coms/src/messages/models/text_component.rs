@@ -1,11 +1,10 @@
-use std::fmt;
-
 use nbt::{
-    nbt_named_tag::NbtNamedTag,
     nbt_types::{NbtCompound, NbtString},
     tag_type::NbtTagType,
 };
-use serde::de::{self, Visitor};
+use serde::{Deserialize, Serialize, de};
+
+use crate::codec::network_nbt::NetworkNbt;
 
 /// A minecraft Text Component
 #[derive(Debug)]
@@ -16,37 +15,26 @@ pub enum TextComponent {
     Compound(NbtCompound),
 }
 
-struct TextComponentVisitor;
-
-impl<'de> Visitor<'de> for TextComponentVisitor {
-    type Value = TextComponent;
+impl Serialize for TextComponent {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let tag = match self {
+            TextComponent::Literal(s) => NbtTagType::TagString(s.clone()),
+            TextComponent::Compound(c) => NbtTagType::TagCompound(c.clone()),
+        };
 
-    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "an NBT string or compound tag")
-    }
-
-    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
-        Ok(TextComponent::Literal(NbtString(v.to_owned())))
-    }
-
-    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
-        Ok(TextComponent::Literal(NbtString(v)))
-    }
-
-    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
-        let mut entries = Vec::new();
-        while let Some((k, v)) = map.next_entry::<String, NbtTagType>()? {
-            entries.push(NbtNamedTag {
-                name: NbtString(k),
-                payload: v,
-            });
-        }
-        Ok(TextComponent::Compound(NbtCompound(entries)))
+        NetworkNbt(tag).serialize(serializer)
     }
 }
 
-impl<'de> serde::Deserialize<'de> for TextComponent {
+impl<'de> Deserialize<'de> for TextComponent {
     fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        deserializer.deserialize_any(TextComponentVisitor)
+        match NetworkNbt::deserialize(deserializer)?.0 {
+            NbtTagType::TagString(s) => Ok(TextComponent::Literal(s)),
+            NbtTagType::TagCompound(c) => Ok(TextComponent::Compound(c)),
+            t => Err(de::Error::custom(format!(
+                "Expected an NBT string or compound tag for text component, got tag {}",
+                t.get_tag_id()
+            ))),
+        }
     }
 }
@@ -0,0 +1,598 @@
+use crate::{
+    client_state::ClientState,
+    messages::{McPacket, McPacketError, McPacketRead, McPacketWrite, clientbound, serverbound},
+    packet_reader::RawPacket,
+    protocol_version::ProtocolVersion,
+};
+
+/// The direction a packet travels in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PacketDirection {
+    /// Sent by the client to the server.
+    Serverbound,
+    /// Sent by the server to the client.
+    Clientbound,
+}
+
+/// A single packet known to the [REGISTRY].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PacketEntry {
+    /// The state this packet is sent in.
+    pub state: ClientState,
+    /// The direction this packet is sent in.
+    pub direction: PacketDirection,
+    /// The ID of the packet within its state & direction.
+    pub id: i32,
+    /// Name of the packet, same as its variant in [ServerboundPacket] / [ClientboundPacket].
+    pub name: &'static str,
+}
+
+/// Find the packet registered for the provided [state], [direction] & [id].
+pub fn lookup(
+    state: ClientState,
+    direction: PacketDirection,
+    id: i32,
+) -> Option<&'static PacketEntry> {
+    REGISTRY
+        .iter()
+        .find(|entry| entry.state == state && entry.direction == direction && entry.id == id)
+}
+
+/// Generates the packet enums & the [REGISTRY] from a single list of packets.
+///
+/// Every packet names its constant in [crate::packet_ids], the build fails if the ID of the packet
+/// disagrees with the vanilla packet report.
+macro_rules! packets {
+    ($(
+        $(#[$meta:meta])*
+        $enum:ident => $direction:ident($direction_mod:ident) {
+            $($state:ident($state_mod:ident) {
+                $($variant:ident($ty:ty) = $report_name:ident),* $(,)?
+            })*
+        }
+    )*) => {
+        /// Every packet known to this crate.
+        pub const REGISTRY: &[PacketEntry] = &[$($($(
+            PacketEntry {
+                state: ClientState::$state,
+                direction: PacketDirection::$direction,
+                id: <$ty as McPacket>::PACKET_ID,
+                name: stringify!($variant),
+            },
+        )*)*)*];
+
+        $($($(
+            #[cfg(packet_report)]
+            const _: () = assert!(
+                <$ty as McPacket>::PACKET_ID
+                    == crate::packet_ids::$state_mod::$direction_mod::$report_name,
+                concat!("ID of ", stringify!($variant), " disagrees with the packet report"),
+            );
+        )*)*)*
+
+        $(
+            $(#[$meta])*
+            #[derive(Debug)]
+            pub enum $enum {
+                $($(
+                    #[doc = concat!("The `", stringify!($variant), "` packet.")]
+                    $variant($ty),
+                )*)*
+            }
+
+            impl $enum {
+                /// Decode the [RawPacket] received whilst in [state], using [ProtocolVersion::LATEST].
+                pub fn decode(state: ClientState, packet: RawPacket) -> Result<Self, McPacketError> {
+                    Self::decode_versioned(ProtocolVersion::LATEST, state, packet)
+                }
+
+                /// Decode the [RawPacket] received whilst in [state] from a connection using [version].
+                pub fn decode_versioned(
+                    version: ProtocolVersion,
+                    state: ClientState,
+                    packet: RawPacket,
+                ) -> Result<Self, McPacketError> {
+                    let id = version.latest_packet_id(state, PacketDirection::$direction, packet.id);
+                    $($(
+                        if state == ClientState::$state && id == Some(<$ty as McPacket>::PACKET_ID) {
+                            return Ok(Self::$variant(<$ty as McPacketRead>::read_versioned(packet, version)?));
+                        }
+                    )*)*
+
+                    Err(McPacketError::UnknownPacket {
+                        state,
+                        direction: PacketDirection::$direction,
+                        id: packet.id,
+                    })
+                }
+
+                /// Encode this packet into a [RawPacket], using [ProtocolVersion::LATEST].
+                pub fn encode(&self) -> Result<RawPacket, McPacketError> {
+                    self.encode_versioned(ProtocolVersion::LATEST)
+                }
+
+                /// Encode this packet into a [RawPacket] for a connection using [version].
+                pub fn encode_versioned(&self, version: ProtocolVersion) -> Result<RawPacket, McPacketError> {
+                    let data = match self {
+                        $($(Self::$variant(packet) => McPacketWrite::write_versioned(packet, version)?,)*)*
+                    };
+                    let id = version.packet_id(self.state(), PacketDirection::$direction, self.id());
+
+                    Ok(RawPacket { id, data })
+                }
+
+                /// The state this packet is sent in.
+                pub fn state(&self) -> ClientState {
+                    match self {
+                        $($(Self::$variant(_) => ClientState::$state,)*)*
+                    }
+                }
+
+                /// The ID of this packet in [ProtocolVersion::LATEST].
+                pub fn id(&self) -> i32 {
+                    match self {
+                        $($(Self::$variant(_) => <$ty as McPacket>::PACKET_ID,)*)*
+                    }
+                }
+
+                /// The name of this packet.
+                pub fn name(&self) -> &'static str {
+                    match self {
+                        $($(Self::$variant(_) => stringify!($variant),)*)*
+                    }
+                }
+            }
+
+            $($(
+                impl From<$ty> for $enum {
+                    fn from(packet: $ty) -> Self {
+                        Self::$variant(packet)
+                    }
+                }
+            )*)*
+        )*
+    };
+}
+
+packets! {
+    /// A packet sent by the client to the server.
+    ServerboundPacket => Serverbound(serverbound) {
+        Handshaking(handshake) {
+            Handshake(serverbound::handshaking::handshake::Handshake) = INTENTION,
+        }
+        Status(status) {
+            StatusRequest(serverbound::status::status_request::StatusRequest) = STATUS_REQUEST,
+            PingRequest(serverbound::status::ping_request::PingRequest) = PING_REQUEST,
+        }
+        Login(login) {
+            LoginStart(serverbound::login::login_start::LoginStart) = HELLO,
+            EncryptionResponse(serverbound::login::encryption_response::EncryptionResponse) = KEY,
+            LoginPluginResponse(serverbound::login::login_plugin_response::LoginPluginResponse) = CUSTOM_QUERY_ANSWER,
+            LoginAcknowledged(serverbound::login::login_acknowledged::LoginAcknowledged) = LOGIN_ACKNOWLEDGED,
+            CookieResponse(serverbound::login::cookie_response::CookieResponse) = COOKIE_RESPONSE,
+        }
+        Configuration(configuration) {
+            ClientInformation(serverbound::configuration::client_information::ClientInformation) = CLIENT_INFORMATION,
+            ServerboundPluginMessage(serverbound::configuration::serverbound_plugin_message::ServerboundPluginMessage) = CUSTOM_PAYLOAD,
+            AcknowledgeFinishConfiguration(serverbound::configuration::acknowledge_finish_configuration::AcknowledgeFinishConfiguration) = FINISH_CONFIGURATION,
+            ServerboundKnownPacks(serverbound::configuration::serverbound_known_packs::ServerboundKnownPacks) = SELECT_KNOWN_PACKS,
+        }
+    }
+
+    /// A packet sent by the server to the client.
+    ClientboundPacket => Clientbound(clientbound) {
+        Status(status) {
+            StatusResponse(clientbound::status::status_response::StatusResponse) = STATUS_RESPONSE,
+            PongResponse(clientbound::status::pong_response::PongResponse) = PONG_RESPONSE,
+        }
+        Login(login) {
+            Disconnect(clientbound::login::disconnect::Disconnect) = LOGIN_DISCONNECT,
+            EncryptionRequest(clientbound::login::encryption_request::EncryptionRequest) = HELLO,
+            LoginSuccess(clientbound::login::login_success::LoginSuccess) = LOGIN_FINISHED,
+            SetCompression(clientbound::login::set_compression::SetCompression) = LOGIN_COMPRESSION,
+            LoginPluginRequest(clientbound::login::login_plugin_request::LoginPluginRequest) = CUSTOM_QUERY,
+            CookieRequest(clientbound::login::cookie_request::CookieRequest) = COOKIE_REQUEST,
+        }
+        Configuration(configuration) {
+            ClientboundPluginMessage(clientbound::configuration::clientbound_plugin_message::ClientboundPluginMessage) = CUSTOM_PAYLOAD,
+            FinishConfiguration(clientbound::configuration::finish_configuration::FinishConfiguration) = FINISH_CONFIGURATION,
+            ClientboundKeepAlive(clientbound::configuration::clientbound_keep_alive::ClientboundKeepAlive) = KEEP_ALIVE,
+            RegistryData(clientbound::configuration::registry_data::RegistryData) = REGISTRY_DATA,
+            FeatureFlags(clientbound::configuration::feature_flags::FeatureFlags) = UPDATE_ENABLED_FEATURES,
+            UpdateTags(clientbound::configuration::update_tags::UpdateTags) = UPDATE_TAGS,
+            ClientboundKnownPacks(clientbound::configuration::clientbound_known_packs::ClientboundKnownPacks) = SELECT_KNOWN_PACKS,
+        }
+        Play(play) {
+            ChangeDifficulty(clientbound::play::change_difficulty::ChangeDifficulty) = CHANGE_DIFFICULTY,
+            Login(clientbound::play::login::Login) = LOGIN,
+            PlayerAbilities(clientbound::play::player_abilities::PlayerAbilities) = PLAYER_ABILITIES,
+            SetHeldItem(clientbound::play::set_held_item::SetHeldItem) = SET_HELD_SLOT,
+            UpdateRecipes(clientbound::play::update_recipes::UpdateRecipes) = UPDATE_RECIPES,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, fs, path::Path};
+
+    use super::*;
+    use crate::{
+        codec::{identifier::Identifier, var_int::VarInt},
+        messages::{
+            clientbound::{
+                login::set_compression::SetCompression, play::set_held_item::SetHeldItem,
+            },
+            serverbound::{
+                configuration::serverbound_plugin_message::ServerboundPluginMessage,
+                login::login_acknowledged::LoginAcknowledged,
+            },
+        },
+        ser::NetworkWriteExt,
+    };
+
+    #[test]
+    fn test_registry_is_unique() {
+        let mut seen = HashSet::new();
+        for entry in REGISTRY {
+            assert!(
+                seen.insert((entry.state, entry.direction, entry.id)),
+                "{entry:?} is registered twice"
+            );
+        }
+
+        let entry = lookup(ClientState::Login, PacketDirection::Clientbound, 0x03).unwrap();
+        assert_eq!(entry.name, "SetCompression");
+        assert!(lookup(ClientState::Play, PacketDirection::Serverbound, 0x03).is_none());
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let packet = ClientboundPacket::from(SetCompression {
+            threshold: VarInt(256),
+        });
+        let raw = packet.encode().unwrap();
+        assert_eq!(raw.id, 0x03);
+        assert_eq!(raw.data, vec![0x80, 0x02]);
+
+        let ClientboundPacket::SetCompression(decoded) =
+            ClientboundPacket::decode(ClientState::Login, raw).unwrap()
+        else {
+            panic!("Expected SetCompression");
+        };
+        assert_eq!(decoded.threshold.0, 256);
+
+        let packet = ServerboundPacket::from(ServerboundPluginMessage {
+            channel: Identifier("minecraft:brand".to_string()),
+            data: b"\x07vanilla".to_vec(),
+        });
+        let raw = packet.encode().unwrap();
+        let ServerboundPacket::ServerboundPluginMessage(decoded) =
+            ServerboundPacket::decode(ClientState::Configuration, raw).unwrap()
+        else {
+            panic!("Expected ServerboundPluginMessage");
+        };
+        assert_eq!(decoded.channel.0, "minecraft:brand");
+        assert_eq!(decoded.data, b"\x07vanilla");
+
+        let raw = RawPacket {
+            id: 0x03,
+            data: Vec::new(),
+        };
+        assert!(matches!(
+            ServerboundPacket::decode(ClientState::Login, raw.clone()),
+            Ok(ServerboundPacket::LoginAcknowledged(_))
+        ));
+        assert!(matches!(
+            ServerboundPacket::decode(ClientState::Play, raw),
+            Err(McPacketError::UnknownPacket {
+                state: ClientState::Play,
+                direction: PacketDirection::Serverbound,
+                id: 0x03,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_versioned_ids() {
+        let packet = ClientboundPacket::from(SetHeldItem { slot: VarInt(4) });
+        let raw = packet.encode_versioned(ProtocolVersion::V1_21_5).unwrap();
+        assert_eq!(raw.id, 0x62);
+        assert_eq!(packet.encode().unwrap().id, 0x67);
+
+        assert!(matches!(
+            ClientboundPacket::decode_versioned(
+                ProtocolVersion::V1_21_5,
+                ClientState::Play,
+                raw.clone()
+            ),
+            Ok(ClientboundPacket::SetHeldItem(SetHeldItem {
+                slot: VarInt(4)
+            }))
+        ));
+        assert!(ClientboundPacket::decode(ClientState::Play, raw).is_err());
+
+        // The latest ID of SetHeldItem is another packet in 1.21.5.
+        let raw = RawPacket {
+            id: 0x67,
+            data: vec![0x04],
+        };
+        assert!(matches!(
+            ClientboundPacket::decode_versioned(ProtocolVersion::V1_21_5, ClientState::Play, raw),
+            Err(McPacketError::UnknownPacket { id: 0x67, .. })
+        ));
+
+        // IDs outside of play are the same in every version.
+        let raw = ServerboundPacket::from(LoginAcknowledged)
+            .encode_versioned(ProtocolVersion::V1_21_5)
+            .unwrap();
+        assert_eq!(raw.id, 0x03);
+    }
+
+    fn var_int(value: i32) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.write_var_int(&VarInt(value)).unwrap();
+        data
+    }
+
+    fn string(value: &str) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.write_string(value).unwrap();
+        data
+    }
+
+    /// Network NBT string tag.
+    fn nbt_string(value: &str) -> Vec<u8> {
+        let len = (value.len() as u16).to_be_bytes();
+        [&[0x08][..], &len, value.as_bytes()].concat()
+    }
+
+    /// Network NBT compound containing a single int.
+    fn nbt_compound(name: &str, value: i32) -> Vec<u8> {
+        let len = (name.len() as u16).to_be_bytes();
+        [
+            &[0x0A, 0x03][..],
+            &len,
+            name.as_bytes(),
+            &value.to_be_bytes(),
+            &[0x00],
+        ]
+        .concat()
+    }
+
+    const UUID: [u8; 16] = [
+        0x06, 0x9a, 0x79, 0xf4, 0x44, 0xe9, 0x47, 0x26, 0xa5, 0xbe, 0xfc, 0xa9, 0x0e, 0x38, 0xaa,
+        0xf5,
+    ];
+
+    /// Round trip [data] of the packet called [name] & check that the bytes didn't change. If the proxy
+    /// captured the packet from a vanilla connection (see `MC_PROXY_CAPTURE_DIR`) & the capture was
+    /// copied to `coms/captures/<direction>/<name>.bin`, the capture is round tripped as well.
+    fn assert_round_trip<P>(
+        direction: PacketDirection,
+        name: &str,
+        data: Vec<u8>,
+        decode: impl Fn(ClientState, RawPacket) -> Result<P, McPacketError>,
+        encode: impl Fn(&P) -> Result<RawPacket, McPacketError>,
+    ) {
+        let entry = REGISTRY
+            .iter()
+            .find(|entry| entry.direction == direction && entry.name == name)
+            .unwrap_or_else(|| panic!("{name} isn't registered"));
+
+        let capture = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("captures")
+            .join(match direction {
+                PacketDirection::Serverbound => "serverbound",
+                PacketDirection::Clientbound => "clientbound",
+            })
+            .join(format!("{name}.bin"));
+        let captured = fs::read(&capture).ok();
+
+        for (source, data) in [("fixture", Some(data)), ("capture", captured)] {
+            let Some(data) = data else {
+                continue;
+            };
+            let raw = RawPacket {
+                id: entry.id,
+                data: data.clone(),
+            };
+
+            let packet = decode(entry.state, raw)
+                .unwrap_or_else(|err| panic!("Failed to decode the {source}: {err:?}"));
+            let encoded = encode(&packet)
+                .unwrap_or_else(|err| panic!("Failed to encode the {source}: {err:?}"));
+
+            assert_eq!(encoded.id, entry.id);
+            assert_eq!(
+                encoded.data, data,
+                "The {source} changed after a round trip"
+            );
+        }
+    }
+
+    /// Generates a module per direction with a round-trip test per packet, named after the packet.
+    macro_rules! round_trip_tests {
+        ($(
+            $(#[$meta:meta])*
+            $module:ident($direction:ident, $packet:ident) {
+                $($name:ident => $data:expr,)*
+            }
+        )*) => {$(
+            $(#[$meta])*
+            #[allow(non_snake_case)]
+            mod $module {
+                use super::*;
+
+                #[test]
+                fn test_every_packet_is_tested() {
+                    let tested = [$(stringify!($name)),*];
+                    for entry in REGISTRY
+                        .iter()
+                        .filter(|entry| entry.direction == PacketDirection::$direction)
+                    {
+                        assert!(tested.contains(&entry.name), "No round-trip test for {}", entry.name);
+                    }
+                }
+
+                $(
+                    #[test]
+                    fn $name() {
+                        assert_round_trip(
+                            PacketDirection::$direction,
+                            stringify!($name),
+                            $data,
+                            $packet::decode,
+                            $packet::encode,
+                        );
+                    }
+                )*
+            }
+        )*};
+    }
+
+    fn status_json() -> Vec<u8> {
+        string(
+            r#"{"version":{"name":"1.21.9","protocol":773},"players":{"max":20,"online":1},"description":"A Minecraft Server","favicon":"data:image/png;base64,AA==","enforcesSecureChat":true}"#,
+        )
+    }
+
+    fn death_location() -> Vec<u8> {
+        [
+            vec![0x01],
+            string("minecraft:overworld"),
+            vec![0x46, 0x07, 0x63, 0x2C, 0x15, 0xB4, 0x83, 0x3F],
+        ]
+        .concat()
+    }
+
+    /// Two stone with a custom name & a removed component, followed by a container slot.
+    fn item_stack() -> Vec<u8> {
+        [
+            vec![0x02, 0x05, 0x03, 0x01],
+            var_int(5),
+            nbt_string("Stone"),
+            var_int(0),
+            nbt_compound("level", 7),
+            vec![0x08, 0x01],
+            nbt_string("Ham"),
+            vec![0x0A],
+        ]
+        .concat()
+    }
+
+    round_trip_tests! {
+        /// Data of every serverbound packet, hand-built from the protocol documentation for 1.21.9.
+        serverbound(Serverbound, ServerboundPacket) {
+            Handshake => [var_int(773), string("localhost"), vec![0x63, 0xDD, 0x02]].concat(),
+            StatusRequest => vec![],
+            PingRequest => vec![0x00, 0x00, 0x01, 0x9A, 0x2B, 0x3C, 0x4D, 0x5E],
+            LoginStart => [string("Pepe"), UUID.to_vec()].concat(),
+            EncryptionResponse => vec![0x03, 0x01, 0x02, 0x03, 0x03, b'h', b'a', b'm'],
+            LoginPluginResponse => vec![0x05, 0x01, 0xDE, 0xAD],
+            LoginAcknowledged => vec![],
+            CookieResponse => [string("minecraft:cookie"), vec![0x01, 0x02, 0xAB, 0xCD]].concat(),
+            ClientInformation => [
+                string("en_us"),
+                vec![0x0C, 0x00, 0x01, 0x7F, 0x01, 0x00, 0x01, 0x00],
+            ]
+            .concat(),
+            ServerboundPluginMessage => [string("minecraft:brand"), string("vanilla")].concat(),
+            AcknowledgeFinishConfiguration => vec![],
+            ServerboundKnownPacks => [
+                var_int(1),
+                string("minecraft"),
+                string("core"),
+                string("1.21.9"),
+            ]
+            .concat(),
+        }
+
+        /// Data of every clientbound packet, hand-built from the protocol documentation for 1.21.9.
+        clientbound(Clientbound, ClientboundPacket) {
+            StatusResponse => status_json(),
+            PongResponse => vec![0x00, 0x00, 0x01, 0x9A, 0x2B, 0x3C, 0x4D, 0x5E],
+            Disconnect => string(r#"{"text":"Bye"}"#),
+            EncryptionRequest => [
+                string(""),
+                vec![0x03, 0x01, 0x02, 0x03, 0x03, b'h', b'a', b'm', 0x01],
+            ]
+            .concat(),
+            LoginSuccess => [
+                UUID.to_vec(),
+                string("Pepe"),
+                var_int(1),
+                string("textures"),
+                string("e30="),
+                vec![0x01],
+                string("c2ln"),
+            ]
+            .concat(),
+            SetCompression => vec![0x80, 0x02],
+            LoginPluginRequest => [var_int(7), string("velocity:player_info"), vec![0x01]].concat(),
+            CookieRequest => string("minecraft:cookie"),
+            ClientboundPluginMessage => [string("minecraft:brand"), string("hamcrafters")].concat(),
+            FinishConfiguration => vec![],
+            ClientboundKeepAlive => vec![0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0xF0],
+            RegistryData => [
+                string("minecraft:dimension_type"),
+                var_int(2),
+                string("minecraft:overworld"),
+                vec![0x01],
+                nbt_compound("min_y", -64),
+                string("minecraft:the_nether"),
+                vec![0x00],
+            ]
+            .concat(),
+            FeatureFlags => [var_int(1), string("minecraft:vanilla")].concat(),
+            UpdateTags => [
+                var_int(1),
+                string("minecraft:block"),
+                var_int(1),
+                string("minecraft:logs"),
+                vec![0x02, 0x0A, 0x0B],
+            ]
+            .concat(),
+            ClientboundKnownPacks => [
+                var_int(1),
+                string("minecraft"),
+                string("core"),
+                string("1.21.9"),
+            ]
+            .concat(),
+            ChangeDifficulty => vec![0x02, 0x00],
+            Login => [
+                vec![0x00, 0x00, 0x00, 0x2A, 0x00, 0x01],
+                string("minecraft:overworld"),
+                vec![0x14, 0x0A, 0x0A, 0x00, 0x01, 0x00, 0x00],
+                string("minecraft:overworld"),
+                vec![0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0xF0],
+                vec![0x01, 0xFF, 0x00, 0x01],
+                death_location(),
+                vec![0x00, 0x3F, 0x00],
+            ]
+            .concat(),
+            PlayerAbilities => [
+                vec![0x0D],
+                0.05f32.to_be_bytes().to_vec(),
+                0.1f32.to_be_bytes().to_vec(),
+            ]
+            .concat(),
+            SetHeldItem => vec![0x04],
+            UpdateRecipes => [
+                var_int(1),
+                string("minecraft:furnace_input"),
+                vec![0x02, 0x01, 0x02],
+                var_int(2),
+                // Ingredients by tag, displayed as an item.
+                var_int(0),
+                string("minecraft:logs"),
+                vec![0x02, 0x05],
+                // Ingredients by ID, displayed as an item stack with components.
+                vec![0x03, 0x01, 0x02, 0x03],
+                item_stack(),
+            ]
+            .concat(),
+        }
+    }
+}
@@ -0,0 +1,8 @@
+use crate::McPacket;
+use mc_packet_macros::mc_packet;
+use serde::{Deserialize, Serialize};
+
+/// Acknowledges the server's finish configuration, the client switches to the play state after this.
+#[derive(Debug, Serialize, Deserialize)]
+#[mc_packet(0x03)]
+pub struct AcknowledgeFinishConfiguration;
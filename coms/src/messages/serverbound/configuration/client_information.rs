@@ -1,9 +1,9 @@
 use crate::{McPacket, codec::var_int::VarInt};
 use mc_packet_macros::mc_packet;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Information about the clients settings.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[mc_packet(0x0)]
 #[serde(rename_all = "PascalCase")]
 pub struct ClientInformation {
@@ -1,3 +1,5 @@
+/// Acknowledge finish configuration message.
+pub mod acknowledge_finish_configuration;
 /// Client configuration information.
 pub mod client_information;
 /// Serverbound known packs message.
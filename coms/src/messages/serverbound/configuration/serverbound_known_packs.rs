@@ -2,11 +2,11 @@ use crate::{
     McPacket, codec::prefixed_array::PrefixedArray, messages::models::data_pack::DataPack,
 };
 use mc_packet_macros::mc_packet;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-/// Serverboudn known packs message.
-#[derive(Debug, Deserialize)]
-#[mc_packet(0x0E)]
+/// Serverbound known packs message.
+#[derive(Debug, Deserialize, Serialize)]
+#[mc_packet(0x07)]
 pub struct ServerboundKnownPacks {
     known_packs: PrefixedArray<DataPack>,
 }
@@ -3,7 +3,8 @@ use std::io::{self, Cursor, Read};
 use crate::{
     McPacket,
     codec::{identifier::Identifier, var_int::VarInt},
-    messages::{McPacketError, McPacketRead},
+    messages::{McPacketError, McPacketRead, McPacketWrite},
+    ser::NetworkWriteExt,
 };
 use mc_packet_macros::mc_packet;
 use serde::Deserialize;
@@ -32,3 +33,13 @@ impl McPacketRead for ServerboundPluginMessage {
         Ok(Self { channel, data })
     }
 }
+
+impl McPacketWrite for ServerboundPluginMessage {
+    fn write(&self) -> Result<Vec<u8>, McPacketError> {
+        let mut data = Vec::new();
+        data.write_string(&self.channel.0)?;
+        data.extend_from_slice(&self.data);
+
+        Ok(data)
+    }
+}
@@ -1,13 +1,49 @@
-use crate::McPacket;
-use mc_packet_macros::mc_packet;
-use serde::Deserialize;
+use std::io::{Cursor, Read};
 
-use crate::codec::{prefixed_optional::PrefixedOptional, var_int::VarInt};
+use crate::{
+    McPacket,
+    codec::var_int::VarInt,
+    messages::{McPacketError, McPacketRead, McPacketWrite},
+    ser::{NetworkReadExt, NetworkWriteExt},
+};
+use mc_packet_macros::mc_packet;
 
 /// Login plugin response message
-#[derive(Debug, Deserialize)]
+#[derive(Debug)]
 #[mc_packet(0x02)]
 pub struct LoginPluginResponse {
     message_id: VarInt,
-    data: PrefixedOptional<Vec<u8>>,
+    data: Option<Vec<u8>>, // length must be infered from the packet length
+}
+
+impl McPacketRead for LoginPluginResponse {
+    type Output = Self;
+
+    fn read(raw_packet: crate::packet_reader::RawPacket) -> Result<Self::Output, McPacketError> {
+        let mut cursor = Cursor::new(raw_packet.data);
+        let message_id = VarInt::decode(&mut cursor)?;
+
+        let data = if cursor.get_bool()? {
+            let mut data = Vec::new();
+            cursor.read_to_end(&mut data)?;
+            Some(data)
+        } else {
+            None
+        };
+
+        Ok(Self { message_id, data })
+    }
+}
+
+impl McPacketWrite for LoginPluginResponse {
+    fn write(&self) -> Result<Vec<u8>, McPacketError> {
+        let mut data = Vec::new();
+        data.write_var_int(&self.message_id)?;
+        data.write_bool(self.data.is_some())?;
+        if let Some(payload) = &self.data {
+            data.extend_from_slice(payload);
+        }
+
+        Ok(data)
+    }
 }
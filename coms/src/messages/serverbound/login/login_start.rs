@@ -1,9 +1,9 @@
 use crate::{McPacket, codec::mc_uuid::McUuid};
 use mc_packet_macros::mc_packet;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Login start message
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[mc_packet(0x0)]
 pub struct LoginStart {
     name: String,
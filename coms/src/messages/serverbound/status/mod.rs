@@ -1,2 +1,4 @@
 /// A ping request packet.
 pub mod ping_request;
+/// A status request packet.
+pub mod status_request;
@@ -0,0 +1,8 @@
+use crate::McPacket;
+use mc_packet_macros::mc_packet;
+use serde::{Deserialize, Serialize};
+
+/// A Minecraft status request packet, asks the server for its [StatusResponse](crate::messages::clientbound::status::status_response::StatusResponse).
+#[derive(Debug, Serialize, Deserialize)]
+#[mc_packet(0x00)]
+pub struct StatusRequest;
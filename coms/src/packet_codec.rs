@@ -0,0 +1,194 @@
+use bytes::{Buf, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::{
+    codec::var_int::VarInt,
+    encryption::{StreamDecryptor, StreamEncryptor},
+    packet_reader::{
+        MAX_PACKET_LENGTH, PacketReadError, RawPacket, check_packet_length, parse_frame,
+    },
+    packet_writer::{PacketWriteError, write_frame},
+    ser::ReadingError,
+};
+
+/// A [Decoder] & [Encoder] of length prefixed Minecraft packets, e.g. for
+/// [Framed](tokio_util::codec::Framed) streams & sinks.
+///
+/// Encryption & compression can be enabled at any point, e.g. through
+/// [Framed::codec_mut](tokio_util::codec::Framed::codec_mut), and apply to every packet decoded or
+/// encoded afterwards. Like [NetworkWriter](crate::packet_writer::NetworkWriter), encoding a packet
+/// doesn't flush, packets are written in batches whenever the sink is flushed.
+#[derive(Default)]
+pub struct PacketCodec {
+    decryptor: Option<StreamDecryptor>,
+    encryptor: Option<StreamEncryptor>,
+    /// How many bytes at the start of the decode buffer have already been decrypted.
+    decrypted: usize,
+    compression_threshold: Option<usize>,
+    /// Reused to compress packets before their Packet Length is known.
+    compressed: Vec<u8>,
+}
+
+impl PacketCodec {
+    /// Creates a [PacketCodec] without encryption or compression.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable encryption of the packets after this point, can only be enabled, not disabled.
+    pub fn enable_encryption(&mut self, key: &[u8; 16]) {
+        log::info!("Enabling encryption for codec");
+        self.decryptor = Some(StreamDecryptor::new(key));
+        self.encryptor = Some(StreamEncryptor::new(key));
+        // Whatever is still buffered arrived after the last packet, so it's encrypted.
+        self.decrypted = 0;
+    }
+
+    /// Enable compression of packets of at least [threshold] bytes, a negative [threshold]
+    /// disables compression again, same as the SetCompression packet.
+    pub fn enable_compression(&mut self, threshold: i32) {
+        log::info!("Enabling compression for codec with threshold {threshold}");
+        self.compression_threshold = usize::try_from(threshold).ok();
+    }
+}
+
+impl Decoder for PacketCodec {
+    type Item = RawPacket;
+    type Error = PacketReadError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if let Some(decryptor) = self.decryptor.as_mut() {
+            // Our block size is 1 byte, so decrypting whatever arrived so far is always safe.
+            decryptor.decrypt(&mut src[self.decrypted..]);
+            self.decrypted = src.len();
+        }
+
+        let mut length_reader = &src[..];
+        let packet_len = match VarInt::decode(&mut length_reader) {
+            Ok(packet_len) => check_packet_length(packet_len.0, MAX_PACKET_LENGTH)?,
+            Err(ReadingError::Incomplete(_)) => return Ok(None),
+            Err(err) => return Err(PacketReadError::LengthParseError(err.to_string())),
+        };
+
+        let header_len = src.len() - length_reader.len();
+        if length_reader.len() < packet_len {
+            src.reserve(header_len + packet_len - src.len());
+            return Ok(None);
+        }
+
+        src.advance(header_len);
+        let packet_buf = src.split_to(packet_len);
+        self.decrypted = self.decrypted.saturating_sub(header_len + packet_len);
+
+        parse_frame(packet_buf.to_vec(), self.compression_threshold).map(Some)
+    }
+}
+
+impl Encoder<RawPacket> for PacketCodec {
+    type Error = PacketWriteError;
+
+    fn encode(&mut self, packet: RawPacket, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let start = dst.len();
+        write_frame(
+            packet.id,
+            &packet.data,
+            self.compression_threshold,
+            &mut self.compressed,
+            dst,
+        )?;
+
+        if let Some(encryptor) = self.encryptor.as_mut() {
+            encryptor.encrypt(&mut dst[start..]);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::io::duplex;
+    use tokio_util::codec::Framed;
+
+    use crate::packet_writer::NetworkWriter;
+
+    use super::*;
+
+    fn packet(id: i32, data: Vec<u8>) -> RawPacket {
+        RawPacket { id, data }
+    }
+
+    #[tokio::test]
+    async fn test_framed_round_trip() {
+        let (client, server) = duplex(64 * 1024);
+        let mut client = Framed::new(client, PacketCodec::new());
+        let mut server = Framed::new(server, PacketCodec::new());
+
+        client.send(packet(0x01, vec![1, 2, 3])).await.unwrap();
+        let received = server.next().await.unwrap().unwrap();
+        assert_eq!((received.id, received.data), (0x01, vec![1, 2, 3]));
+
+        let key = [7u8; 16];
+        for framed in [&mut client, &mut server] {
+            framed.codec_mut().enable_encryption(&key);
+            framed.codec_mut().enable_compression(256);
+        }
+
+        let large = (0..4096).map(|i| (i % 7) as u8).collect::<Vec<_>>();
+        // Fed packets are only written once the sink is flushed.
+        client.feed(packet(0x02, vec![4; 16])).await.unwrap();
+        client.feed(packet(0x03, large.clone())).await.unwrap();
+        client.flush().await.unwrap();
+
+        let received = server.next().await.unwrap().unwrap();
+        assert_eq!((received.id, received.data), (0x02, vec![4; 16]));
+        let received = server.next().await.unwrap().unwrap();
+        assert_eq!((received.id, received.data), (0x03, large));
+
+        drop(client);
+        assert!(server.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_decode_network_writer_output() {
+        let mut written = Vec::new();
+        let mut writer = NetworkWriter::new(&mut written);
+        writer.write_raw_packet(0x01, &[1, 2, 3]).await.unwrap();
+        writer.enable_encryption(&[3u8; 16]).unwrap();
+        writer.enable_compression(8);
+        writer.write_raw_packet(0x02, &[5; 64]).await.unwrap();
+
+        // Everything arrives at once & in pieces, including the encrypted packet.
+        let mut codec = PacketCodec::new();
+        let mut src = BytesMut::new();
+        let mut packets = Vec::new();
+        for chunk in written.chunks(7) {
+            src.extend_from_slice(chunk);
+            while let Some(packet) = codec.decode(&mut src).unwrap() {
+                if packet.id == 0x01 {
+                    codec.enable_encryption(&[3u8; 16]);
+                    codec.enable_compression(8);
+                }
+                packets.push((packet.id, packet.data));
+            }
+        }
+
+        assert_eq!(packets, vec![(0x01, vec![1, 2, 3]), (0x02, vec![5; 64])]);
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_length() {
+        let mut codec = PacketCodec::new();
+
+        let mut src = BytesMut::from(&[0x80, 0x80][..]);
+        assert!(codec.decode(&mut src).unwrap().is_none());
+
+        let mut src = BytesMut::from(&[0xFF, 0xFF, 0xFF, 0xFF, 0x0F][..]);
+        assert!(matches!(
+            codec.decode(&mut src),
+            Err(PacketReadError::InvalidPacketLength { length: -1, .. })
+        ));
+    }
+}
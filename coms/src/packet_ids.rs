@@ -0,0 +1,3 @@
+// Generated by `build.rs` from `generated/reports/packets.json`. Empty if the report isn't available,
+// which only fails the build when `MC_REQUIRE_PACKET_REPORT` is set.
+include!(concat!(env!("OUT_DIR"), "/packet_ids.rs"));
@@ -1,15 +1,23 @@
 use crate::{
+    client_state::ClientState,
     codec::var_int::VarInt,
+    encryption::StreamDecryptor,
     ser::{ReadingError, deserializer::Deserializer},
 };
-use aes::cipher::{BlockDecryptMut, BlockSizeUser, KeyIvInit};
+use flate2::read::ZlibDecoder;
 use std::{
-    io::{self, Cursor},
+    future::poll_fn,
+    io::{self, Cursor, Read},
+    mem,
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 use thiserror::Error;
-use tokio::io::{AsyncRead, AsyncReadExt, ReadBuf};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, ReadBuf},
+    time::{Instant, timeout_at},
+};
 
 /// Error occurred during the reading of a packet.
 #[derive(Error, Debug)]
@@ -21,17 +29,116 @@ pub enum PacketReadError {
     ConnectionClosed,
     #[error("Failed to parse length `{0}`")]
     LengthParseError(String),
+    #[error("Packet length `{length}` is negative or larger than the maximum of `{max}`")]
+    InvalidPacketLength { length: i32, max: usize },
+    #[error("The rest of the packet wasn't received within `{0:?}`")]
+    Timeout(Duration),
     #[error("The received packet ID was not valid, err: `{0}`")]
     InvalidPacketId(ReadingError),
     #[error("Failed to read packet data `{0}`")]
     PacketDataReadError(String),
+    #[error("Badly compressed packet, err: `{0}`")]
+    CompressionError(String),
+}
+
+/// Vanilla refuses to decompress packets larger than 8 MiB.
+pub const MAX_DECOMPRESSED_PACKET_SIZE: usize = 8 * 1024 * 1024;
+
+/// The largest Packet Length vanilla accepts, the most a 3 byte VarInt can hold.
+pub const MAX_PACKET_LENGTH: usize = 2_097_151;
+
+/// The most the buffer of a packet grows by per read, so a packet only takes up as much memory as
+/// has actually arrived of it rather than what its Packet Length claims.
+const READ_CHUNK_SIZE: usize = 8 * 1024;
+
+/// Limits on what a [NetworkReader] accepts from the other side of the connection, so a
+/// misbehaving peer can't make us allocate huge buffers or hold on to a connection forever.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PacketLimits {
+    /// Largest Packet Length accepted in the handshaking state.
+    pub handshaking: usize,
+    /// Largest Packet Length accepted in the status state.
+    pub status: usize,
+    /// Largest Packet Length accepted in the login state.
+    pub login: usize,
+    /// Largest Packet Length accepted in the configuration state.
+    pub configuration: usize,
+    /// Largest Packet Length accepted in the play state.
+    pub play: usize,
+    /// How long the rest of a packet may take to arrive once its first byte has been received,
+    /// [None] to wait forever. Waiting for the next packet to start is never timed out.
+    pub read_timeout: Option<Duration>,
+}
+
+impl PacketLimits {
+    /// Limits for packets sent by a client. The handshake holds at most a 255 character server
+    /// address & the status packets are a few bytes, so anything larger is rejected right away.
+    pub const fn serverbound() -> Self {
+        Self {
+            handshaking: 1024,
+            status: 32,
+            login: MAX_PACKET_LENGTH,
+            configuration: MAX_PACKET_LENGTH,
+            play: MAX_PACKET_LENGTH,
+            // Same as the timeout of vanilla, which keeps connections alive with a packet every 15s.
+            read_timeout: Some(Duration::from_secs(30)),
+        }
+    }
+
+    /// Limits for packets sent by a server, which can be large in every state, e.g. a status
+    /// response with a favicon. The server is trusted to send its packets in time.
+    pub const fn clientbound() -> Self {
+        Self {
+            handshaking: MAX_PACKET_LENGTH,
+            status: MAX_PACKET_LENGTH,
+            read_timeout: None,
+            ..Self::serverbound()
+        }
+    }
+
+    /// The largest Packet Length accepted in [state].
+    pub fn max_packet_length(&self, state: ClientState) -> usize {
+        match state {
+            ClientState::Handshaking => self.handshaking,
+            ClientState::Status => self.status,
+            ClientState::Login => self.login,
+            ClientState::Configuration => self.configuration,
+            ClientState::Play => self.play,
+        }
+    }
+}
+
+impl Default for PacketLimits {
+    fn default() -> Self {
+        Self::serverbound()
+    }
 }
 
 /// Reader for reading packets from the network based on the underlying [reader].
 pub struct NetworkReader<R: AsyncRead + Unpin> {
     reader: R,
     bytes_read: usize,
-    encryption_key: Option<Encryption>,
+    encryption_key: Option<StreamDecryptor>,
+    compression_threshold: Option<usize>,
+    limits: PacketLimits,
+    state: ClientState,
+    frame: PartialFrame,
+}
+
+/// The part of a frame that has been read so far, kept in the [NetworkReader] so nothing is lost
+/// when [NetworkReader::get_packet] is cancelled.
+#[derive(Default)]
+struct PartialFrame {
+    /// The bytes of the Packet Length read so far.
+    length: Vec<u8>,
+    /// The Packet Length, once all of it has been read.
+    packet_len: Option<usize>,
+    /// Buffer for everything after the Packet Length, of which [PartialFrame::filled] bytes have
+    /// been read.
+    data: Vec<u8>,
+    filled: usize,
+    /// When the rest of the frame has to have arrived, set once its first byte is read.
+    deadline: Option<Instant>,
 }
 
 /// A generic minecraft packet that has yet to be parsed into its specific packet type.
@@ -59,46 +166,59 @@ impl<R: AsyncRead + Unpin> AsyncRead for NetworkReader<R> {
     ) -> Poll<io::Result<()>> {
         let self_ref = self.get_mut();
 
-        let read = Pin::new(&mut self_ref.reader);
-        match self_ref.encryption_key.as_mut() {
-            Some(s) => {
-                // Get the starting position
-                let original_fill = buf.filled().len();
-                // Read the raw data
-                let internal_poll = read.poll_read(cx, buf);
-
-                if matches!(internal_poll, Poll::Ready(Ok(_))) {
-                    self_ref.bytes_read += buf.filled().len() - original_fill;
-
-                    // Decrypt the raw data in-place, note that our block size is 1 byte, so this is always safe
-                    for block in buf.filled_mut()[original_fill..]
-                        .chunks_mut(cfb8::Encryptor::<aes::Aes128>::block_size())
-                    {
-                        s.cipher.decrypt_block_mut(block.into());
-                    }
-                }
+        poll_read_decrypted(
+            &mut self_ref.reader,
+            self_ref.encryption_key.as_mut(),
+            &mut self_ref.bytes_read,
+            cx,
+            buf,
+        )
+    }
+}
 
-                internal_poll
-            }
-            None => {
-                let original_fill = buf.filled().len();
-                let read = read.poll_read(cx, buf);
-                if matches!(read, Poll::Ready(Ok(_))) {
-                    self_ref.bytes_read += buf.filled().len() - original_fill;
-                }
-                read
-            }
+/// Read from [reader] into [buf], counting the bytes read in [bytes_read] & decrypting them if
+/// encryption is enabled.
+fn poll_read_decrypted<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    decryptor: Option<&mut StreamDecryptor>,
+    bytes_read: &mut usize,
+    cx: &mut Context<'_>,
+    buf: &mut ReadBuf<'_>,
+) -> Poll<io::Result<()>> {
+    // Get the starting position
+    let original_fill = buf.filled().len();
+    // Read the raw data
+    let read = Pin::new(reader).poll_read(cx, buf);
+
+    if matches!(read, Poll::Ready(Ok(_))) {
+        *bytes_read += buf.filled().len() - original_fill;
+
+        // Decrypt the raw data in-place, note that our block size is 1 byte, so this is always safe
+        if let Some(s) = decryptor {
+            s.decrypt(&mut buf.filled_mut()[original_fill..]);
         }
     }
+
+    read
 }
 
 impl<R: AsyncRead + Unpin> NetworkReader<R> {
-    /// Create a new [NetworkReader] utilizing the provided [reader] as a basis for incoming packets.
+    /// Create a new [NetworkReader] utilizing the provided [reader] as a basis for incoming packets,
+    /// using the [PacketLimits] for packets sent by a client.
     pub fn new(reader: R) -> Self {
+        Self::with_limits(reader, PacketLimits::default())
+    }
+
+    /// Create a new [NetworkReader] that enforces the provided [limits] on incoming packets.
+    pub fn with_limits(reader: R, limits: PacketLimits) -> Self {
         Self {
             reader,
             bytes_read: 0,
             encryption_key: None,
+            compression_threshold: None,
+            limits,
+            state: ClientState::Handshaking,
+            frame: PartialFrame::default(),
         }
     }
 
@@ -110,46 +230,373 @@ impl<R: AsyncRead + Unpin> NetworkReader<R> {
     /// Enable encryption for this communication, can only be enabled, not disabled.
     pub fn enable_encryption(&mut self, key: &[u8; 16]) -> Result<(), PacketReadError> {
         log::info!("Enabling encryption for reader");
-        let cipher =
-            cfb8::Decryptor::<aes::Aes128>::new_from_slices(key, key).expect("invalid key");
-
-        self.encryption_key = Some(Encryption { cipher });
+        self.encryption_key = Some(StreamDecryptor::new(key));
 
         Ok(())
     }
 
-    /// Read a single [RawPacket] from the [reader].
+    /// Enable compression for this communication, as requested by the SetCompression packet.
+    /// Packets are expected to be compressed if they are at least [threshold] bytes long, a negative
+    /// [threshold] disables compression again.
+    pub fn enable_compression(&mut self, threshold: i32) {
+        log::info!("Enabling compression for reader with threshold {threshold}");
+        self.compression_threshold = usize::try_from(threshold).ok();
+    }
+
+    /// Set the state of the connection, which decides the largest packet that is accepted.
+    pub fn set_state(&mut self, state: ClientState) {
+        self.state = state;
+    }
+
+    /// Read a single [RawPacket] from the [reader]. Waiting for the packet to start isn't limited,
+    /// but once its first byte arrived the rest of it has to arrive within the read timeout of the
+    /// [PacketLimits].
+    ///
+    /// This is cancel safe, e.g. as a branch of `tokio::select!`, a partly read packet is kept &
+    /// completed by the next call.
     pub async fn get_packet(&mut self) -> Result<RawPacket, PacketReadError> {
-        // TODO: handle connection closed?
+        loop {
+            let packet = match (self.frame.deadline, self.limits.read_timeout) {
+                (Some(deadline), Some(read_timeout)) => timeout_at(deadline, self.read_frame())
+                    .await
+                    .unwrap_or(Err(PacketReadError::Timeout(read_timeout))),
+                _ => self.read_frame().await,
+            };
 
-        let packet_len = VarInt::decode_async(self).await.map_err(|err| match err {
-            ReadingError::CleanEOF(_) => PacketReadError::ConnectionClosed,
-            err => PacketReadError::LengthParseError(err.to_string()),
-        })?;
+            match packet {
+                Ok(Some(packet)) => return Ok(packet),
+                Ok(None) => {}
+                Err(err) => {
+                    // The frame can't be completed anymore, the next call starts a new one.
+                    self.frame = PartialFrame::default();
+                    return Err(err);
+                }
+            }
+        }
+    }
 
-        let packet_len = packet_len.0 as u64;
+    /// Do a single read for the current frame, returns the packet once all of it has arrived.
+    ///
+    /// Everything read is stored in [NetworkReader::frame] before the next await, which is what
+    /// makes [NetworkReader::get_packet] cancel safe.
+    async fn read_frame(&mut self) -> Result<Option<RawPacket>, PacketReadError> {
+        let Some(packet_len) = self.frame.packet_len else {
+            self.read_length_byte().await?;
+            return Ok(None);
+        };
 
-        // TODO: Validate packet length.
+        if self.frame.filled < packet_len {
+            let filled = self.frame.filled;
+            if self.frame.data.len() == filled {
+                self.frame
+                    .data
+                    .resize(packet_len.min(filled + READ_CHUNK_SIZE), 0);
+            }
 
-        let mut packet_reader = self.take(packet_len);
+            let read = self
+                .read_into_frame()
+                .await
+                .map_err(|err| PacketReadError::PacketDataReadError(err.to_string()))?;
+            if read == 0 {
+                return Err(PacketReadError::ConnectionClosed);
+            }
 
-        let packet_id = VarInt::decode_async(&mut packet_reader)
-            .await
-            .map_err(|err| PacketReadError::InvalidPacketId(err))?;
+            self.frame.filled += read;
+            if self.frame.filled < packet_len {
+                return Ok(None);
+            }
+        }
 
-        let mut packet_data = Vec::new();
-        packet_reader
-            .read_to_end(&mut packet_data)
-            .await
-            .map_err(|err| PacketReadError::PacketDataReadError(err.to_string()))?;
+        let frame = mem::take(&mut self.frame);
+        parse_frame(frame.data, self.compression_threshold).map(Some)
+    }
+
+    /// Read the next byte of the Packet Length. It's read a byte at a time so nothing after it is
+    /// read before the Packet Length is known.
+    async fn read_length_byte(&mut self) -> Result<(), PacketReadError> {
+        let mut byte = [0u8];
+        if self.read(&mut byte).await? == 0 {
+            return Err(match self.frame.length.is_empty() {
+                true => PacketReadError::ConnectionClosed,
+                false => PacketReadError::LengthParseError(
+                    "connection closed in the middle of the packet length".to_string(),
+                ),
+            });
+        }
+
+        if self.frame.length.is_empty() {
+            self.frame.deadline = self
+                .limits
+                .read_timeout
+                .map(|read_timeout| Instant::now() + read_timeout);
+        }
+        self.frame.length.push(byte[0]);
+
+        match VarInt::decode(&mut &self.frame.length[..]) {
+            Ok(packet_len) => {
+                let max = self.limits.max_packet_length(self.state);
+                self.frame.packet_len = Some(check_packet_length(packet_len.0, max)?);
+                Ok(())
+            }
+            Err(ReadingError::Incomplete(_)) => Ok(()),
+            Err(err) => Err(PacketReadError::LengthParseError(err.to_string())),
+        }
+    }
 
-        Ok(RawPacket {
-            id: packet_id.0,
-            data: packet_data,
+    /// Read whatever is available into the unfilled part of the frame buffer.
+    async fn read_into_frame(&mut self) -> io::Result<usize> {
+        poll_fn(|cx| {
+            let mut buf = ReadBuf::new(&mut self.frame.data[self.frame.filled..]);
+            poll_read_decrypted(
+                &mut self.reader,
+                self.encryption_key.as_mut(),
+                &mut self.bytes_read,
+                cx,
+                &mut buf,
+            )
+            .map_ok(|()| buf.filled().len())
         })
+        .await
+    }
+}
+
+/// Validate the Packet Length of a frame, it may be at most [max] bytes.
+pub(crate) fn check_packet_length(length: i32, max: usize) -> Result<usize, PacketReadError> {
+    usize::try_from(length)
+        .ok()
+        .filter(|length| *length <= max)
+        .ok_or(PacketReadError::InvalidPacketLength { length, max })
+}
+
+/// Parse the contents of a frame (everything after the Packet Length) into a [RawPacket].
+pub(crate) fn parse_frame(
+    packet_buf: Vec<u8>,
+    compression_threshold: Option<usize>,
+) -> Result<RawPacket, PacketReadError> {
+    let packet_buf = match compression_threshold {
+        Some(threshold) => decompress(packet_buf, threshold)?,
+        None => packet_buf,
+    };
+
+    let mut packet_reader = Cursor::new(packet_buf);
+    let packet_id = VarInt::decode(&mut packet_reader).map_err(PacketReadError::InvalidPacketId)?;
+
+    let position = packet_reader.position() as usize;
+    let mut packet_data = packet_reader.into_inner();
+    packet_data.drain(..position);
+
+    Ok(RawPacket {
+        id: packet_id.0,
+        data: packet_data,
+    })
+}
+
+/// Unwrap the ID & data of a compressed packet.
+///
+/// Compressed packet structure (after the Packet Length):
+///
+/// Data Length (VarInt), length of the uncompressed ID & data or 0 if not compressed
+/// Packet ID & Data, zlib compressed if Data Length isn't 0
+fn decompress(packet_buf: Vec<u8>, threshold: usize) -> Result<Vec<u8>, PacketReadError> {
+    let mut reader = Cursor::new(packet_buf);
+    let data_len = VarInt::decode(&mut reader)
+        .map_err(|err| PacketReadError::LengthParseError(err.to_string()))?;
+
+    let data_len = usize::try_from(data_len.0).map_err(|_| {
+        PacketReadError::CompressionError(format!("negative data length {}", data_len.0))
+    })?;
+    if data_len == 0 {
+        let position = reader.position() as usize;
+        let mut packet_buf = reader.into_inner();
+        packet_buf.drain(..position);
+        return Ok(packet_buf);
     }
+
+    if data_len < threshold {
+        return Err(PacketReadError::CompressionError(format!(
+            "size of {data_len} is below the threshold of {threshold}"
+        )));
+    }
+    if data_len > MAX_DECOMPRESSED_PACKET_SIZE {
+        return Err(PacketReadError::CompressionError(format!(
+            "size of {data_len} is larger than the maximum of {MAX_DECOMPRESSED_PACKET_SIZE}"
+        )));
+    }
+
+    let mut decompressed = Vec::with_capacity(data_len);
+    // Read one byte more than expected to detect packets that are larger than they claim.
+    ZlibDecoder::new(reader)
+        .take(data_len as u64 + 1)
+        .read_to_end(&mut decompressed)
+        .map_err(|err| PacketReadError::CompressionError(err.to_string()))?;
+    if decompressed.len() != data_len {
+        return Err(PacketReadError::CompressionError(format!(
+            "expected {data_len} bytes but decompressed {}",
+            decompressed.len()
+        )));
+    }
+
+    Ok(decompressed)
 }
 
-struct Encryption {
-    cipher: cfb8::Decryptor<aes::Aes128>,
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncWriteExt, duplex};
+
+    use crate::packet_writer::NetworkWriter;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_compression_round_trip() {
+        let (client, server) = duplex(64 * 1024);
+        let mut writer = NetworkWriter::new(client);
+        let mut reader = NetworkReader::new(server);
+        reader.set_state(ClientState::Play);
+
+        let key = [7u8; 16];
+        writer.enable_encryption(&key).unwrap();
+        reader.enable_encryption(&key).unwrap();
+        writer.enable_compression(256);
+        reader.enable_compression(256);
+
+        let small = vec![1u8; 16];
+        let large = (0..4096).map(|i| (i % 7) as u8).collect::<Vec<_>>();
+
+        writer.write_raw_packet(0x12, &small).await.unwrap();
+        writer.write_raw_packet(0x34, &large).await.unwrap();
+        // Compression is a win on this data, which proves the large packet was actually compressed.
+        assert!(writer.get_total_written() < large.len());
+
+        let packet = reader.get_packet().await.unwrap();
+        assert_eq!(packet.id, 0x12);
+        assert_eq!(packet.data, small);
+
+        let packet = reader.get_packet().await.unwrap();
+        assert_eq!(packet.id, 0x34);
+        assert_eq!(packet.data, large);
+    }
+
+    #[tokio::test]
+    async fn test_compressed_below_threshold() {
+        let (client, server) = duplex(1024);
+        // The writer compresses everything, which the reader must refuse given its higher threshold.
+        let mut writer = NetworkWriter::new(client);
+        writer.enable_compression(0);
+        let mut reader = NetworkReader::new(server);
+        reader.enable_compression(256);
+
+        writer.write_raw_packet(0x1, &[0u8; 16]).await.unwrap();
+
+        assert!(matches!(
+            reader.get_packet().await,
+            Err(PacketReadError::CompressionError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_packet_length() {
+        let (mut client, server) = duplex(1024);
+        let mut reader = NetworkReader::new(server);
+        reader.set_state(ClientState::Status);
+
+        // A status request that claims to be a lot larger than any status packet.
+        VarInt(64).encode_async(&mut client).await.unwrap();
+        assert!(matches!(
+            reader.get_packet().await,
+            Err(PacketReadError::InvalidPacketLength {
+                length: 64,
+                max: 32
+            })
+        ));
+
+        reader.set_state(ClientState::Play);
+        // -1 as a VarInt.
+        client
+            .write_all(&[0xFF, 0xFF, 0xFF, 0xFF, 0x0F])
+            .await
+            .unwrap();
+        assert!(matches!(
+            reader.get_packet().await,
+            Err(PacketReadError::InvalidPacketLength { length: -1, .. })
+        ));
+
+        VarInt(MAX_PACKET_LENGTH as i32 + 1)
+            .encode_async(&mut client)
+            .await
+            .unwrap();
+        assert!(matches!(
+            reader.get_packet().await,
+            Err(PacketReadError::InvalidPacketLength { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_read_timeout() {
+        let (mut client, server) = duplex(1024);
+        let read_timeout = Duration::from_millis(20);
+        let mut reader = NetworkReader::with_limits(
+            server,
+            PacketLimits {
+                read_timeout: Some(read_timeout),
+                ..PacketLimits::default()
+            },
+        );
+
+        // Only part of the packet ever arrives.
+        VarInt(16).encode_async(&mut client).await.unwrap();
+        client.write_all(&[0x00, 0x01]).await.unwrap();
+
+        assert!(matches!(
+            reader.get_packet().await,
+            Err(PacketReadError::Timeout(timeout)) if timeout == read_timeout
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_idle_connection() {
+        let (mut client, server) = duplex(1024);
+        let mut reader = NetworkReader::with_limits(
+            server,
+            PacketLimits {
+                read_timeout: Some(Duration::from_millis(20)),
+                ..PacketLimits::default()
+            },
+        );
+
+        // Nothing arrives for longer than the timeout, which is fine between packets.
+        let write = async {
+            tokio::time::sleep(Duration::from_millis(60)).await;
+            client.write_all(&[0x02, 0x00, 0x07]).await.unwrap();
+        };
+        let (packet, ()) = tokio::join!(reader.get_packet(), write);
+
+        let packet = packet.unwrap();
+        assert_eq!((packet.id, packet.data), (0x00, vec![0x07]));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_read() {
+        let (mut client, server) = duplex(1024);
+        let mut reader = NetworkReader::new(server);
+        reader.set_state(ClientState::Play);
+
+        // The read is cancelled both halfway through the Packet Length & the data.
+        let data = (0..200).map(|i| i as u8).collect::<Vec<_>>();
+        let mut frame = vec![0xC9, 0x01, 0x05];
+        frame.extend_from_slice(&data);
+        for part in [&frame[..1], &frame[1..50], &frame[50..]] {
+            client.write_all(part).await.unwrap();
+            tokio::select! {
+                packet = reader.get_packet() => {
+                    let packet = packet.unwrap();
+                    assert_eq!((packet.id, packet.data), (0x05, data.clone()));
+                    return;
+                }
+                _ = tokio::time::sleep(Duration::from_millis(10)) => {}
+            }
+        }
+
+        panic!("The packet was never completed");
+    }
 }
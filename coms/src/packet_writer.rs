@@ -1,14 +1,15 @@
 use crate::{
-    ClientPacket,
-    codec::var_int::VarInt,
-    messages::McPacket,
-    ser::{NetworkWriteExt, WritingError},
+    ClientPacket, codec::var_int::VarInt, encryption::StreamEncryptor, messages::McPacket,
+    ser::WritingError,
 };
-#[allow(deprecated)]
-use aes::cipher::{BlockEncryptMut, BlockSizeUser, KeyIvInit, generic_array::GenericArray};
+use bytes::BufMut;
+use flate2::{Compression, write::ZlibEncoder};
 use log::error;
 use serde::Serialize;
-use std::io::{self, Cursor};
+use std::{
+    io::{self, Write},
+    mem,
+};
 use thiserror::Error;
 use tokio::io::{AsyncWrite, AsyncWriteExt};
 
@@ -25,10 +26,18 @@ pub enum PacketWriteError {
 }
 
 /// A writing for writing packets to the network.
+///
+/// Packets aren't flushed on their own, call [NetworkWriter::flush] once everything that should go
+/// out together has been written.
 pub struct NetworkWriter<W: AsyncWrite + Unpin> {
     writer: W,
     data_written: usize,
-    encryption_key: Option<Encryption>,
+    encryption_key: Option<StreamEncryptor>,
+    compression_threshold: Option<usize>,
+    /// Reused for every packet, so writing a packet doesn't allocate once these have grown.
+    data: Vec<u8>,
+    frame: Vec<u8>,
+    compressed: Vec<u8>,
 }
 
 impl<W: AsyncWrite + Unpin> NetworkWriter<W> {
@@ -38,6 +47,10 @@ impl<W: AsyncWrite + Unpin> NetworkWriter<W> {
             writer,
             data_written: 0,
             encryption_key: None,
+            compression_threshold: None,
+            data: Vec::new(),
+            frame: Vec::new(),
+            compressed: Vec::new(),
         }
     }
 
@@ -51,40 +64,33 @@ impl<W: AsyncWrite + Unpin> NetworkWriter<W> {
         // TODO: Check that encryption isn't already enabled.
 
         log::info!("Enabling encryption for writer");
-        let cipher =
-            cfb8::Encryptor::<aes::Aes128>::new_from_slices(key, key).expect("invalid key");
-
-        self.encryption_key = Some(Encryption { cipher });
+        self.encryption_key = Some(StreamEncryptor::new(key));
 
         Ok(())
     }
 
+    /// Enable compression for this writer, packets of at least [threshold] bytes are compressed.
+    /// A negative [threshold] disables compression again, same as the SetCompression packet.
+    pub fn enable_compression(&mut self, threshold: i32) {
+        log::info!("Enabling compression for writer with threshold {threshold}");
+        self.compression_threshold = usize::try_from(threshold).ok();
+    }
+
     /// Write some data using this writer.
-    pub async fn write_data(&mut self, data: Vec<u8>) -> Result<(), PacketWriteError> {
-        match self.encryption_key.as_mut() {
-            Some(s) => {
-                let mut bf = Vec::new();
-
-                // Encrypt the raw data, note that our block size is 1 byte, so this is always safe
-                for block in data.chunks(cfb8::Encryptor::<aes::Aes128>::block_size()) {
-                    let mut out = [0u8];
-
-                    // This is a stream cipher, so this value must be used
-                    let out_block = GenericArray::from_mut_slice(&mut out);
-                    s.cipher.encrypt_block_b2b_mut(block.into(), out_block);
-
-                    bf.push(out_block[0]);
-                }
-
-                self.data_written += bf.len();
-                self.writer.write_all(&bf).await?;
-            }
-            None => {
-                self.data_written += data.len();
-                self.writer.write_all(&data).await?;
-            }
+    pub async fn write_data(&mut self, mut data: Vec<u8>) -> Result<(), PacketWriteError> {
+        // Encrypt the data in place, we own it anyway.
+        if let Some(s) = self.encryption_key.as_mut() {
+            s.encrypt(&mut data);
         }
 
+        self.data_written += data.len();
+        self.writer.write_all(&data).await?;
+
+        Ok(())
+    }
+
+    /// Flush everything written so far to the underlying writer.
+    pub async fn flush(&mut self) -> Result<(), PacketWriteError> {
         self.writer.flush().await?;
 
         Ok(())
@@ -97,54 +103,138 @@ impl<W: AsyncWrite + Unpin> NetworkWriter<W> {
     /// Packet Length (VarInt)
     /// Packet ID (VarInt)
     /// Data (Optional, Bytes)
+    ///
+    /// With compression enabled:
+    ///
+    /// Packet Length (VarInt)
+    /// Data Length (VarInt), length of the uncompressed ID & data or 0 if below the threshold
+    /// Packet ID & Data, zlib compressed if Data Length isn't 0
     pub async fn write_packet<P: McPacket + Serialize>(
         &mut self,
         packet: P,
     ) -> Result<(), PacketWriteError> {
-        let mut packet_buffer = Vec::new();
-        packet_buffer.write_var_int(&P::get_packet_id())?;
-        packet.write_packet_data(&mut packet_buffer)?;
-
-        let packet_length: VarInt = packet_buffer.len().try_into().map_err(|err| {
-            error!("Packet length was too large to fit into VarInt! (err: {err:?})");
-            PacketWriteError::PacketLengthTooLarge
-        })?;
-
-        match self.encryption_key.as_mut() {
-            Some(s) => {
-                let mut encryption_buffer = Vec::new();
-                packet_length.encode_async(&mut encryption_buffer).await?;
-                io::copy(&mut Cursor::new(packet_buffer), &mut encryption_buffer)?;
-
-                let mut bf = Vec::new();
-
-                // Decrypt the raw data, note that our block size is 1 byte, so this is always safe
-                for block in encryption_buffer.chunks(cfb8::Encryptor::<aes::Aes128>::block_size())
-                {
-                    let mut out = [0u8];
-
-                    // This is a stream cipher, so this value must be used
-                    // TODO: Wait for aes/crypto-common to update generic array
-                    let out_block = GenericArray::from_mut_slice(&mut out);
-                    s.cipher.encrypt_block_b2b_mut(block.into(), out_block);
-
-                    bf.push(out_block[0]);
-                }
-
-                self.writer.write_all(&bf).await?;
-            }
-            None => {
-                packet_length.encode_async(&mut self.writer).await?;
-                self.writer.write_all(&packet_buffer).await?;
-            }
+        let mut packet_data = mem::take(&mut self.data);
+        packet_data.clear();
+        packet.write_packet_data(&mut packet_data)?;
+
+        let result = self
+            .write_raw_packet(P::get_packet_id().0, &packet_data)
+            .await;
+        self.data = packet_data;
+
+        result
+    }
+
+    /// Same as [NetworkWriter::write_packet] but for a packet that has already been serialized.
+    pub async fn write_raw_packet(&mut self, id: i32, data: &[u8]) -> Result<(), PacketWriteError> {
+        self.frame.clear();
+        write_frame(
+            id,
+            data,
+            self.compression_threshold,
+            &mut self.compressed,
+            &mut self.frame,
+        )?;
+
+        if let Some(s) = self.encryption_key.as_mut() {
+            s.encrypt(&mut self.frame);
         }
 
-        self.writer.flush().await?;
+        self.data_written += self.frame.len();
+        self.writer.write_all(&self.frame).await?;
 
         Ok(())
     }
 }
 
-struct Encryption {
-    cipher: cfb8::Encryptor<aes::Aes128>,
+/// Append the frame of a packet, i.e. its Packet Length followed by its (compressed) ID & data, to
+/// [dst]. The Packet Length is known up front unless the packet is compressed, so only compressed
+/// packets go through the [compressed] buffer first.
+pub(crate) fn write_frame(
+    id: i32,
+    data: &[u8],
+    compression_threshold: Option<usize>,
+    compressed: &mut Vec<u8>,
+    dst: &mut impl BufMut,
+) -> Result<(), PacketWriteError> {
+    let id = VarInt(id);
+    let packet_len = id.written_size() + data.len();
+
+    match compression_threshold {
+        Some(threshold) if packet_len >= threshold => {
+            compressed.clear();
+            let mut encoder = ZlibEncoder::new(compressed, Compression::default());
+            id.encode(&mut encoder)?;
+            encoder.write_all(data)?;
+            let compressed = encoder.finish()?;
+
+            let data_length = to_var_int(packet_len)?;
+            put_var_int(
+                dst,
+                to_var_int(data_length.written_size() + compressed.len())?,
+            )?;
+            put_var_int(dst, data_length)?;
+            dst.put_slice(compressed);
+        }
+        Some(_) => {
+            // A Data Length of 0 marks the ID & data as uncompressed.
+            put_var_int(dst, to_var_int(VarInt(0).written_size() + packet_len)?)?;
+            put_var_int(dst, VarInt(0))?;
+            put_var_int(dst, id)?;
+            dst.put_slice(data);
+        }
+        None => {
+            put_var_int(dst, to_var_int(packet_len)?)?;
+            put_var_int(dst, id)?;
+            dst.put_slice(data);
+        }
+    }
+
+    Ok(())
+}
+
+fn to_var_int(length: usize) -> Result<VarInt, PacketWriteError> {
+    length.try_into().map_err(|err| {
+        error!("Packet length was too large to fit into VarInt! (err: {err:?})");
+        PacketWriteError::PacketLengthTooLarge
+    })
+}
+
+fn put_var_int(dst: &mut impl BufMut, value: VarInt) -> Result<(), PacketWriteError> {
+    Ok(value.encode(&mut dst.writer())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, BufWriter, duplex};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_buffered_frames() {
+        let (client, mut server) = duplex(1024);
+        let mut writer = NetworkWriter::new(BufWriter::new(client));
+
+        writer.write_raw_packet(0x01, &[1, 2, 3]).await.unwrap();
+        writer.enable_compression(256);
+        writer.write_raw_packet(0x02, &[4, 5]).await.unwrap();
+        let capacity = writer.frame.capacity();
+        writer.write_raw_packet(0x02, &[4, 5]).await.unwrap();
+        // The frame buffer is reused instead of allocating a new one for every packet.
+        assert_eq!(writer.frame.capacity(), capacity);
+        writer.flush().await.unwrap();
+        drop(writer);
+
+        let mut written = Vec::new();
+        server.read_to_end(&mut written).await.unwrap();
+        assert_eq!(
+            written,
+            [
+                [4, 0x01, 1, 2, 3].as_slice(),
+                &[4, 0, 0x02, 4, 5],
+                &[4, 0, 0x02, 4, 5]
+            ]
+            .concat()
+        );
+    }
 }
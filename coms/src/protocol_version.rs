@@ -0,0 +1,168 @@
+use std::fmt::{self, Display};
+
+use crate::{client_state::ClientState, messages::registry::PacketDirection};
+
+/// A Minecraft protocol version supported by this crate, selected by the `protocol_version` of
+/// the client's handshake.
+///
+/// Packets are defined with their IDs & layout as of [ProtocolVersion::LATEST], older versions
+/// remap the IDs that differ. Packets whose layout differs between versions implement
+/// [McPacketRead](crate::messages::McPacketRead) / [McPacketWrite](crate::messages::McPacketWrite)
+/// by hand & branch on the version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProtocolVersion {
+    /// 1.21.5 (770).
+    V1_21_5,
+    /// 1.21.6 (771).
+    V1_21_6,
+    /// 1.21.7 & 1.21.8 (772).
+    V1_21_7,
+    /// 1.21.9 & 1.21.10 (773).
+    V1_21_9,
+}
+
+/// A packet with a different ID than in [ProtocolVersion::LATEST].
+struct RemappedId {
+    state: ClientState,
+    direction: PacketDirection,
+    latest_id: i32,
+    id: i32,
+}
+
+const fn play_clientbound(latest_id: i32, id: i32) -> RemappedId {
+    RemappedId {
+        state: ClientState::Play,
+        direction: PacketDirection::Clientbound,
+        latest_id,
+        id,
+    }
+}
+
+/// 1.21.9 added the debug & game test highlight packets in front of most clientbound play packets.
+const BEFORE_1_21_9: &[RemappedId] = &[
+    play_clientbound(0x30, 0x2B), // Login
+    play_clientbound(0x3E, 0x39), // PlayerAbilities
+    play_clientbound(0x67, 0x62), // SetHeldItem
+    play_clientbound(0x83, 0x7E), // UpdateRecipes
+];
+
+impl ProtocolVersion {
+    /// The newest supported version, which the packets in this crate are defined for.
+    pub const LATEST: Self = Self::V1_21_9;
+    /// The oldest supported version.
+    pub const OLDEST: Self = Self::V1_21_5;
+    /// Every supported version, oldest first.
+    pub const ALL: &[Self] = &[Self::V1_21_5, Self::V1_21_6, Self::V1_21_7, Self::V1_21_9];
+
+    /// Find the supported version with the provided protocol number.
+    pub fn from_protocol(protocol: i32) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|version| version.protocol() == protocol)
+    }
+
+    /// The protocol number of this version, as sent in the handshake.
+    pub const fn protocol(self) -> i32 {
+        match self {
+            Self::V1_21_5 => 770,
+            Self::V1_21_6 => 771,
+            Self::V1_21_7 => 772,
+            Self::V1_21_9 => 773,
+        }
+    }
+
+    /// Name of the newest release using this version, e.g. for the server list.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::V1_21_5 => "1.21.5",
+            Self::V1_21_6 => "1.21.6",
+            Self::V1_21_7 => "1.21.8",
+            Self::V1_21_9 => "1.21.10",
+        }
+    }
+
+    fn remapped_ids(self) -> &'static [RemappedId] {
+        match self {
+            Self::V1_21_5 | Self::V1_21_6 | Self::V1_21_7 => BEFORE_1_21_9,
+            Self::V1_21_9 => &[],
+        }
+    }
+
+    /// The ID in this version of the packet with [latest_id] in [ProtocolVersion::LATEST].
+    pub fn packet_id(self, state: ClientState, direction: PacketDirection, latest_id: i32) -> i32 {
+        self.remapped_ids()
+            .iter()
+            .find(|remap| {
+                remap.state == state && remap.direction == direction && remap.latest_id == latest_id
+            })
+            .map_or(latest_id, |remap| remap.id)
+    }
+
+    /// The ID in [ProtocolVersion::LATEST] of the packet with [id] in this version, [None] if the
+    /// ID belongs to a packet that isn't known to this crate.
+    pub fn latest_packet_id(
+        self,
+        state: ClientState,
+        direction: PacketDirection,
+        id: i32,
+    ) -> Option<i32> {
+        let mut remapped = self
+            .remapped_ids()
+            .iter()
+            .filter(|remap| remap.state == state && remap.direction == direction);
+
+        if let Some(remap) = remapped.clone().find(|remap| remap.id == id) {
+            return Some(remap.latest_id);
+        }
+
+        // The latest ID of a remapped packet is used by some other packet in this version.
+        (!remapped.any(|remap| remap.latest_id == id)).then_some(id)
+    }
+}
+
+impl Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name(), self.protocol())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packet_id_mapping() {
+        assert_eq!(
+            ProtocolVersion::from_protocol(772),
+            Some(ProtocolVersion::V1_21_7)
+        );
+        assert_eq!(ProtocolVersion::from_protocol(767), None);
+
+        let play = |version: ProtocolVersion, id| {
+            let mapped = version.packet_id(ClientState::Play, PacketDirection::Clientbound, id);
+            let latest =
+                version.latest_packet_id(ClientState::Play, PacketDirection::Clientbound, mapped);
+            assert_eq!(latest, Some(id));
+            mapped
+        };
+        assert_eq!(play(ProtocolVersion::V1_21_9, 0x30), 0x30);
+        assert_eq!(play(ProtocolVersion::V1_21_6, 0x30), 0x2B);
+        assert_eq!(play(ProtocolVersion::V1_21_5, 0x0A), 0x0A);
+
+        // 0x30 is some other packet in 1.21.5.
+        let latest = ProtocolVersion::V1_21_5.latest_packet_id(
+            ClientState::Play,
+            PacketDirection::Clientbound,
+            0x30,
+        );
+        assert_eq!(latest, None);
+
+        let latest = ProtocolVersion::V1_21_5.latest_packet_id(
+            ClientState::Configuration,
+            PacketDirection::Clientbound,
+            0x30,
+        );
+        assert_eq!(latest, Some(0x30));
+    }
+}
@@ -1,8 +1,11 @@
 use crate::{
-    codec::var_int::VarInt,
+    codec::{network_nbt::NetworkNbt, var_int::VarInt},
     ser::{NetworkReadExt, ReadingError},
 };
-use nbt::{nbt_named_tag::NbtNamedTag, nbt_value::value::NbtValue};
+use nbt::{
+    limits::{NbtLimits, NbtReader},
+    tag_type::NbtTagType,
+};
 use serde::de::{
     self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess,
     Visitor, value::U32Deserializer,
@@ -12,6 +15,7 @@ use std::{fmt::Display, io::Read};
 /// A deserializer for Minecraft packets.
 pub struct Deserializer<R: Read> {
     inner: R,
+    nbt_limits: NbtLimits,
 }
 
 impl de::Error for ReadingError {
@@ -23,7 +27,16 @@ impl de::Error for ReadingError {
 impl<R: Read> Deserializer<R> {
     /// Returns a new [Deserializer] using the underlying [read] as a source for the payload.
     pub fn new(read: R) -> Self {
-        Self { inner: read }
+        Self {
+            inner: read,
+            nbt_limits: NbtLimits::NETWORK,
+        }
+    }
+
+    /// Use [limits] for NBT in the payload instead of [NbtLimits::NETWORK].
+    pub fn with_nbt_limits(mut self, limits: NbtLimits) -> Self {
+        self.nbt_limits = limits;
+        self
     }
 }
 
@@ -150,19 +163,25 @@ impl<'de, R: Read> de::Deserializer<'de> for &mut Deserializer<R> {
         name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        if name == "NbtValue" {
-            return match NbtNamedTag::read(&mut self.inner)? {
-                Some(nbt) => {
-                    let nbt_value: NbtValue = nbt.into();
-                    let s = visitor.visit_newtype_struct(nbt_value.into_deserializer())?;
-                    Ok(s)
-                }
-                None => visitor.visit_none(),
-            };
+        if name == NetworkNbt::SERDE_NAME {
+            let tag = NbtTagType::read_network_limited(&mut NbtReader::new(
+                &mut self.inner,
+                self.nbt_limits,
+            ))?;
+            if tag == NbtTagType::TagEnd {
+                return visitor.visit_unit();
+            }
+
+            return visitor
+                .visit_newtype_struct(nbt::ser::deserializer::Deserializer::from_nbt_tag(tag))
+                .map_err(|err| match err {
+                    nbt::ser::Error::Nbt(err) => ReadingError::NbtParseError(err),
+                    err => ReadingError::Message(err.to_string()),
+                });
         }
 
-        log::warn!("Deserialize unsupported newtype struct, name {name}");
-        unimplemented!()
+        // Newtypes are transparent on the wire, same as in the serializer.
+        visitor.visit_newtype_struct(self)
     }
 
     fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
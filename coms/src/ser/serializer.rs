@@ -28,6 +28,7 @@ impl ser::Error for WritingError {
 
 /// This serializer just writes a sequence without a varint prefix and defers the rest of the
 /// serialization to the wrapped serializer
+#[allow(dead_code)] // Unused until NO_PREFIX_MARKER is supported, see serialize_newtype_struct.
 struct NonPrefixedSeqSerializer<'a, W: Write> {
     wrapped: &'a mut Serializer<W>,
 }
@@ -267,15 +268,10 @@ impl<W: Write> ser::Serializer for &mut Serializer<W> {
         _name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error> {
+        // NetworkNbt is already encoded by the time it gets here, serialize_bytes writes it as-is.
         // TODO: This is super sketchy... is there a way to do it better? Can we choose what
         // serializer to use on a struct somehow from within the struct?
-        // if name == "TextComponent" {
-        //     let mut nbt_serializer =
-        //         pumpkin_nbt::serializer::Serializer::new(&mut self.write, None);
-        //     value.serialize(&mut nbt_serializer).map_err(|err| {
-        //         WritingError::Serde(format!("Failed to serialize TextComponent NBT: {err}"))
-        //     })
-        // } else if name == NO_PREFIX_MARKER {
+        // if name == NO_PREFIX_MARKER {
         //     value.serialize(NonPrefixedSeqSerializer { wrapped: self })
         // } else {
         //     value.serialize(self)
@@ -0,0 +1,469 @@
+//! Command line tool for looking inside & editing NBT files, run `nbt-tool help` for usage.
+
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+    process::ExitCode,
+};
+
+use nbt::{
+    compression::{NbtCompression, write_compressed},
+    error::NbtError,
+    json::{JsonMode, from_json, to_json},
+    nbt_named_tag::NbtNamedTag,
+    nbt_path::NbtPath,
+    nbt_types::NbtString,
+    read_nbt_file_with_compression,
+    snbt::{Snbt, SnbtFormat},
+    tag_type::NbtTagType,
+};
+
+const USAGE: &str = "\
+Usage: nbt-tool <command> [options]
+
+Commands:
+  dump <file>                  Print the file as SNBT (or JSON with --json / --typed-json)
+  convert <input> <output>     Convert between binary NBT, SNBT (.snbt) & JSON (.json)
+  get <file> <path>            Print all tags matching an NBT path, e.g. Data.Player.Pos[0]
+  set <file> <path> <snbt>     Set all tags matching the path to a value, in place
+  remove <file> <path>         Remove all tags matching the path, in place
+  tree <file>                  Print the structure of the file with the size of every tag
+  help                         Print this message
+
+Options:
+  --json                       Output lossy JSON, meant for reading
+  --typed-json                 Output JSON that keeps the tag types, .json files are written this way
+  --compact                    Output everything on a single line
+  --compression <c>            gzip, zlib or none, for binary files written by convert (default gzip)
+  --depth <n>                  Only show n levels of the tree
+
+Files ending in .snbt or .json are read as text, anything else as binary NBT in any compression.";
+
+type ToolResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// The way a file is stored on disk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FileFormat {
+    Binary(NbtCompression),
+    Snbt,
+    Json,
+}
+
+impl FileFormat {
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("snbt") => Self::Snbt,
+            Some("json") => Self::Json,
+            _ => Self::Binary(NbtCompression::Gzip),
+        }
+    }
+}
+
+/// How tags are printed to stdout.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Output {
+    Snbt(SnbtFormat),
+    Json { mode: JsonMode, compact: bool },
+}
+
+#[derive(Debug, Default)]
+struct Args {
+    positional: Vec<String>,
+    json: Option<JsonMode>,
+    compact: bool,
+    compression: Option<NbtCompression>,
+    depth: Option<usize>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> ToolResult<Self> {
+        let mut parsed = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {arg}"))
+            };
+            match arg.as_str() {
+                "--json" => parsed.json = Some(JsonMode::Lossy),
+                "--typed-json" => parsed.json = Some(JsonMode::Typed),
+                "--compact" => parsed.compact = true,
+                "--compression" => {
+                    parsed.compression = Some(match value()?.as_str() {
+                        "gzip" => NbtCompression::Gzip,
+                        "zlib" => NbtCompression::Zlib,
+                        "none" => NbtCompression::Uncompressed,
+                        c => return Err(format!("Unknown compression {c}").into()),
+                    })
+                }
+                "--depth" => parsed.depth = Some(value()?.parse()?),
+                flag if flag.starts_with("--") => {
+                    return Err(format!("Unknown option {flag}").into());
+                }
+                _ => parsed.positional.push(arg.clone()),
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// The positional arguments following the command, errors unless there are exactly [N].
+    fn expect<const N: usize>(&self, usage: &str) -> ToolResult<[&str; N]> {
+        let args: Vec<&str> = self.positional[1..].iter().map(String::as_str).collect();
+        args.try_into()
+            .map_err(|_| format!("Usage: nbt-tool {usage}").into())
+    }
+
+    fn output(&self) -> Output {
+        match self.json {
+            Some(mode) => Output::Json {
+                mode,
+                compact: self.compact,
+            },
+            None if self.compact => Output::Snbt(SnbtFormat::COMPACT),
+            None => Output::Snbt(SnbtFormat::PRETTY),
+        }
+    }
+}
+
+fn run(args: &[String]) -> ToolResult<()> {
+    let args = Args::parse(args)?;
+    let Some(command) = args.positional.first() else {
+        println!("{USAGE}");
+        return Ok(());
+    };
+
+    match command.as_str() {
+        "dump" => {
+            let [file] = args.expect("dump <file>")?;
+            let (tag, _) = read(Path::new(file))?;
+            print(&tag.payload, args.output())
+        }
+        "convert" => {
+            let [input, output] = args.expect("convert <input> <output>")?;
+            let (tag, _) = read(Path::new(input))?;
+            let output = Path::new(output);
+            let format = match FileFormat::from_path(output) {
+                FileFormat::Binary(compression) => {
+                    FileFormat::Binary(args.compression.unwrap_or(compression))
+                }
+                format => format,
+            };
+            write(output, &tag, format)
+        }
+        "get" => {
+            let [file, path] = args.expect("get <file> <path>")?;
+            let (tag, _) = read(Path::new(file))?;
+            for found in path.parse::<NbtPath>()?.get(&tag.payload)? {
+                print(&found, args.output())?;
+            }
+            Ok(())
+        }
+        "set" => {
+            let [file, path, value] = args.expect("set <file> <path> <snbt>")?;
+            let value = NbtTagType::try_from(&Snbt::from(value))?;
+            let count = modify(Path::new(file), |tag| {
+                Ok(path.parse::<NbtPath>()?.set(tag, value)?)
+            })?;
+            println!("Set {count} tag(s)");
+            Ok(())
+        }
+        "remove" => {
+            let [file, path] = args.expect("remove <file> <path>")?;
+            let count = modify(Path::new(file), |tag| {
+                Ok(path.parse::<NbtPath>()?.remove(tag)?)
+            })?;
+            println!("Removed {count} tag(s)");
+            Ok(())
+        }
+        "tree" => {
+            let [file] = args.expect("tree <file>")?;
+            let (tag, _) = read(Path::new(file))?;
+            let mut out = String::new();
+            tree(
+                &mut out,
+                &tag.name.0,
+                &tag.payload,
+                0,
+                args.depth.unwrap_or(usize::MAX),
+            );
+            print!("{out}");
+            Ok(())
+        }
+        "help" => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        c => Err(format!("Unknown command {c}, see nbt-tool help").into()),
+    }
+}
+
+/// Read [path] in the format given by its extension, binary NBT may use any compression.
+/// JSON has no root name so it's read as an unnamed tag.
+fn read(path: &Path) -> ToolResult<(NbtNamedTag, FileFormat)> {
+    let format = FileFormat::from_path(path);
+    let tag = match format {
+        FileFormat::Snbt => NbtNamedTag::try_from(&Snbt::from(fs::read_to_string(path)?))?,
+        FileFormat::Json => {
+            let value: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+            let payload = from_json(&value, JsonMode::Typed)
+                .or_else(|_| from_json(&value, JsonMode::Lossy))?;
+            NbtNamedTag {
+                name: NbtString(String::new()),
+                payload,
+            }
+        }
+        FileFormat::Binary(_) => {
+            let (tag, compression) = read_nbt_file_with_compression(path)?;
+            let tag =
+                tag.ok_or_else(|| NbtError::MalformedNbt("File contains no NBT".to_string()))?;
+            return Ok((tag, FileFormat::Binary(compression)));
+        }
+    };
+
+    Ok((tag, format))
+}
+
+fn write(path: &Path, tag: &NbtNamedTag, format: FileFormat) -> ToolResult<()> {
+    let content = match format {
+        FileFormat::Binary(compression) => {
+            let mut bytes = Vec::new();
+            write_compressed(&mut bytes, tag, compression)?;
+            bytes
+        }
+        FileFormat::Snbt => SnbtFormat::PRETTY
+            .format_named(tag)?
+            .to_string()
+            .into_bytes(),
+        FileFormat::Json => {
+            serde_json::to_string_pretty(&to_json(&tag.payload, JsonMode::Typed))?.into_bytes()
+        }
+    };
+
+    replace_file(path, &content)
+}
+
+/// Write [content] to a temporary file next to [path] & rename it over [path], so a crash or full disk
+/// halfway through leaves the original file intact instead of a truncated one.
+fn replace_file(path: &Path, content: &[u8]) -> ToolResult<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("{} is not a file", path.display()))?;
+    let temp = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+
+    let written = File::create(&temp).and_then(|mut file| {
+        file.write_all(content)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| fs::rename(&temp, path)) {
+        let _ = fs::remove_file(&temp);
+        return Err(e.into());
+    }
+
+    Ok(())
+}
+
+/// Read [path], apply [f] to its root tag & write it back in the same format.
+fn modify(path: &Path, f: impl FnOnce(&mut NbtTagType) -> ToolResult<usize>) -> ToolResult<usize> {
+    let (mut tag, format) = read(path)?;
+    let count = f(&mut tag.payload)?;
+    write(path, &tag, format)?;
+    Ok(count)
+}
+
+fn print(tag: &NbtTagType, output: Output) -> ToolResult<()> {
+    let text = match output {
+        Output::Snbt(format) => format.format(tag)?.to_string(),
+        Output::Json { mode, compact } if compact => serde_json::to_string(&to_json(tag, mode))?,
+        Output::Json { mode, .. } => serde_json::to_string_pretty(&to_json(tag, mode))?,
+    };
+    writeln!(io::stdout(), "{text}")?;
+    Ok(())
+}
+
+/// Counts the bytes written to it.
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The size of the payload of [tag] in binary NBT.
+fn payload_size(tag: &NbtTagType) -> usize {
+    let mut counter = ByteCounter(0);
+    tag.write(&mut counter)
+        .expect("Writing to a ByteCounter can't fail");
+    counter.0
+}
+
+fn human_size(bytes: usize) -> String {
+    match bytes {
+        0..1024 => format!("{bytes} B"),
+        1024..1048576 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1048576.0),
+    }
+}
+
+/// Append one line per compound member & nested list element of [tag] to [out], [max_depth] levels deep.
+/// Returns the payload size of [tag], sizes are added up from the children so every tag is only
+/// serialized once.
+fn tree(out: &mut String, name: &str, tag: &NbtTagType, depth: usize, max_depth: usize) -> usize {
+    let mut children = String::new();
+    let size = match tag {
+        _ if depth + 1 >= max_depth => payload_size(tag),
+        NbtTagType::TagCompound(compound) => {
+            // Every member has a tag id & name, followed by a TAG_End closing the compound
+            let members: usize = compound
+                .0
+                .iter()
+                .map(|member| {
+                    1 + payload_size(&NbtTagType::TagString(member.name.clone()))
+                        + tree(
+                            &mut children,
+                            &member.name.0,
+                            &member.payload,
+                            depth + 1,
+                            max_depth,
+                        )
+                })
+                .sum();
+            members + 1
+        }
+        NbtTagType::TagList(list) => {
+            // The element tag id & length, followed by the elements
+            let elements: usize = list
+                .0
+                .iter()
+                .enumerate()
+                .map(|(i, element)| match element {
+                    NbtTagType::TagCompound(_) | NbtTagType::TagList(_) => tree(
+                        &mut children,
+                        &format!("[{i}]"),
+                        element,
+                        depth + 1,
+                        max_depth,
+                    ),
+                    element => payload_size(element),
+                })
+                .sum();
+            5 + elements
+        }
+        tag => payload_size(tag),
+    };
+
+    let summary = match tag {
+        NbtTagType::TagCompound(compound) => format!("{} entries", compound.0.len()),
+        NbtTagType::TagList(list) => match list.0.first() {
+            Some(first) => format!("{} {}", list.0.len(), first.type_name()),
+            None => "empty".to_string(),
+        },
+        NbtTagType::TagByteArray(array) => format!("{} elements", array.0.len()),
+        NbtTagType::TagIntArray(array) => format!("{} elements", array.0.len()),
+        NbtTagType::TagLongArray(array) => format!("{} elements", array.0.len()),
+        tag => SnbtFormat::COMPACT.format_lossy(tag).to_string(),
+    };
+    out.push_str(&format!(
+        "{}{name}: {} ({summary}, {})\n",
+        "  ".repeat(depth),
+        tag.type_name(),
+        human_size(size)
+    ));
+    out.push_str(&children);
+
+    size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree() {
+        let tag = NbtTagType::try_from(&Snbt::from(
+            r#"{Data:{Version:{Id:4189,Name:"1.21.10"},Pos:[1.0d,2.0d],Players:[{id:[I;1,2,3,4]}]}}"#,
+        ))
+        .unwrap();
+
+        let mut out = String::new();
+        tree(&mut out, "", &tag, 0, usize::MAX);
+        assert_eq!(
+            out,
+            r#": compound (1 entries, 113 B)
+  Data: compound (3 entries, 105 B)
+    Version: compound (2 entries, 26 B)
+      Id: int (4189, 4 B)
+      Name: string ("1.21.10", 9 B)
+    Pos: list (2 double, 21 B)
+    Players: list (1 compound, 31 B)
+      [0]: compound (1 entries, 26 B)
+        id: int_array (4 elements, 20 B)
+"#
+        );
+
+        let mut out = String::new();
+        tree(&mut out, "", &tag, 0, 2);
+        assert_eq!(out.lines().count(), 2);
+
+        // Sizes added up from the children match the size of the serialized tag
+        for max_depth in 1..5 {
+            let size = tree(&mut String::new(), "", &tag, 0, max_depth);
+            assert_eq!(size, payload_size(&tag));
+        }
+    }
+
+    #[test]
+    fn test_replace_file() {
+        let dir = env::temp_dir().join(format!("nbt-tool-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("level.snbt");
+        fs::write(&path, "old").unwrap();
+
+        replace_file(&path, b"new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        // A failed write leaves the original file & no temporary file behind
+        assert!(replace_file(&dir.join("missing").join("level.snbt"), b"new").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_args() {
+        let args = Args::parse(&[
+            "convert".to_string(),
+            "level.dat".to_string(),
+            "--compression".to_string(),
+            "zlib".to_string(),
+            "level.snbt".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(args.expect::<2>("").unwrap(), ["level.dat", "level.snbt"]);
+        assert_eq!(args.compression, Some(NbtCompression::Zlib));
+        assert!(args.expect::<1>("").is_err());
+        assert!(Args::parse(&["--nope".to_string()]).is_err());
+    }
+}
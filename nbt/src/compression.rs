@@ -0,0 +1,118 @@
+use std::io::{Cursor, Read, Write};
+
+use flate2::{
+    Compression,
+    read::{GzDecoder, ZlibDecoder},
+    write::{GzEncoder, ZlibEncoder},
+};
+
+use crate::{NbtResult, nbt_named_tag::NbtNamedTag};
+
+/// The compression of an NBT file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NbtCompression {
+    /// Used by vanilla for `level.dat`, player data & structure files.
+    #[default]
+    Gzip,
+    /// Same as used for chunks in region files.
+    Zlib,
+    /// No compression at all.
+    Uncompressed,
+}
+
+impl NbtCompression {
+    /// Detect the compression from the first bytes of a file. Neither magic number is a valid tag ID
+    /// so anything that isn't gzip or zlib has to be uncompressed NBT.
+    pub fn detect(bytes: &[u8]) -> Self {
+        match bytes {
+            [0x1f, 0x8b, ..] => Self::Gzip,
+            // Deflate with any window size & a valid header checksum.
+            [cmf, flg, ..]
+                if cmf & 0x0f == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0 =>
+            {
+                Self::Zlib
+            }
+            _ => Self::Uncompressed,
+        }
+    }
+}
+
+/// Read NBT from [r] which may be compressed with any [NbtCompression], returns the NBT together with the
+/// detected compression so it can be written back the same way.
+pub fn read_compressed<R: Read>(mut r: R) -> NbtResult<(Option<NbtNamedTag>, NbtCompression)> {
+    let mut content = Vec::new();
+    r.read_to_end(&mut content)?;
+
+    let compression = NbtCompression::detect(&content);
+    let content = match compression {
+        NbtCompression::Gzip => decompress(GzDecoder::new(&content[..]))?,
+        NbtCompression::Zlib => decompress(ZlibDecoder::new(&content[..]))?,
+        NbtCompression::Uncompressed => content,
+    };
+
+    let nbt = NbtNamedTag::read(&mut Cursor::new(content))?;
+
+    Ok((nbt, compression))
+}
+
+/// Write [nbt] to [w] compressed with [compression].
+pub fn write_compressed<W: Write>(
+    w: W,
+    nbt: &NbtNamedTag,
+    compression: NbtCompression,
+) -> NbtResult<()> {
+    match compression {
+        NbtCompression::Gzip => {
+            let mut encoder = GzEncoder::new(w, Compression::default());
+            nbt.write(&mut encoder)?;
+            encoder.finish()?.flush()?;
+        }
+        NbtCompression::Zlib => {
+            let mut encoder = ZlibEncoder::new(w, Compression::default());
+            nbt.write(&mut encoder)?;
+            encoder.finish()?.flush()?;
+        }
+        NbtCompression::Uncompressed => {
+            let mut w = w;
+            nbt.write(&mut w)?;
+            w.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+fn decompress<R: Read>(mut decoder: R) -> NbtResult<Vec<u8>> {
+    let mut content = Vec::new();
+    decoder.read_to_end(&mut content)?;
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{nbt_types::NbtString, snbt::Snbt};
+
+    use super::*;
+
+    #[test]
+    fn test_compression_round_trip() {
+        let nbt = NbtNamedTag {
+            name: NbtString("data".to_string()),
+            ..NbtNamedTag::try_from(&Snbt::from(r#"{DataVersion:4189,data:{map_0:3}}"#)).unwrap()
+        };
+
+        for compression in [
+            NbtCompression::Gzip,
+            NbtCompression::Zlib,
+            NbtCompression::Uncompressed,
+        ] {
+            let mut bytes = Vec::new();
+            write_compressed(&mut bytes, &nbt, compression).unwrap();
+            assert_eq!(NbtCompression::detect(&bytes), compression);
+
+            let (read, detected) = read_compressed(&bytes[..]).unwrap();
+            assert_eq!(read, Some(nbt.clone()));
+            assert_eq!(detected, compression);
+        }
+    }
+}
@@ -0,0 +1,463 @@
+use std::fmt::Display;
+
+use crate::{
+    NbtResult,
+    error::NbtError,
+    nbt_named_tag::NbtNamedTag,
+    nbt_path::NbtPath,
+    nbt_types::{NbtByteArray, NbtIntArray, NbtList, NbtLongArray},
+    snbt::{Snbt, SnbtFormat},
+    tag_type::NbtTagType,
+};
+
+/// A single difference between two NBT trees.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtChange {
+    /// A compound member or list element that only exists in the new tree.
+    Added { path: NbtPath, value: NbtTagType },
+    /// A compound member or list element that only exists in the old tree.
+    Removed { path: NbtPath, value: NbtTagType },
+    /// The tag at [path] has a different type in the new tree.
+    TypeChanged {
+        path: NbtPath,
+        old: NbtTagType,
+        new: NbtTagType,
+    },
+    /// The tag at [path] has the same type but a different value in the new tree.
+    ValueChanged {
+        path: NbtPath,
+        old: NbtTagType,
+        new: NbtTagType,
+    },
+    /// Elements `start..start + old.len()` of the array at [path] were replaced by [new], both [old]
+    /// & [new] are arrays of the same type as the one at [path].
+    ArrayRange {
+        path: NbtPath,
+        start: usize,
+        old: NbtTagType,
+        new: NbtTagType,
+    },
+}
+
+impl Display for NbtChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NbtChange::Added { path, value } => write!(f, "+ {path}: {}", snbt(value)),
+            NbtChange::Removed { path, value } => write!(f, "- {path}: {}", snbt(value)),
+            NbtChange::TypeChanged { path, old, new } => write!(
+                f,
+                "~ {path}: {} -> {} (tag {} -> {})",
+                snbt(old),
+                snbt(new),
+                old.get_tag_id(),
+                new.get_tag_id()
+            ),
+            NbtChange::ValueChanged { path, old, new } => {
+                write!(f, "~ {path}: {} -> {}", snbt(old), snbt(new))
+            }
+            NbtChange::ArrayRange {
+                path,
+                start,
+                old,
+                new,
+            } => write!(
+                f,
+                "~ {path}[{start}..{}]: {} -> {}",
+                start + array_len(old),
+                snbt(old),
+                snbt(new)
+            ),
+        }
+    }
+}
+
+/// [tag] as SNBT for messages, NaN & infinite numbers are shown rather than causing an error.
+fn snbt(tag: &NbtTagType) -> Snbt {
+    SnbtFormat::COMPACT.format_lossy(tag)
+}
+
+/// The changes turning one NBT tree into another, see [diff].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NbtDiff(pub Vec<NbtChange>);
+
+impl NbtDiff {
+    /// Whether both trees were identical.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Apply the changes to [root], which has to be the old tree the diff was created from (or at
+    /// least contain the same values at the changed paths).
+    pub fn apply(&self, root: &mut NbtTagType) -> NbtResult<()> {
+        for change in &self.0 {
+            apply_change(change, root)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for NbtDiff {
+    /// One change per line.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, change) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{change}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Find the differences between [old] & [new]. Members of compounds are matched by name so their
+/// order doesn't matter, lists & arrays are compared element by element.
+pub fn diff(old: &NbtTagType, new: &NbtTagType) -> NbtDiff {
+    let mut changes = Vec::new();
+    diff_at(&NbtPath::root(), old, new, &mut changes);
+    NbtDiff(changes)
+}
+
+fn diff_at(path: &NbtPath, old: &NbtTagType, new: &NbtTagType, changes: &mut Vec<NbtChange>) {
+    match (old, new) {
+        (NbtTagType::TagCompound(old), NbtTagType::TagCompound(new)) => {
+            for old_tag in &old.0 {
+                match find(&new.0, &old_tag.name.0) {
+                    Some(new_tag) => diff_at(
+                        &path.key(&old_tag.name.0),
+                        &old_tag.payload,
+                        &new_tag.payload,
+                        changes,
+                    ),
+                    None => changes.push(NbtChange::Removed {
+                        path: path.key(&old_tag.name.0),
+                        value: old_tag.payload.clone(),
+                    }),
+                }
+            }
+            for new_tag in new.0.iter().filter(|t| find(&old.0, &t.name.0).is_none()) {
+                changes.push(NbtChange::Added {
+                    path: path.key(&new_tag.name.0),
+                    value: new_tag.payload.clone(),
+                });
+            }
+        }
+        // Elements can't change type one by one, so replace the whole list if they do.
+        (NbtTagType::TagList(NbtList(old_items)), NbtTagType::TagList(NbtList(new_items)))
+            if !matches!(
+                (old_items.first(), new_items.first()),
+                (Some(o), Some(n)) if o.get_tag_id() != n.get_tag_id()
+            ) =>
+        {
+            for (i, (o, n)) in old_items.iter().zip(new_items).enumerate() {
+                diff_at(&path.index(i), o, n, changes);
+            }
+            // Removed from the back so the indices of earlier removals stay valid.
+            for i in (new_items.len()..old_items.len()).rev() {
+                changes.push(NbtChange::Removed {
+                    path: path.index(i),
+                    value: old_items[i].clone(),
+                });
+            }
+            for (i, item) in new_items.iter().enumerate().skip(old_items.len()) {
+                changes.push(NbtChange::Added {
+                    path: path.index(i),
+                    value: item.clone(),
+                });
+            }
+        }
+        (NbtTagType::TagByteArray(o), NbtTagType::TagByteArray(n)) => {
+            diff_array(path, &o.0, &n.0, changes, |a| {
+                NbtTagType::TagByteArray(NbtByteArray(a))
+            })
+        }
+        (NbtTagType::TagIntArray(o), NbtTagType::TagIntArray(n)) => {
+            diff_array(path, &o.0, &n.0, changes, |a| {
+                NbtTagType::TagIntArray(NbtIntArray(a))
+            })
+        }
+        (NbtTagType::TagLongArray(o), NbtTagType::TagLongArray(n)) => {
+            diff_array(path, &o.0, &n.0, changes, |a| {
+                NbtTagType::TagLongArray(NbtLongArray(a))
+            })
+        }
+        (old, new) if old.get_tag_id() != new.get_tag_id() => {
+            changes.push(NbtChange::TypeChanged {
+                path: path.clone(),
+                old: old.clone(),
+                new: new.clone(),
+            })
+        }
+        (old, new) if !identical(old, new) => changes.push(NbtChange::ValueChanged {
+            path: path.clone(),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// Arrays of the same length produce a range per run of changed elements, otherwise a single range
+/// covering everything between the common prefix & suffix.
+fn diff_array<T: PartialEq + Clone>(
+    path: &NbtPath,
+    old: &[T],
+    new: &[T],
+    changes: &mut Vec<NbtChange>,
+    to_tag: impl Fn(Vec<T>) -> NbtTagType,
+) {
+    let mut range = |start: usize, old_end: usize, new_end: usize| {
+        changes.push(NbtChange::ArrayRange {
+            path: path.clone(),
+            start,
+            old: to_tag(old[start..old_end].to_vec()),
+            new: to_tag(new[start..new_end].to_vec()),
+        })
+    };
+
+    if old.len() == new.len() {
+        let mut i = 0;
+        while i < old.len() {
+            if old[i] == new[i] {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < old.len() && old[i] != new[i] {
+                i += 1;
+            }
+            range(start, i, i);
+        }
+        return;
+    }
+
+    let prefix = old.iter().zip(new).take_while(|(o, n)| o == n).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(o, n)| o == n)
+        .count();
+    range(prefix, old.len() - suffix, new.len() - suffix);
+}
+
+fn find<'a>(tags: &'a [NbtNamedTag], name: &str) -> Option<&'a NbtNamedTag> {
+    tags.iter().find(|t| t.name.0 == name)
+}
+
+fn array_len(tag: &NbtTagType) -> usize {
+    match tag {
+        NbtTagType::TagByteArray(a) => a.0.len(),
+        NbtTagType::TagIntArray(a) => a.0.len(),
+        NbtTagType::TagLongArray(a) => a.0.len(),
+        _ => 0,
+    }
+}
+
+/// Same as `==` except that floats are compared by their bits, so NaN is identical to itself, and
+/// the order of compound members doesn't matter.
+fn identical(a: &NbtTagType, b: &NbtTagType) -> bool {
+    match (a, b) {
+        (NbtTagType::TagFloat(a), NbtTagType::TagFloat(b)) => a.0.to_bits() == b.0.to_bits(),
+        (NbtTagType::TagDouble(a), NbtTagType::TagDouble(b)) => a.0.to_bits() == b.0.to_bits(),
+        (NbtTagType::TagList(a), NbtTagType::TagList(b)) => {
+            a.0.len() == b.0.len() && a.0.iter().zip(&b.0).all(|(a, b)| identical(a, b))
+        }
+        (NbtTagType::TagCompound(a), NbtTagType::TagCompound(b)) => {
+            a.0.len() == b.0.len()
+                && a.0.iter().all(|a| {
+                    find(&b.0, &a.name.0).is_some_and(|b| identical(&a.payload, &b.payload))
+                })
+        }
+        (a, b) => a == b,
+    }
+}
+
+fn mismatch(path: &NbtPath, reason: impl Into<String>) -> NbtError {
+    NbtError::NbtPathMismatch {
+        path: path.to_string(),
+        reason: reason.into(),
+    }
+}
+
+/// The single tag at [path], [NbtPath::root] being [root] itself.
+fn get(path: &NbtPath, root: &NbtTagType) -> NbtResult<NbtTagType> {
+    if path.is_root() {
+        return Ok(root.clone());
+    }
+
+    let mut found = path.get(root)?;
+    match found.len() {
+        1 => Ok(found.remove(0)),
+        n => Err(mismatch(path, format!("expected a single tag, found {n}"))),
+    }
+}
+
+fn set(path: &NbtPath, root: &mut NbtTagType, value: NbtTagType) -> NbtResult<()> {
+    if path.is_root() {
+        *root = value;
+        return Ok(());
+    }
+
+    path.set(root, value).map(|_| ())
+}
+
+/// Errors unless the tag at [path] is [expected], so patches aren't applied to the wrong tree.
+fn expect(path: &NbtPath, root: &NbtTagType, expected: &NbtTagType) -> NbtResult<()> {
+    let found = get(path, root)?;
+    if !identical(&found, expected) {
+        return Err(mismatch(
+            path,
+            format!("expected {} but found {}", snbt(expected), snbt(&found)),
+        ));
+    }
+
+    Ok(())
+}
+
+fn apply_change(change: &NbtChange, root: &mut NbtTagType) -> NbtResult<()> {
+    match change {
+        NbtChange::Added { path, value } => match path.split_index() {
+            Some((list_path, index)) => {
+                let NbtTagType::TagList(mut list) = get(&list_path, root)? else {
+                    return Err(mismatch(&list_path, "expected a list"));
+                };
+                let index = usize::try_from(index)?;
+                if index > list.0.len() {
+                    return Err(mismatch(path, "index is past the end of the list"));
+                }
+                list.0.insert(index, value.clone());
+                set(&list_path, root, NbtTagType::TagList(list))
+            }
+            None => set(path, root, value.clone()),
+        },
+        NbtChange::Removed { path, value } => {
+            expect(path, root, value)?;
+            path.remove(root).map(|_| ())
+        }
+        NbtChange::TypeChanged { path, old, new } | NbtChange::ValueChanged { path, old, new } => {
+            expect(path, root, old)?;
+            set(path, root, new.clone())
+        }
+        NbtChange::ArrayRange {
+            path,
+            start,
+            old,
+            new,
+        } => {
+            let mismatched = || mismatch(path, format!("expected {} at {start}", snbt(old)));
+            let array = match (get(path, root)?, old, new) {
+                (
+                    NbtTagType::TagByteArray(mut a),
+                    NbtTagType::TagByteArray(o),
+                    NbtTagType::TagByteArray(n),
+                ) => {
+                    splice(&mut a.0, *start, &o.0, &n.0).ok_or_else(mismatched)?;
+                    NbtTagType::TagByteArray(a)
+                }
+                (
+                    NbtTagType::TagIntArray(mut a),
+                    NbtTagType::TagIntArray(o),
+                    NbtTagType::TagIntArray(n),
+                ) => {
+                    splice(&mut a.0, *start, &o.0, &n.0).ok_or_else(mismatched)?;
+                    NbtTagType::TagIntArray(a)
+                }
+                (
+                    NbtTagType::TagLongArray(mut a),
+                    NbtTagType::TagLongArray(o),
+                    NbtTagType::TagLongArray(n),
+                ) => {
+                    splice(&mut a.0, *start, &o.0, &n.0).ok_or_else(mismatched)?;
+                    NbtTagType::TagLongArray(a)
+                }
+                _ => return Err(mismatched()),
+            };
+            set(path, root, array)
+        }
+    }
+}
+
+/// Replace [old] at [start] in [array] by [new], `None` if [array] doesn't contain [old] there.
+fn splice<T: PartialEq + Clone>(
+    array: &mut Vec<T>,
+    start: usize,
+    old: &[T],
+    new: &[T],
+) -> Option<()> {
+    let end = start + old.len();
+    if array.get(start..end)? != old {
+        return None;
+    }
+
+    array.splice(start..end, new.iter().cloned());
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> NbtTagType {
+        NbtTagType::try_from(&Snbt::from(s)).unwrap()
+    }
+
+    fn assert_patches(old: &str, new: &str) -> NbtDiff {
+        let (old, new) = (parse(old), parse(new));
+        let changes = diff(&old, &new);
+
+        let mut patched = old.clone();
+        changes.apply(&mut patched).unwrap();
+        assert!(diff(&patched, &new).is_empty(), "{changes}");
+
+        changes
+    }
+
+    #[test]
+    fn test_diff() {
+        let changes = assert_patches(
+            r#"{DataVersion:3953,Status:"full",sections:[{Y:0b,data:[L;1l,2l,3l,4l]},{Y:1b}],
+                light:[B;1b,2b,3b],removed:1b,list:[1,2,3]}"#,
+            r#"{Status:1b,DataVersion:4189,sections:[{Y:0b,data:[L;1l,5l,3l,6l]},{Y:1b,new:{}}],
+                light:[B;1b,9b,9b,3b],list:[1],added:"a"}"#,
+        );
+
+        assert_eq!(
+            changes.to_string(),
+            r#"~ DataVersion: 3953 -> 4189
+~ Status: "full" -> 1b (tag 8 -> 1)
+~ sections[0].data[1..2]: [L;2l] -> [L;5l]
+~ sections[0].data[3..4]: [L;4l] -> [L;6l]
++ sections[1].new: {}
+~ light[1..2]: [B;2b] -> [B;9b,9b]
+- removed: 1b
+- list[2]: 3
+- list[1]: 2
++ added: "a""#
+        );
+        assert!(
+            diff(&parse("{a:1,b:[2]}"), &parse("{b:[2],a:1}")).is_empty(),
+            "Member order doesn't matter"
+        );
+    }
+
+    #[test]
+    fn test_patch() {
+        assert_patches("{list:[]}", "{list:[{a:1},{b:2}]}");
+        assert_patches("{list:[1,2]}", "{list:[1b,2b]}");
+        assert_patches("{a:[I;1,2,3]}", "{a:[I;]}");
+        assert_patches("{a:[I;]}", "{a:[I;4,5]}");
+        assert_patches("{a:{b:{c:1}}}", "{a:{b:{c:[1]}}}");
+        assert_patches("{a:1}", "[1]");
+
+        // Applying to a tree that doesn't match the old one fails.
+        let changes = diff(&parse("{a:1}"), &parse("{a:2}"));
+        let mut other = parse("{a:3}");
+        assert!(matches!(
+            changes.apply(&mut other),
+            Err(NbtError::NbtPathMismatch { .. })
+        ));
+    }
+}
@@ -12,8 +12,24 @@ pub enum NbtError {
     MalformedNbt(String),
     #[error("String contained invalid utf8, err: {0}")]
     InvalidUtf8(#[from] FromUtf8Error),
+    #[error("String contained invalid modified utf8, err: {0}")]
+    InvalidMutf8(String),
     #[error("Provided length was invalid, err: {0}")]
     InvalidLength(#[from] TryFromIntError),
+    #[error("Invalid SNBT at position {position}: {reason}")]
+    InvalidSnbt { position: usize, reason: String },
+    #[error("Can't write {0} as SNBT, only finite numbers can be parsed back")]
+    NonFiniteSnbt(String),
+    #[error("Invalid NBT path at position {position}: {reason}")]
+    InvalidNbtPath { position: usize, reason: String },
+    #[error("Found no elements matching NBT path {0}")]
+    NbtPathNotFound(String),
+    #[error("Can't apply NBT path {path}: {reason}")]
+    NbtPathMismatch { path: String, reason: String },
+    #[error("NBT exceeds the maximum {limit} of {max}")]
+    LimitExceeded { limit: &'static str, max: usize },
+    #[error("Can't convert JSON to NBT: {0}")]
+    InvalidJson(String),
 }
 
 /// Result type for the crate.
@@ -0,0 +1,346 @@
+use std::io::{Read, Write};
+
+use crate::{
+    NbtResult,
+    error::NbtError,
+    limits::{NbtLimits, NbtReader},
+    mutf8,
+    nbt_named_tag::NbtNamedTag,
+};
+
+/// The binary encodings of NBT, they share the same tags but encode numbers & lengths differently.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NbtFlavor {
+    /// Big-endian with Modified UTF-8 strings, used by Java Edition.
+    #[default]
+    Java,
+    /// Little-endian with UTF-8 strings, used by Bedrock Edition for files like `level.dat`.
+    BedrockLittleEndian,
+    /// Same as [NbtFlavor::BedrockLittleEndian] but ints, longs & lengths are (zigzag) VarInts,
+    /// used by Bedrock Edition for NBT in packets.
+    BedrockNetwork,
+}
+
+/// Writer context writing NBT in the provided [NbtFlavor].
+pub struct NbtWriter<W: Write> {
+    inner: W,
+    flavor: NbtFlavor,
+}
+
+macro_rules! write_fixed {
+    ($self:expr, $v:expr) => {
+        match $self.flavor {
+            NbtFlavor::Java => $self.write_bytes(&$v.to_be_bytes()),
+            NbtFlavor::BedrockLittleEndian | NbtFlavor::BedrockNetwork => {
+                $self.write_bytes(&$v.to_le_bytes())
+            }
+        }
+    };
+}
+
+impl<W: Write> NbtWriter<W> {
+    /// Create a writer writing [flavor] NBT to [inner].
+    pub fn new(inner: W, flavor: NbtFlavor) -> Self {
+        Self { inner, flavor }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    pub(crate) fn write_bytes(&mut self, bytes: &[u8]) -> NbtResult<()> {
+        self.inner.write_all(bytes)?;
+        Ok(())
+    }
+
+    pub(crate) fn write_i16(&mut self, v: i16) -> NbtResult<()> {
+        write_fixed!(self, v)
+    }
+
+    pub(crate) fn write_i32(&mut self, v: i32) -> NbtResult<()> {
+        match self.flavor {
+            NbtFlavor::BedrockNetwork => self.write_var_u64(u64::from(zigzag_32(v))),
+            _ => write_fixed!(self, v),
+        }
+    }
+
+    pub(crate) fn write_i64(&mut self, v: i64) -> NbtResult<()> {
+        match self.flavor {
+            NbtFlavor::BedrockNetwork => self.write_var_u64(zigzag_64(v)),
+            _ => write_fixed!(self, v),
+        }
+    }
+
+    pub(crate) fn write_f32(&mut self, v: f32) -> NbtResult<()> {
+        write_fixed!(self, v)
+    }
+
+    pub(crate) fn write_f64(&mut self, v: f64) -> NbtResult<()> {
+        write_fixed!(self, v)
+    }
+
+    /// Writes the provided [s] prefixed by its length, a u16 or an unsigned VarInt for network NBT.
+    pub(crate) fn write_str(&mut self, s: &str) -> NbtResult<()> {
+        let bytes = match self.flavor {
+            NbtFlavor::Java => mutf8::encode(s),
+            NbtFlavor::BedrockLittleEndian | NbtFlavor::BedrockNetwork => s.as_bytes().into(),
+        };
+
+        match self.flavor {
+            NbtFlavor::BedrockNetwork => {
+                self.write_var_u64(u64::from(u32::try_from(bytes.len())?))?
+            }
+            _ => write_fixed!(self, u16::try_from(bytes.len())?)?,
+        }
+        self.write_bytes(&bytes)
+    }
+
+    /// Writes the length prefix used by lists & arrays, which is always encoded the same as a TAG_Int.
+    pub(crate) fn write_array_length(&mut self, length: usize) -> NbtResult<()> {
+        self.write_i32(i32::try_from(length)?)
+    }
+
+    fn write_var_u64(&mut self, mut v: u64) -> NbtResult<()> {
+        loop {
+            let byte = (v & 0x7f) as u8;
+            v >>= 7;
+            if v == 0 {
+                return self.write_bytes(&[byte]);
+            }
+            self.write_bytes(&[byte | 0x80])?;
+        }
+    }
+}
+
+impl<R: Read> NbtReader<R> {
+    pub(crate) fn read_i16(&mut self) -> NbtResult<i16> {
+        let bytes = self.read_bytes()?;
+        Ok(match self.flavor() {
+            NbtFlavor::Java => i16::from_be_bytes(bytes),
+            _ => i16::from_le_bytes(bytes),
+        })
+    }
+
+    pub(crate) fn read_i32(&mut self) -> NbtResult<i32> {
+        Ok(match self.flavor() {
+            NbtFlavor::Java => i32::from_be_bytes(self.read_bytes()?),
+            NbtFlavor::BedrockLittleEndian => i32::from_le_bytes(self.read_bytes()?),
+            NbtFlavor::BedrockNetwork => {
+                let v = u32::try_from(self.read_var_u64(5)?)
+                    .map_err(|_| NbtError::MalformedNbt("VarInt exceeds 32 bits".to_string()))?;
+                ((v >> 1) as i32) ^ -((v & 1) as i32)
+            }
+        })
+    }
+
+    pub(crate) fn read_i64(&mut self) -> NbtResult<i64> {
+        Ok(match self.flavor() {
+            NbtFlavor::Java => i64::from_be_bytes(self.read_bytes()?),
+            NbtFlavor::BedrockLittleEndian => i64::from_le_bytes(self.read_bytes()?),
+            NbtFlavor::BedrockNetwork => {
+                let v = self.read_var_u64(10)?;
+                ((v >> 1) as i64) ^ -((v & 1) as i64)
+            }
+        })
+    }
+
+    pub(crate) fn read_f32(&mut self) -> NbtResult<f32> {
+        let bytes = self.read_bytes()?;
+        Ok(match self.flavor() {
+            NbtFlavor::Java => f32::from_be_bytes(bytes),
+            _ => f32::from_le_bytes(bytes),
+        })
+    }
+
+    pub(crate) fn read_f64(&mut self) -> NbtResult<f64> {
+        let bytes = self.read_bytes()?;
+        Ok(match self.flavor() {
+            NbtFlavor::Java => f64::from_be_bytes(bytes),
+            _ => f64::from_le_bytes(bytes),
+        })
+    }
+
+    /// Read a string prefixed by its length, see [NbtWriter::write_str].
+    pub(crate) fn read_string(&mut self) -> NbtResult<String> {
+        let length = match self.flavor() {
+            NbtFlavor::Java => usize::from(u16::from_be_bytes(self.read_bytes()?)),
+            NbtFlavor::BedrockLittleEndian => usize::from(u16::from_le_bytes(self.read_bytes()?)),
+            NbtFlavor::BedrockNetwork => usize::try_from(self.read_var_u64(5)?)?,
+        };
+        let bytes = self.read_vec(length)?;
+
+        match self.flavor() {
+            NbtFlavor::Java => Ok(mutf8::decode(&bytes)?.into_owned()),
+            _ => Ok(String::from_utf8(bytes)?),
+        }
+    }
+
+    fn read_var_u64(&mut self, max_length: usize) -> NbtResult<u64> {
+        let mut value = 0;
+        for i in 0..max_length {
+            let [byte] = self.read_bytes()?;
+            value |= u64::from(byte & 0x7f) << (7 * i);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(NbtError::MalformedNbt(format!(
+            "VarInt is longer than {max_length} bytes"
+        )))
+    }
+}
+
+fn zigzag_32(v: i32) -> u32 {
+    ((v << 1) ^ (v >> 31)) as u32
+}
+
+fn zigzag_64(v: i64) -> u64 {
+    ((v << 1) ^ (v >> 63)) as u64
+}
+
+/// Read a Bedrock `level.dat`, i.e. little-endian NBT prefixed by an 8 byte header containing the
+/// storage version & the length of the NBT. Returns the storage version together with the NBT.
+pub fn read_bedrock_level_dat<R: Read>(r: &mut R) -> NbtResult<(i32, Option<NbtNamedTag>)> {
+    let mut header = [0u8; 8];
+    r.read_exact(&mut header)?;
+    let (version, length) = header.split_at(4);
+    let version = i32::from_le_bytes(version.try_into().expect("Split the header in half"));
+    let length = u32::from_le_bytes(length.try_into().expect("Split the header in half"));
+
+    let mut reader =
+        NbtReader::new(r, NbtLimits::default()).with_flavor(NbtFlavor::BedrockLittleEndian);
+    let nbt = NbtNamedTag::read_limited(&mut reader)?;
+    if reader.bytes_read() != usize::try_from(length)? {
+        return Err(NbtError::MalformedNbt(format!(
+            "level.dat header claims {length} bytes of NBT but it contained {}",
+            reader.bytes_read()
+        )));
+    }
+
+    Ok((version, nbt))
+}
+
+/// Write [nbt] as a Bedrock `level.dat` with the provided storage [version], see [read_bedrock_level_dat].
+pub fn write_bedrock_level_dat<W: Write>(
+    w: &mut W,
+    version: i32,
+    nbt: &NbtNamedTag,
+) -> NbtResult<()> {
+    let mut writer = NbtWriter::new(Vec::new(), NbtFlavor::BedrockLittleEndian);
+    nbt.write_flavored(&mut writer)?;
+    let bytes = writer.into_inner();
+
+    w.write_all(&version.to_le_bytes())?;
+    w.write_all(&u32::try_from(bytes.len())?.to_le_bytes())?;
+    w.write_all(&bytes)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use serde::Deserialize;
+
+    use crate::{ser::deserializer::Deserializer, snbt::Snbt};
+
+    use super::*;
+
+    fn named(snbt: &str) -> NbtNamedTag {
+        NbtNamedTag::try_from(&Snbt::from(snbt)).unwrap()
+    }
+
+    #[test]
+    fn test_bedrock_level_dat() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        #[serde(rename_all = "PascalCase")]
+        struct LevelDat {
+            storage_version: i32,
+            level_name: String,
+            random_seed: i64,
+        }
+
+        let mut payload = vec![10, 0, 0];
+        payload.extend([3, 14, 0]);
+        payload.extend(b"StorageVersion");
+        payload.extend([10, 0, 0, 0]);
+        payload.extend([8, 9, 0]);
+        payload.extend(b"LevelName");
+        payload.extend([5, 0]);
+        payload.extend(b"Hello");
+        payload.extend([4, 10, 0]);
+        payload.extend(b"RandomSeed");
+        payload.extend([8, 7, 6, 5, 4, 3, 2, 1]);
+        payload.push(0);
+
+        let mut level_dat = vec![10, 0, 0, 0, payload.len() as u8, 0, 0, 0];
+        level_dat.extend(&payload);
+
+        let (version, nbt) = read_bedrock_level_dat(&mut Cursor::new(&level_dat)).unwrap();
+        let nbt = nbt.unwrap();
+        assert_eq!(version, 10);
+        assert_eq!(
+            nbt,
+            named(r#"{StorageVersion:10,LevelName:"Hello",RandomSeed:72623859790382856L}"#)
+        );
+        assert_eq!(
+            LevelDat::deserialize(Deserializer::from_nbt_tag(nbt.payload.clone())).unwrap(),
+            LevelDat {
+                storage_version: 10,
+                level_name: "Hello".to_string(),
+                random_seed: 0x0102030405060708,
+            }
+        );
+
+        let mut written = Vec::new();
+        write_bedrock_level_dat(&mut written, version, &nbt).unwrap();
+        assert_eq!(written, level_dat);
+
+        level_dat[4] += 1;
+        assert!(matches!(
+            read_bedrock_level_dat(&mut Cursor::new(&level_dat)),
+            Err(NbtError::MalformedNbt(_))
+        ));
+    }
+
+    #[test]
+    fn test_bedrock_network() {
+        let bytes = [
+            10, 0, // Root compound without a name.
+            3, 1, b'a', 5, // Int -3 as a zigzag VarInt.
+            4, 1, b'b', 0xd8, 0x04, // Long 300 as a zigzag VarLong.
+            9, 1, b'c', 2, 4, 1, 0, 2, 0, // List of 2 little-endian shorts.
+            8, 1, b'd', 2, b'h', b'i', // String with an unsigned VarInt length.
+            11, 1, b'e', 4, 1, 0x80, 0x01, // Int array with -1 & 64.
+            0,
+        ];
+        let expected = named(r#"{a:-3,b:300L,c:[1s,2s],d:"hi",e:[I;-1,64]}"#);
+
+        let mut reader =
+            NbtReader::new(&bytes[..], NbtLimits::NETWORK).with_flavor(NbtFlavor::BedrockNetwork);
+        assert_eq!(
+            NbtNamedTag::read_limited(&mut reader).unwrap(),
+            Some(expected.clone())
+        );
+        assert_eq!(reader.bytes_read(), bytes.len());
+
+        let mut writer = NbtWriter::new(Vec::new(), NbtFlavor::BedrockNetwork);
+        expected.write_flavored(&mut writer).unwrap();
+        assert_eq!(writer.into_inner(), bytes);
+
+        // The same tag is read back from every flavor.
+        for flavor in [NbtFlavor::Java, NbtFlavor::BedrockLittleEndian] {
+            let mut writer = NbtWriter::new(Vec::new(), flavor);
+            expected.write_flavored(&mut writer).unwrap();
+            let written = writer.into_inner();
+            let mut reader = NbtReader::new(&written[..], NbtLimits::default()).with_flavor(flavor);
+            assert_eq!(
+                NbtNamedTag::read_limited(&mut reader).unwrap(),
+                Some(expected.clone())
+            );
+        }
+    }
+}
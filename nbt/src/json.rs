@@ -0,0 +1,355 @@
+use serde_json::{Map, Number, Value};
+
+use crate::{
+    NbtResult,
+    error::NbtError,
+    nbt_named_tag::NbtNamedTag,
+    nbt_types::{
+        NbtByte, NbtByteArray, NbtCompound, NbtDouble, NbtFloat, NbtInt, NbtIntArray, NbtList,
+        NbtLong, NbtLongArray, NbtShort, NbtString,
+    },
+    tag_type::NbtTagType,
+};
+
+/// How NBT is represented as JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonMode {
+    /// Plain JSON meant for humans, e.g. `{"Count":1,"id":"minecraft:stone"}`. Tag types are lost
+    /// so converting back infers them: integers become ints or longs, other numbers doubles, booleans
+    /// bytes & arrays lists.
+    #[default]
+    Lossy,
+    /// Every tag is encoded as `{"type":"long_array","value":[...]}` so the JSON converts back to
+    /// identical binary NBT. Non-finite floats & doubles are written as `"NaN"`, `"Infinity"` &
+    /// `"-Infinity"`.
+    Typed,
+}
+
+/// Convert [tag] to JSON in the provided [mode].
+pub fn to_json(tag: &NbtTagType, mode: JsonMode) -> Value {
+    match mode {
+        JsonMode::Lossy => to_lossy(tag),
+        JsonMode::Typed => to_typed(tag),
+    }
+}
+
+/// Convert JSON written in the provided [mode] back to NBT.
+pub fn from_json(value: &Value, mode: JsonMode) -> NbtResult<NbtTagType> {
+    match mode {
+        JsonMode::Lossy => from_lossy(value),
+        JsonMode::Typed => from_typed(value),
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> NbtError {
+    NbtError::InvalidJson(reason.into())
+}
+
+fn to_lossy(tag: &NbtTagType) -> Value {
+    match tag {
+        NbtTagType::TagEnd => Value::Null,
+        NbtTagType::TagByte(v) => v.0.into(),
+        NbtTagType::TagShort(v) => v.0.into(),
+        NbtTagType::TagInt(v) => v.0.into(),
+        NbtTagType::TagLong(v) => v.0.into(),
+        // Non-finite numbers don't exist in JSON & become null. Floats go through their shortest
+        // representation so `0.1f` becomes `0.1` rather than `0.10000000149011612`.
+        NbtTagType::TagFloat(v) => {
+            v.0.to_string()
+                .parse::<f64>()
+                .map_or(Value::Null, Value::from)
+        }
+        NbtTagType::TagDouble(v) => v.0.into(),
+        NbtTagType::TagByteArray(v) => v.0.iter().map(|b| b.0).collect(),
+        NbtTagType::TagString(v) => v.0.clone().into(),
+        NbtTagType::TagList(v) => v.0.iter().map(to_lossy).collect(),
+        NbtTagType::TagCompound(v) => Value::Object(
+            v.0.iter()
+                .map(|tag| (tag.name.0.clone(), to_lossy(&tag.payload)))
+                .collect(),
+        ),
+        NbtTagType::TagIntArray(v) => v.0.iter().map(|i| i.0).collect(),
+        NbtTagType::TagLongArray(v) => v.0.iter().map(|l| l.0).collect(),
+    }
+}
+
+fn from_lossy(value: &Value) -> NbtResult<NbtTagType> {
+    Ok(match value {
+        Value::Null => return Err(invalid("null has no NBT equivalent")),
+        Value::Bool(b) => NbtTagType::TagByte(NbtByte(i8::from(*b))),
+        Value::Number(n) => match (n.as_i64(), n.as_f64()) {
+            (Some(l), _) => match i32::try_from(l) {
+                Ok(i) => NbtTagType::TagInt(NbtInt(i)),
+                Err(_) => NbtTagType::TagLong(NbtLong(l)),
+            },
+            (None, Some(d)) => NbtTagType::TagDouble(NbtDouble(d)),
+            (None, None) => return Err(invalid(format!("number {n} is out of range"))),
+        },
+        Value::String(s) => NbtTagType::TagString(NbtString(s.clone())),
+        Value::Array(values) => NbtTagType::TagList(NbtList(unify_list(
+            values.iter().map(from_lossy).collect::<NbtResult<_>>()?,
+        )?)),
+        Value::Object(map) => NbtTagType::TagCompound(NbtCompound(
+            map.iter()
+                .map(|(name, value)| {
+                    Ok(NbtNamedTag {
+                        name: NbtString(name.clone()),
+                        payload: from_lossy(value)?,
+                    })
+                })
+                .collect::<NbtResult<_>>()?,
+        )),
+    })
+}
+
+/// NBT lists can only hold a single tag type, widen numbers to the widest type in the list so e.g.
+/// `[1, 3000000000, 0.5]` becomes a list of doubles.
+fn unify_list(items: Vec<NbtTagType>) -> NbtResult<Vec<NbtTagType>> {
+    let Some(widest) = items.iter().map(NbtTagType::get_tag_id).max() else {
+        return Ok(items);
+    };
+    if items.iter().all(|item| item.get_tag_id() == widest) {
+        return Ok(items);
+    }
+
+    let integer = |item: &NbtTagType| match item {
+        NbtTagType::TagByte(b) => Some(i64::from(b.0)),
+        NbtTagType::TagShort(s) => Some(i64::from(s.0)),
+        NbtTagType::TagInt(i) => Some(i64::from(i.0)),
+        NbtTagType::TagLong(l) => Some(l.0),
+        _ => None,
+    };
+
+    items
+        .into_iter()
+        .map(|item| match (integer(&item), widest) {
+            _ if item.get_tag_id() == widest => Ok(item),
+            (Some(i), 3) => Ok(NbtTagType::TagInt(NbtInt(i as i32))),
+            (Some(l), 4) => Ok(NbtTagType::TagLong(NbtLong(l))),
+            (Some(l), 6) => Ok(NbtTagType::TagDouble(NbtDouble(l as f64))),
+            _ => Err(invalid("list contains elements of different types")),
+        })
+        .collect()
+}
+
+fn typed_float(v: f64) -> Value {
+    match Number::from_f64(v) {
+        Some(n) => Value::Number(n),
+        None if v.is_nan() => "NaN".into(),
+        None if v > 0.0 => "Infinity".into(),
+        None => "-Infinity".into(),
+    }
+}
+
+fn to_typed(tag: &NbtTagType) -> Value {
+    let value = match tag {
+        NbtTagType::TagEnd => Value::Null,
+        // Every f32 is exactly representable as f64, so nothing is lost by widening.
+        NbtTagType::TagFloat(v) => typed_float(v.0.into()),
+        NbtTagType::TagDouble(v) => typed_float(v.0),
+        NbtTagType::TagList(v) => v.0.iter().map(to_typed).collect(),
+        NbtTagType::TagCompound(v) => Value::Object(
+            v.0.iter()
+                .map(|tag| (tag.name.0.clone(), to_typed(&tag.payload)))
+                .collect(),
+        ),
+        tag => to_lossy(tag),
+    };
+
+    let mut object = Map::new();
+    object.insert("type".to_string(), tag.type_name().into());
+    if !value.is_null() {
+        object.insert("value".to_string(), value);
+    }
+    Value::Object(object)
+}
+
+fn typed_integer<T: TryFrom<i64>>(value: &Value) -> NbtResult<T> {
+    value
+        .as_i64()
+        .and_then(|v| T::try_from(v).ok())
+        .ok_or_else(|| {
+            invalid(format!(
+                "{value} is not a valid {}",
+                std::any::type_name::<T>()
+            ))
+        })
+}
+
+fn typed_double(value: &Value) -> NbtResult<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => match s.as_str() {
+            "NaN" => Some(f64::NAN),
+            "Infinity" => Some(f64::INFINITY),
+            "-Infinity" => Some(f64::NEG_INFINITY),
+            _ => None,
+        },
+        _ => None,
+    }
+    .ok_or_else(|| invalid(format!("{value} is not a valid floating point number")))
+}
+
+fn typed_array(value: &Value) -> NbtResult<&Vec<Value>> {
+    value
+        .as_array()
+        .ok_or_else(|| invalid(format!("expected an array but found {value}")))
+}
+
+fn from_typed(value: &Value) -> NbtResult<NbtTagType> {
+    let type_name = value
+        .get("type")
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid(format!("expected an object with a type but found {value}")))?;
+    let value = value.get("value").unwrap_or(&Value::Null);
+
+    Ok(match type_name {
+        "end" => NbtTagType::TagEnd,
+        "byte" => NbtTagType::TagByte(NbtByte(typed_integer(value)?)),
+        "short" => NbtTagType::TagShort(NbtShort(typed_integer(value)?)),
+        "int" => NbtTagType::TagInt(NbtInt(typed_integer(value)?)),
+        "long" => NbtTagType::TagLong(NbtLong(typed_integer(value)?)),
+        "float" => NbtTagType::TagFloat(NbtFloat(typed_double(value)? as f32)),
+        "double" => NbtTagType::TagDouble(NbtDouble(typed_double(value)?)),
+        "byte_array" => NbtTagType::TagByteArray(NbtByteArray(
+            typed_array(value)?
+                .iter()
+                .map(|b| typed_integer(b).map(NbtByte))
+                .collect::<NbtResult<_>>()?,
+        )),
+        "string" => NbtTagType::TagString(NbtString(
+            value
+                .as_str()
+                .ok_or_else(|| invalid(format!("expected a string but found {value}")))?
+                .to_string(),
+        )),
+        "list" => {
+            let items: Vec<_> = typed_array(value)?
+                .iter()
+                .map(from_typed)
+                .collect::<NbtResult<_>>()?;
+            if items
+                .windows(2)
+                .any(|w| w[0].get_tag_id() != w[1].get_tag_id())
+            {
+                return Err(invalid("list contains elements of different types"));
+            }
+            NbtTagType::TagList(NbtList(items))
+        }
+        "compound" => NbtTagType::TagCompound(NbtCompound(
+            value
+                .as_object()
+                .ok_or_else(|| invalid(format!("expected an object but found {value}")))?
+                .iter()
+                .map(|(name, value)| {
+                    Ok(NbtNamedTag {
+                        name: NbtString(name.clone()),
+                        payload: from_typed(value)?,
+                    })
+                })
+                .collect::<NbtResult<_>>()?,
+        )),
+        "int_array" => NbtTagType::TagIntArray(NbtIntArray(
+            typed_array(value)?
+                .iter()
+                .map(|i| typed_integer(i).map(NbtInt))
+                .collect::<NbtResult<_>>()?,
+        )),
+        "long_array" => NbtTagType::TagLongArray(NbtLongArray(
+            typed_array(value)?
+                .iter()
+                .map(|l| typed_integer(l).map(NbtLong))
+                .collect::<NbtResult<_>>()?,
+        )),
+        t => return Err(invalid(format!("unknown tag type {t}"))),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::snbt::Snbt;
+
+    use super::*;
+
+    fn binary(tag: &NbtTagType) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        tag.write_network(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_typed_round_trip() {
+        let NbtTagType::TagCompound(mut compound) = NbtTagType::try_from(&Snbt::from(
+            r#"{z:1b,short:-2s,int:3,long:9007199254740993L,double:0.1d,string:"a\"b",
+                byte_array:[B;1b,-2b],int_array:[I;3,4],long_array:[L;-9223372036854775808L],
+                list:[{a:1.5f},{}],empty:[],nested:{list:[[I;1],[I;]]}}"#,
+        ))
+        .unwrap() else {
+            unreachable!()
+        };
+        for (name, payload) in [
+            ("float", NbtTagType::TagFloat(NbtFloat(0.1))),
+            ("nan", NbtTagType::TagFloat(NbtFloat(f32::NAN))),
+            ("inf", NbtTagType::TagDouble(NbtDouble(f64::NEG_INFINITY))),
+            ("zero", NbtTagType::TagDouble(NbtDouble(-0.0))),
+        ] {
+            compound.0.push(NbtNamedTag {
+                name: NbtString(name.to_string()),
+                payload,
+            });
+        }
+        let nbt = NbtTagType::TagCompound(compound);
+
+        let text = to_json(&nbt, JsonMode::Typed).to_string();
+        let value: Value = serde_json::from_str(&text).unwrap();
+        let back = from_json(&value, JsonMode::Typed).unwrap();
+
+        // Compare the bytes, NaN != NaN.
+        assert_eq!(binary(&back), binary(&nbt));
+        assert_eq!(
+            to_json(
+                &NbtTagType::TagLongArray(NbtLongArray(vec![NbtLong(1)])),
+                JsonMode::Typed
+            ),
+            json!({"type": "long_array", "value": [1]})
+        );
+    }
+
+    #[test]
+    fn test_lossy() {
+        let nbt = NbtTagType::try_from(&Snbt::from(
+            r#"{Count:1b,id:"minecraft:stone",pos:[I;1,2,3],rot:[0.5f,1.0f]}"#,
+        ))
+        .unwrap();
+        let value = to_json(&nbt, JsonMode::Lossy);
+        assert_eq!(
+            value,
+            json!({"Count": 1, "id": "minecraft:stone", "pos": [1, 2, 3], "rot": [0.5, 1.0]})
+        );
+
+        let inferred = NbtTagType::try_from(&Snbt::from(
+            r#"{Count:1,id:"minecraft:stone",pos:[1,2,3],rot:[0.5d,1.0d]}"#,
+        ))
+        .unwrap();
+        assert_eq!(from_json(&value, JsonMode::Lossy).unwrap(), inferred);
+
+        assert_eq!(
+            from_json(&json!([1, 3000000000i64, true]), JsonMode::Lossy).unwrap(),
+            NbtTagType::TagList(NbtList(vec![
+                NbtTagType::TagLong(NbtLong(1)),
+                NbtTagType::TagLong(NbtLong(3000000000)),
+                NbtTagType::TagLong(NbtLong(1)),
+            ]))
+        );
+        assert!(matches!(
+            from_json(&json!([1, "a"]), JsonMode::Lossy),
+            Err(NbtError::InvalidJson(_))
+        ));
+        assert!(matches!(
+            from_json(&json!({"a": null}), JsonMode::Lossy),
+            Err(NbtError::InvalidJson(_))
+        ));
+    }
+}
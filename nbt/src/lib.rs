@@ -6,22 +6,40 @@
 
 use std::{
     fs::File,
-    io::{Cursor, Read},
+    io::{BufReader, BufWriter},
     path::Path,
 };
 
-use flate2::read::GzDecoder;
-
-use crate::{error::NbtResult, nbt_named_tag::NbtNamedTag};
+use crate::{
+    compression::{NbtCompression, read_compressed, write_compressed},
+    error::NbtResult,
+    nbt_named_tag::NbtNamedTag,
+};
 
+/// Reading & writing gzip, zlib or uncompressed NBT.
+pub mod compression;
+/// Structural diffs & patches of NBT trees.
+pub mod diff;
 /// Error types for this crate.
 pub mod error;
+/// The Java & Bedrock binary encodings of NBT.
+pub mod flavor;
+/// Conversions between NBT & JSON.
+pub mod json;
+/// Limits for reading untrusted NBT.
+pub mod limits;
+/// Modified UTF-8, the string encoding used by NBT.
+pub mod mutf8;
 /// NBT Named Tag implementation.
 pub mod nbt_named_tag;
+/// NBT paths as used by the `/data` command, for querying & modifying NBT trees.
+pub mod nbt_path;
 /// NBT type implementations.
 pub mod nbt_types;
 /// Deserialize impl for nbt format.
 pub mod nbt_value;
+/// Schemas describing the expected shape of NBT, for validating it.
+pub mod schema;
 /// Serde implementations for NBT.
 pub mod ser;
 /// SNBT (Serialized Named Binary Tag) implementation.
@@ -29,30 +47,55 @@ pub mod snbt;
 /// Tag type, wrapper for all NBT types.
 pub mod tag_type;
 
-/// Read & parse a gzipped NBT file from the provided path.
+/// Read & parse an NBT file from the provided path, the compression is detected automatically.
 pub fn read_nbt_file(path: &Path) -> NbtResult<Option<NbtNamedTag>> {
-    let file = File::open(path)?;
-    let mut decoder = GzDecoder::new(file);
-
-    let mut file_content = Vec::new();
-    decoder.read_to_end(&mut file_content)?;
+    Ok(read_nbt_file_with_compression(path)?.0)
+}
 
-    let mut file_content = Cursor::new(file_content);
+/// Same as [read_nbt_file] but also returns the compression of the file, so it can be written back the same way.
+pub fn read_nbt_file_with_compression(
+    path: &Path,
+) -> NbtResult<(Option<NbtNamedTag>, NbtCompression)> {
+    let file = File::open(path)?;
+    read_compressed(BufReader::new(file))
+}
 
-    let nbt = NbtNamedTag::read(&mut file_content)?;
+/// Write the provided [NbtNamedTag] as a gzipped NBT file to the provided path, overwriting any existing file.
+pub fn write_nbt_file(path: &Path, nbt: &NbtNamedTag) -> NbtResult<()> {
+    write_nbt_file_with_compression(path, nbt, NbtCompression::Gzip)
+}
 
-    Ok(nbt)
+/// Same as [write_nbt_file] but with the provided [compression].
+pub fn write_nbt_file_with_compression(
+    path: &Path,
+    nbt: &NbtNamedTag,
+    compression: NbtCompression,
+) -> NbtResult<()> {
+    let file = File::create(path)?;
+    write_compressed(BufWriter::new(file), nbt, compression)
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::BTreeMap;
+    use std::{collections::BTreeMap, io::Cursor};
 
     use serde::{Deserialize, Serialize};
 
     use crate::{
-        nbt_types::{NbtCompound, NbtString},
-        ser::serializer::to_nbt_tag_type,
+        error::NbtError,
+        limits::{NbtLimits, NbtReader},
+        nbt_types::{
+            NbtByte, NbtByteArray, NbtCompound, NbtDouble, NbtFloat, NbtInt, NbtIntArray, NbtList,
+            NbtLong, NbtLongArray, NbtShort, NbtString,
+        },
+        nbt_value::value::NbtValue,
+        ser::{
+            array::{ByteArray, IntArray},
+            serializer::to_nbt_tag_type,
+            slice_deserializer::{LongArrayView, from_slice},
+            stream_deserializer::from_reader,
+            stream_serializer::to_writer,
+        },
         snbt::Snbt,
         tag_type::NbtTagType,
     };
@@ -86,12 +129,660 @@ mod tests {
         let pepe = Pepe::deserialize(deserializer).expect("Failed to deserialize map");
         let serialized = to_nbt_tag_type(&pepe).expect("Failed to serialize map");
 
-        let input: Snbt = (&input).into();
-        let serialized: Snbt = (&serialized.unwrap()).into();
+        let input = Snbt::try_from(&input).unwrap();
+        let serialized = Snbt::try_from(&serialized.unwrap()).unwrap();
 
         assert_eq!(serialized.to_string(), input.to_string());
     }
 
+    fn named(name: &str, payload: NbtTagType) -> NbtNamedTag {
+        NbtNamedTag {
+            name: NbtString(name.to_string()),
+            payload,
+        }
+    }
+
+    fn all_tag_types() -> NbtNamedTag {
+        named(
+            "root",
+            NbtTagType::TagCompound(NbtCompound(vec![
+                named("byte", NbtTagType::TagByte(NbtByte(-12))),
+                named("short", NbtTagType::TagShort(NbtShort(-1234))),
+                named("int", NbtTagType::TagInt(NbtInt(123456789))),
+                named("long", NbtTagType::TagLong(NbtLong(-1234567890123))),
+                named("float", NbtTagType::TagFloat(NbtFloat(0.5))),
+                named("double", NbtTagType::TagDouble(NbtDouble(-0.125))),
+                named(
+                    "byte_array",
+                    NbtTagType::TagByteArray(NbtByteArray(vec![NbtByte(1), NbtByte(-1)])),
+                ),
+                named(
+                    "string",
+                    NbtTagType::TagString(NbtString("Bananrama".into())),
+                ),
+                named(
+                    "list",
+                    NbtTagType::TagList(NbtList(vec![
+                        NbtTagType::TagShort(NbtShort(1)),
+                        NbtTagType::TagShort(NbtShort(2)),
+                    ])),
+                ),
+                named("empty_list", NbtTagType::TagList(NbtList(vec![]))),
+                named(
+                    "compound",
+                    NbtTagType::TagCompound(NbtCompound(vec![named(
+                        "nested",
+                        NbtTagType::TagCompound(NbtCompound(vec![])),
+                    )])),
+                ),
+                named(
+                    "int_array",
+                    NbtTagType::TagIntArray(NbtIntArray(vec![NbtInt(i32::MIN), NbtInt(i32::MAX)])),
+                ),
+                named(
+                    "long_array",
+                    NbtTagType::TagLongArray(NbtLongArray(vec![
+                        NbtLong(i64::MIN),
+                        NbtLong(i64::MAX),
+                    ])),
+                ),
+            ])),
+        )
+    }
+
+    #[test]
+    fn test_tree_deserializer_bools() {
+        #[derive(Deserialize)]
+        struct Flag {
+            flag: bool,
+        }
+
+        // Regression test, 0 used to be read as true & 1 as false. Any non-zero byte is true, the
+        // same for every deserializer.
+        for (byte, expected) in [(0, false), (1, true), (2, true), (-1, true)] {
+            let tree = bool::deserialize(ser::deserializer::Deserializer::from_nbt_tag(
+                NbtTagType::TagByte(NbtByte(byte)),
+            ));
+            assert_eq!(tree.unwrap(), expected);
+
+            let bytes = [&[10, 0, 0, 1, 0, 4][..], b"flag", &[byte as u8, 0]].concat();
+            let stream: Flag = ser::stream_deserializer::from_reader(&mut &bytes[..]).unwrap();
+            assert_eq!(stream.flag, expected);
+            let slice: Flag = ser::slice_deserializer::from_slice(&bytes).unwrap();
+            assert_eq!(slice.flag, expected);
+        }
+
+        for value in [true, false] {
+            let tag = to_nbt_tag_type(&value).unwrap().unwrap();
+            let read = bool::deserialize(ser::deserializer::Deserializer::from_nbt_tag(tag));
+            assert_eq!(read.unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_write_hello_world() {
+        // hello_world.nbt from the NBT specification.
+        let expected: Vec<u8> = [
+            &[0x0a, 0x00, 0x0b][..],
+            b"hello world",
+            &[0x08, 0x00, 0x04],
+            b"name",
+            &[0x00, 0x09],
+            b"Bananrama",
+            &[0x00],
+        ]
+        .concat();
+
+        let nbt = named(
+            "hello world",
+            NbtTagType::TagCompound(NbtCompound(vec![named(
+                "name",
+                NbtTagType::TagString(NbtString("Bananrama".to_string())),
+            )])),
+        );
+
+        let mut written = Vec::new();
+        nbt.write(&mut written).expect("Failed to write NBT");
+
+        assert_eq!(written, expected);
+    }
+
+    #[test]
+    fn test_write_read_round_trip() {
+        let nbt = all_tag_types();
+
+        let mut written = Vec::new();
+        nbt.write(&mut written).expect("Failed to write NBT");
+
+        let read = NbtNamedTag::read(&mut Cursor::new(&written))
+            .expect("Failed to read written NBT")
+            .expect("Expected a root tag");
+        assert_eq!(read, nbt);
+
+        let mut rewritten = Vec::new();
+        read.write(&mut rewritten).expect("Failed to rewrite NBT");
+        assert_eq!(rewritten, written);
+    }
+
+    #[test]
+    fn test_write_list_with_mixed_types_fails() {
+        let list = NbtTagType::TagList(NbtList(vec![
+            NbtTagType::TagByte(NbtByte(1)),
+            NbtTagType::TagInt(NbtInt(1)),
+        ]));
+
+        let result = list.write(&mut Vec::new());
+        assert!(matches!(result, Err(NbtError::MalformedNbt(_))));
+    }
+
+    #[test]
+    fn test_write_nbt_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("nbt-write-test-{}.dat", std::process::id()));
+        let nbt = all_tag_types();
+
+        write_nbt_file(&path, &nbt).expect("Failed to write NBT file");
+        let read = read_nbt_file(&path).expect("Failed to read NBT file");
+        std::fs::remove_file(&path).expect("Failed to remove NBT file");
+
+        assert_eq!(read, Some(nbt));
+    }
+
+    #[test]
+    fn test_network_nbt_round_trip() {
+        let nbt = all_tag_types();
+        let mut bytes = Vec::new();
+        nbt.payload.write_network(&mut bytes).unwrap();
+
+        // Nameless root, the compound payload starts right after the tag ID.
+        assert_eq!(bytes[0], 10);
+        assert_eq!(bytes[1], 1);
+
+        let read = NbtTagType::read_network(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(read, nbt.payload);
+
+        let absent = NbtTagType::read_network(&mut Cursor::new(vec![0])).unwrap();
+        assert_eq!(absent, NbtTagType::TagEnd);
+    }
+
+    #[test]
+    fn test_read_limits() {
+        let mut bytes = Vec::new();
+        all_tag_types().write(&mut bytes).unwrap();
+
+        let limited = |limits| NbtNamedTag::read_limited(&mut NbtReader::new(&bytes[..], limits));
+        let mut reader = NbtReader::new(&bytes[..], NbtLimits::NETWORK);
+        assert_eq!(
+            NbtNamedTag::read_limited(&mut reader).unwrap(),
+            Some(all_tag_types())
+        );
+        assert_eq!(reader.bytes_read(), bytes.len());
+
+        for (limits, limit) in [
+            (
+                NbtLimits {
+                    max_bytes: bytes.len() - 1,
+                    ..NbtLimits::UNLIMITED
+                },
+                "size in bytes",
+            ),
+            (
+                NbtLimits {
+                    max_depth: 1,
+                    ..NbtLimits::UNLIMITED
+                },
+                "nesting depth",
+            ),
+            (
+                NbtLimits {
+                    max_array_length: 1,
+                    ..NbtLimits::UNLIMITED
+                },
+                "array length",
+            ),
+        ] {
+            match limited(limits) {
+                Err(NbtError::LimitExceeded { limit: l, .. }) => assert_eq!(l, limit),
+                other => panic!("Expected {limit} to be exceeded, got {other:?}"),
+            }
+        }
+
+        // A huge length prefix is rejected before anything is allocated.
+        let huge_array = [7, 0x7f, 0xff, 0xff, 0xff];
+        assert!(matches!(
+            NbtTagType::read_network_limited(&mut NbtReader::new(
+                &huge_array[..],
+                NbtLimits::NETWORK
+            )),
+            Err(NbtError::LimitExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_serde_read_limits() {
+        // A root list followed by TAG_Lists of TAG_Lists, far deeper than the stack allows.
+        let mut nested = vec![9, 0, 0, 9];
+        nested.extend([0, 0, 0, 1, 9].repeat(200_000));
+        nested.extend([0, 0, 0, 0]);
+
+        let is_limit = |result: Result<NbtValue, ser::Error>, expected: &str| match result {
+            Err(ser::Error::Nbt(NbtError::LimitExceeded { limit, .. })) => limit == expected,
+            _ => false,
+        };
+        assert!(is_limit(from_reader(&mut &nested[..]), "nesting depth"));
+        assert!(is_limit(
+            ser::slice_deserializer::from_slice(&nested),
+            "nesting depth"
+        ));
+
+        let mut bytes = Vec::new();
+        all_tag_types().write(&mut bytes).unwrap();
+        let limits = NbtLimits {
+            max_bytes: bytes.len() - 1,
+            ..NbtLimits::NETWORK
+        };
+        assert!(is_limit(
+            ser::stream_deserializer::from_reader_with_limits(&mut &bytes[..], limits),
+            "size in bytes"
+        ));
+        assert!(is_limit(
+            ser::slice_deserializer::from_slice_with_limits(&bytes, limits),
+            "size in bytes"
+        ));
+        assert!(ser::slice_deserializer::from_slice::<NbtValue>(&bytes).is_ok());
+    }
+
+    #[test]
+    fn test_read_huge_declared_length() {
+        // Lengths within max_array_length, but with nothing after them.
+        let long_array = [12, 0x00, 0x20, 0x00, 0x00];
+        let nested_lists = [9, 9, 0x00, 0x20, 0x00, 0x00].repeat(512);
+        for bytes in [&long_array[..], &nested_lists[..]] {
+            let mut reader = NbtReader::new(bytes, NbtLimits::NETWORK);
+            assert!(matches!(
+                NbtTagType::read_network_limited(&mut reader),
+                Err(NbtError::IoError(_) | NbtError::LimitExceeded { .. })
+            ));
+        }
+
+        // What gets reserved is bounded by the remaining bytes, not the declared length.
+        let limits = NbtLimits {
+            max_bytes: 1024,
+            ..NbtLimits::NETWORK
+        };
+        let reader = NbtReader::new(&[][..], limits);
+        assert_eq!(reader.capacity_for(0x200000, size_of::<i64>()), 128);
+        assert_eq!(reader.capacity_for(10, size_of::<i64>()), 10);
+        let reader = NbtReader::new(&[][..], NbtLimits::UNLIMITED);
+        assert!(reader.capacity_for(i32::MAX as usize, 1) <= 4096);
+    }
+
+    #[test]
+    fn test_modified_utf8_strings() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Sign {
+            text: String,
+        }
+
+        // A compound with the string "\u{1F600}\0" as Java writes it, a surrogate pair followed by 0xC0 0x80.
+        let java = [
+            10, 0, 0, 8, 0, 4, b't', b'e', b'x', b't', 0, 8, 0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80,
+            0xC0, 0x80, 0,
+        ];
+        let sign = Sign {
+            text: "\u{1F600}\0".to_string(),
+        };
+
+        let tag = NbtNamedTag::read(&mut Cursor::new(&java)).unwrap().unwrap();
+        assert_eq!(
+            tag.payload,
+            NbtTagType::TagCompound(NbtCompound(vec![named(
+                "text",
+                NbtTagType::TagString(NbtString(sign.text.clone()))
+            )]))
+        );
+        let mut written = Vec::new();
+        tag.write(&mut written).unwrap();
+        assert_eq!(written, java);
+
+        let streamed: Sign = from_reader(&mut Cursor::new(&java)).unwrap();
+        assert_eq!(streamed, sign);
+        let borrowed: Sign = from_slice(&java).unwrap();
+        assert_eq!(borrowed, sign);
+        let mut written = Vec::new();
+        to_writer(&mut written, &sign).unwrap();
+        assert_eq!(written, java);
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Streamed {
+        flag: bool,
+        byte: i8,
+        short: i16,
+        int: i32,
+        long: i64,
+        float: f32,
+        double: f64,
+        name: String,
+        missing: Option<i32>,
+        present: Option<String>,
+        longs: Vec<i64>,
+        empty: Vec<i32>,
+        nested: Vec<StreamedInner>,
+        map: BTreeMap<String, StreamedInner>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct StreamedInner {
+        values: Vec<Vec<String>>,
+    }
+
+    fn streamed() -> Streamed {
+        let inner = || StreamedInner {
+            values: vec![vec!["a".to_string(), "b".to_string()], vec![]],
+        };
+
+        Streamed {
+            flag: true,
+            byte: -1,
+            short: 300,
+            int: -70000,
+            long: i64::MAX,
+            float: 1.5,
+            double: -2.25,
+            name: "Bananrama".to_string(),
+            missing: None,
+            present: Some("here".to_string()),
+            longs: vec![1, -2, 3],
+            empty: vec![],
+            nested: vec![inner(), inner()],
+            map: BTreeMap::from([("first".to_string(), inner())]),
+        }
+    }
+
+    #[test]
+    fn test_stream_serializer_matches_tree_serializer() {
+        let value = streamed();
+
+        let mut streamed_bytes = Vec::new();
+        to_writer(&mut streamed_bytes, &value).expect("Failed to stream NBT");
+
+        let tree = NbtNamedTag {
+            name: NbtString(String::new()),
+            payload: to_nbt_tag_type(&value).unwrap().unwrap(),
+        };
+        let mut tree_bytes = Vec::new();
+        tree.write(&mut tree_bytes).unwrap();
+
+        assert_eq!(streamed_bytes, tree_bytes);
+    }
+
+    #[test]
+    fn test_stream_round_trip() {
+        let value = streamed();
+
+        let mut bytes = Vec::new();
+        to_writer(&mut bytes, &value).unwrap();
+
+        let read: Streamed = from_reader(&mut Cursor::new(&bytes)).expect("Failed to read NBT");
+        assert_eq!(read, value);
+
+        let tree = NbtNamedTag::read(&mut Cursor::new(&bytes))
+            .unwrap()
+            .unwrap();
+        let from_tree =
+            Streamed::deserialize(ser::deserializer::Deserializer::from_nbt_tag(tree.payload))
+                .unwrap();
+        assert_eq!(from_tree, value);
+    }
+
+    /// Claims a length of [claimed] but serializes [actual] elements.
+    struct WrongLength {
+        claimed: usize,
+        actual: usize,
+    }
+
+    impl Serialize for WrongLength {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeSeq;
+
+            let mut seq = serializer.serialize_seq(Some(self.claimed))?;
+            for i in 0..self.actual {
+                seq.serialize_element(&(i as i32))?;
+            }
+            seq.end()
+        }
+    }
+
+    #[test]
+    fn test_stream_serializer_list_length_mismatch() {
+        let write = |claimed, actual| {
+            let mut map = BTreeMap::new();
+            map.insert("list", WrongLength { claimed, actual });
+            to_writer(&mut Vec::new(), &map)
+        };
+
+        assert!(write(3, 3).is_ok());
+        assert!(write(0, 0).is_ok());
+        assert!(write(3, 2).is_err());
+        assert!(write(3, 0).is_err());
+        assert!(write(2, 3).is_err());
+    }
+
+    #[test]
+    fn test_stream_deserializer_skips_unknown_fields() {
+        #[derive(Deserialize, Debug)]
+        struct OnlyName {
+            name: String,
+            short: i16,
+        }
+
+        let mut bytes = Vec::new();
+        to_writer(&mut bytes, &streamed()).unwrap();
+        let mut reader = Cursor::new(&bytes);
+
+        let read: OnlyName = from_reader(&mut reader).unwrap();
+        assert_eq!(read.name, "Bananrama");
+        assert_eq!(read.short, 300);
+        assert_eq!(reader.position() as usize, bytes.len());
+    }
+
+    #[test]
+    fn test_slice_deserializer_round_trip() {
+        let value = streamed();
+
+        let mut bytes = Vec::new();
+        to_writer(&mut bytes, &value).unwrap();
+
+        let read: Streamed = from_slice(&bytes).expect("Failed to read NBT");
+        assert_eq!(read, value);
+    }
+
+    #[test]
+    fn test_slice_deserializer_borrows() {
+        #[derive(Deserialize, Debug)]
+        struct Borrowed<'a> {
+            name: &'a str,
+            light: &'a [u8],
+            #[serde(borrow)]
+            blocks: Vec<BorrowedBlock<'a>>,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct BorrowedBlock<'a> {
+            id: &'a str,
+            #[serde(borrow)]
+            data: LongArrayView<'a>,
+        }
+
+        let block = |id: &str, data: Vec<i64>| {
+            NbtTagType::TagCompound(NbtCompound(vec![
+                named("id", NbtTagType::TagString(NbtString(id.to_string()))),
+                named(
+                    "data",
+                    NbtTagType::TagLongArray(NbtLongArray(data.into_iter().map(NbtLong).collect())),
+                ),
+            ]))
+        };
+        let tag = named(
+            "",
+            NbtTagType::TagCompound(NbtCompound(vec![
+                named(
+                    "name",
+                    NbtTagType::TagString(NbtString("Bananrama".to_string())),
+                ),
+                named(
+                    "light",
+                    NbtTagType::TagByteArray(NbtByteArray(vec![NbtByte(-1), NbtByte(2)])),
+                ),
+                named(
+                    "blocks",
+                    NbtTagType::TagList(NbtList(vec![
+                        block("minecraft:stone", vec![1, -2, i64::MIN]),
+                        block("minecraft:air", vec![]),
+                    ])),
+                ),
+            ])),
+        );
+        let mut bytes = Vec::new();
+        tag.write(&mut bytes).unwrap();
+
+        let read: Borrowed = from_slice(&bytes).expect("Failed to read NBT");
+        let input = bytes.as_ptr_range();
+        assert_eq!(read.name, "Bananrama");
+        assert!(input.contains(&read.name.as_ptr()));
+        assert_eq!(read.light, [0xff, 2]);
+        assert!(input.contains(&read.light.as_ptr()));
+
+        assert_eq!(read.blocks[0].id, "minecraft:stone");
+        assert_eq!(read.blocks[0].data.len(), 3);
+        assert_eq!(read.blocks[0].data.get(1), Some(-2));
+        assert_eq!(read.blocks[0].data.get(3), None);
+        assert_eq!(read.blocks[0].data.get(usize::MAX / 8), None);
+        assert_eq!(read.blocks[0].data.get(usize::MAX), None);
+        assert_eq!(read.blocks[0].data.to_vec(), vec![1, -2, i64::MIN]);
+        assert_eq!(read.blocks[1].id, "minecraft:air");
+        assert!(read.blocks[1].data.is_empty());
+    }
+
+    #[test]
+    fn test_nbt_value_tag_type_conversion() {
+        let tag = all_tag_types().payload;
+
+        let value = Option::<NbtValue>::from(tag.clone()).unwrap();
+        let NbtValue::Compound(entries) = &value else {
+            panic!("Expected compound, got {value:?}");
+        };
+        let keys: Vec<&str> = entries.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "byte",
+                "short",
+                "int",
+                "long",
+                "float",
+                "double",
+                "byte_array",
+                "string",
+                "list",
+                "empty_list",
+                "compound",
+                "int_array",
+                "long_array"
+            ]
+        );
+
+        assert_eq!(
+            Snbt::try_from(&value).unwrap(),
+            Snbt::try_from(&tag).unwrap()
+        );
+        assert_eq!(NbtTagType::from(value), tag);
+    }
+
+    #[test]
+    fn test_nbt_value_serde_round_trip() {
+        let tag = all_tag_types();
+        let value = Option::<NbtValue>::from(tag.payload.clone()).unwrap();
+        let mut bytes = Vec::new();
+        tag.write(&mut bytes).unwrap();
+
+        let from_tree = NbtValue::deserialize(ser::deserializer::Deserializer::from_nbt_tag(
+            tag.payload.clone(),
+        ))
+        .unwrap();
+        assert_eq!(from_tree, value);
+        let from_stream: NbtValue = from_reader(&mut Cursor::new(&bytes)).unwrap();
+        assert_eq!(from_stream, value);
+        let borrowed: NbtValue = from_slice(&bytes).unwrap();
+        assert_eq!(borrowed, value);
+        let from_value = NbtValue::deserialize(value.clone()).unwrap();
+        assert_eq!(from_value, value);
+
+        assert_eq!(to_nbt_tag_type(&value).unwrap(), Some(tag.payload.clone()));
+        let mut streamed = Vec::new();
+        ser::stream_serializer::to_writer_named(&mut streamed, "root", &value).unwrap();
+        assert_eq!(streamed, bytes);
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Arrays {
+        bytes: ByteArray,
+        ints: Option<IntArray>,
+        #[serde(with = "ser::array::long_array")]
+        longs: Vec<i64>,
+        list: Vec<i64>,
+    }
+
+    #[test]
+    fn test_array_adaptors() {
+        let value = Arrays {
+            bytes: ByteArray(vec![1, -1]),
+            ints: Some(IntArray(vec![])),
+            longs: vec![i64::MIN, 0],
+            list: vec![5],
+        };
+        let tag = NbtTagType::try_from(&Snbt::from(
+            "{bytes:[B;1b,-1b],ints:[I;],longs:[L;-9223372036854775808L,0L],list:[5L]}",
+        ))
+        .unwrap();
+
+        assert_eq!(to_nbt_tag_type(&value).unwrap(), Some(tag.clone()));
+        let mut bytes = Vec::new();
+        to_writer(&mut bytes, &value).unwrap();
+        let mut tree_bytes = Vec::new();
+        named("", tag.clone()).write(&mut tree_bytes).unwrap();
+        assert_eq!(bytes, tree_bytes);
+
+        let from_tree =
+            Arrays::deserialize(ser::deserializer::Deserializer::from_nbt_tag(tag.clone()))
+                .unwrap();
+        assert_eq!(from_tree, value);
+        let from_stream: Arrays = from_reader(&mut Cursor::new(&bytes)).unwrap();
+        assert_eq!(from_stream, value);
+        let borrowed: Arrays = from_slice(&bytes).unwrap();
+        assert_eq!(borrowed, value);
+        let from_value = Arrays::deserialize(Option::<NbtValue>::from(tag).unwrap()).unwrap();
+        assert_eq!(from_value, value);
+
+        // Lists are accepted as well, in case the data was written without array types.
+        let lists = NbtTagType::try_from(&Snbt::from(
+            "{bytes:[1b,-1b],ints:[],longs:[-9223372036854775808L,0L],list:[5L]}",
+        ))
+        .unwrap();
+        let mut list_bytes = Vec::new();
+        named("", lists.clone()).write(&mut list_bytes).unwrap();
+        let from_tree =
+            Arrays::deserialize(ser::deserializer::Deserializer::from_nbt_tag(lists.clone()))
+                .unwrap();
+        assert_eq!(from_tree, value);
+        let from_stream: Arrays = from_reader(&mut Cursor::new(&list_bytes)).unwrap();
+        assert_eq!(from_stream, value);
+        let borrowed: Arrays = from_slice(&list_bytes).unwrap();
+        assert_eq!(borrowed, value);
+        let from_value = Arrays::deserialize(Option::<NbtValue>::from(lists).unwrap()).unwrap();
+        assert_eq!(from_value, value);
+    }
+
     #[derive(Serialize, Deserialize, Debug)]
     struct Pepe {
         my_map: BTreeMap<String, String>,
@@ -0,0 +1,159 @@
+use std::io::Read;
+
+use crate::{NbtResult, error::NbtError, flavor::NbtFlavor};
+
+/// The most elements reserved up front for a list or array, anything longer grows as it's read.
+const MAX_PREALLOCATED_ELEMENTS: usize = 4096;
+
+/// Limits enforced while reading binary NBT, so untrusted input can't make us recurse or allocate
+/// without bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NbtLimits {
+    /// Maximum number of nested lists & compounds.
+    pub max_depth: usize,
+    /// Maximum number of bytes the NBT may take up.
+    pub max_bytes: usize,
+    /// Maximum number of elements in a single list or array.
+    pub max_array_length: usize,
+}
+
+impl NbtLimits {
+    /// No limits at all, only use this for trusted input.
+    pub const UNLIMITED: Self = Self {
+        max_depth: usize::MAX,
+        max_bytes: usize::MAX,
+        max_array_length: usize::MAX,
+    };
+
+    /// The limits vanilla applies to NBT received from clients, 512 levels deep & 2 MiB in size.
+    pub const NETWORK: Self = Self {
+        max_depth: 512,
+        max_bytes: 2 * 1024 * 1024,
+        max_array_length: 2 * 1024 * 1024,
+    };
+}
+
+impl Default for NbtLimits {
+    /// Vanilla limits the depth of all NBT it reads, sizes are only limited for network NBT.
+    fn default() -> Self {
+        Self {
+            max_depth: 512,
+            ..Self::UNLIMITED
+        }
+    }
+}
+
+/// Reader context keeping track of the nesting depth & bytes read, returns
+/// [NbtError::LimitExceeded] when the NBT being read exceeds the [NbtLimits].
+/// Reads [NbtFlavor::Java] NBT unless another flavor is provided with [NbtReader::with_flavor].
+pub struct NbtReader<R: Read> {
+    inner: R,
+    limits: NbtLimits,
+    flavor: NbtFlavor,
+    depth: usize,
+    bytes_read: usize,
+}
+
+impl<R: Read> NbtReader<R> {
+    /// Create a reader enforcing [limits] on the NBT read from [inner].
+    pub fn new(inner: R, limits: NbtLimits) -> Self {
+        Self {
+            inner,
+            limits,
+            flavor: NbtFlavor::Java,
+            depth: 0,
+            bytes_read: 0,
+        }
+    }
+
+    /// Read [flavor] NBT instead of [NbtFlavor::Java].
+    pub fn with_flavor(mut self, flavor: NbtFlavor) -> Self {
+        self.flavor = flavor;
+        self
+    }
+
+    /// The flavor of NBT being read.
+    pub fn flavor(&self) -> NbtFlavor {
+        self.flavor
+    }
+
+    /// The number of bytes read so far.
+    pub fn bytes_read(&self) -> usize {
+        self.bytes_read
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn account(&mut self, bytes: usize) -> NbtResult<()> {
+        match self.bytes_read.checked_add(bytes) {
+            Some(total) if total <= self.limits.max_bytes => {
+                self.bytes_read = total;
+                Ok(())
+            }
+            _ => Err(NbtError::LimitExceeded {
+                limit: "size in bytes",
+                max: self.limits.max_bytes,
+            }),
+        }
+    }
+
+    /// Read exactly [N] bytes.
+    pub(crate) fn read_bytes<const N: usize>(&mut self) -> NbtResult<[u8; N]> {
+        self.account(N)?;
+        let mut bytes = [0u8; N];
+        self.inner.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Read exactly [length] bytes, the limit is checked before anything is allocated.
+    pub(crate) fn read_vec(&mut self, length: usize) -> NbtResult<Vec<u8>> {
+        self.account(length)?;
+        let mut bytes = vec![0u8; length];
+        self.inner.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Read the length prefix of a list or array.
+    pub(crate) fn read_array_length(&mut self) -> NbtResult<usize> {
+        let length = usize::try_from(self.read_i32()?)?;
+        if length > self.limits.max_array_length {
+            return Err(NbtError::LimitExceeded {
+                limit: "array length",
+                max: self.limits.max_array_length,
+            });
+        }
+
+        Ok(length)
+    }
+
+    /// The capacity to reserve for [length] elements taking up at least [element_size] bytes each.
+    /// The length prefix is untrusted, so this never exceeds what the remaining byte budget could
+    /// hold, nor [MAX_PREALLOCATED_ELEMENTS].
+    pub(crate) fn capacity_for(&self, length: usize, element_size: usize) -> usize {
+        let remaining = (self.limits.max_bytes - self.bytes_read) / element_size;
+        length.min(remaining).min(MAX_PREALLOCATED_ELEMENTS)
+    }
+
+    /// Run [f] one level deeper into the NBT tree.
+    pub(crate) fn nested<T, E: From<NbtError>>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, E>,
+    ) -> Result<T, E> {
+        if self.depth >= self.limits.max_depth {
+            return Err(NbtError::LimitExceeded {
+                limit: "nesting depth",
+                max: self.limits.max_depth,
+            }
+            .into());
+        }
+
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+
+        result
+    }
+}
@@ -0,0 +1,122 @@
+use std::borrow::Cow;
+
+use crate::{NbtResult, error::NbtError};
+
+/// Decode a Modified UTF-8 string, i.e. UTF-8 where null is encoded as `0xC0 0x80` and characters
+/// outside the Basic Multilingual Plane as a surrogate pair of two 3 byte sequences.
+/// Borrows [bytes] if they are also valid UTF-8, which is the case for nearly all strings.
+pub fn decode(bytes: &[u8]) -> NbtResult<Cow<'_, str>> {
+    if let Ok(s) = std::str::from_utf8(bytes) {
+        return Ok(Cow::Borrowed(s));
+    }
+
+    let invalid = |position: usize| {
+        NbtError::InvalidMutf8(format!("invalid byte sequence at position {position}"))
+    };
+
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let continuation = |offset: usize| match bytes.get(i + offset) {
+            Some(b) if b & 0xC0 == 0x80 => Ok(u32::from(b & 0x3F)),
+            _ => Err(invalid(i)),
+        };
+
+        let b = bytes[i];
+        let (code_point, length) = match b {
+            0x00..=0x7F => (u32::from(b), 1),
+            0xC0..=0xDF => ((u32::from(b & 0x1F) << 6) | continuation(1)?, 2),
+            0xE0..=0xEF => (
+                (u32::from(b & 0x0F) << 12) | (continuation(1)? << 6) | continuation(2)?,
+                3,
+            ),
+            // Not valid Modified UTF-8, but some tools write regular UTF-8 so accept it anyway.
+            0xF0..=0xF7 => (
+                (u32::from(b & 0x07) << 18)
+                    | (continuation(1)? << 12)
+                    | (continuation(2)? << 6)
+                    | continuation(3)?,
+                4,
+            ),
+            _ => return Err(invalid(i)),
+        };
+
+        match u16::try_from(code_point) {
+            Ok(unit) => units.push(unit),
+            Err(_) => {
+                let c = char::from_u32(code_point).ok_or_else(|| invalid(i))?;
+                units.extend_from_slice(c.encode_utf16(&mut [0; 2]));
+            }
+        }
+        i += length;
+    }
+
+    String::from_utf16(&units)
+        .map(Cow::Owned)
+        .map_err(|_| NbtError::InvalidMutf8("unpaired surrogate".to_string()))
+}
+
+/// Encode [s] as Modified UTF-8, see [decode]. Borrows the bytes of [s] if it contains neither nulls
+/// nor characters outside the Basic Multilingual Plane, in which case both encodings are the same.
+pub fn encode(s: &str) -> Cow<'_, [u8]> {
+    if !s.chars().any(|c| c == '\0' || c.len_utf8() == 4) {
+        return Cow::Borrowed(s.as_bytes());
+    }
+
+    let mut bytes = Vec::with_capacity(s.len() + 2);
+    for c in s.chars() {
+        match c {
+            '\0' => bytes.extend_from_slice(&[0xC0, 0x80]),
+            c if c.len_utf8() == 4 => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    bytes.extend_from_slice(&[
+                        0xE0 | (*unit >> 12) as u8,
+                        0x80 | ((*unit >> 6) & 0x3F) as u8,
+                        0x80 | (*unit & 0x3F) as u8,
+                    ]);
+                }
+            }
+            c => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+
+    Cow::Owned(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_surrogate_pairs() {
+        // "Hi 😀" as written by Java's DataOutputStream.writeUTF.
+        let java = [0x48, 0x69, 0x20, 0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80];
+        assert_eq!(decode(&java).unwrap(), "Hi 😀");
+        assert_eq!(encode("Hi 😀").as_ref(), java);
+
+        // Null & a two byte character.
+        let java = [0x61, 0xC0, 0x80, 0xC3, 0xA9];
+        assert_eq!(decode(&java).unwrap(), "a\0é");
+        assert_eq!(encode("a\0é").as_ref(), java);
+
+        assert!(matches!(encode("plain ascii é"), Cow::Borrowed(_)));
+        assert!(matches!(
+            decode("plain ascii é".as_bytes()).unwrap(),
+            Cow::Borrowed(_)
+        ));
+        // Regular UTF-8 for supplementary characters is accepted as well.
+        assert_eq!(decode("😀".as_bytes()).unwrap(), "😀");
+    }
+
+    #[test]
+    fn test_invalid() {
+        for bytes in [
+            &[0xED, 0xA0, 0xBD][..],               // Unpaired high surrogate.
+            &[0xED, 0xB8, 0x80, 0xED, 0xA0, 0xBD], // Swapped surrogates.
+            &[0xC3],                               // Truncated sequence.
+            &[0xFF],
+        ] {
+            assert!(matches!(decode(bytes), Err(NbtError::InvalidMutf8(_))));
+        }
+    }
+}
@@ -1,7 +1,9 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
 use crate::{
     NbtResult,
+    flavor::{NbtFlavor, NbtWriter},
+    limits::{NbtLimits, NbtReader},
     nbt_types::{NbtString, NbtType},
     tag_type::NbtTagType,
 };
@@ -18,17 +20,38 @@ pub struct NbtNamedTag {
 impl NbtNamedTag {
     /// Reads a [NbtNamedTag] from the provided [r], if the byte_tag is TAG_End returns None.
     pub fn read<R: Read>(r: &mut R) -> NbtResult<Option<Self>> {
-        let mut tag_type = [0u8; 1];
-        r.read_exact(&mut tag_type)?;
+        Self::read_limited(&mut NbtReader::new(r, NbtLimits::default()))
+    }
+
+    /// Same as [NbtNamedTag::read] but enforcing the limits of the provided [r].
+    pub fn read_limited<R: Read>(r: &mut NbtReader<R>) -> NbtResult<Option<Self>> {
+        let [tag_type] = r.read_bytes()?;
 
-        if tag_type[0] == NbtTagType::TagEnd.get_tag_id() {
+        if tag_type == NbtTagType::TagEnd.get_tag_id() {
             return Ok(None);
         }
 
-        let name = NbtString::read(r)?;
+        let name = NbtString::read_limited(r)?;
 
-        let payload = NbtTagType::read(tag_type[0], r)?;
+        let payload = NbtTagType::read_limited(tag_type, r)?;
 
         Ok(Some(Self { name, payload }))
     }
+
+    /// Writes this [NbtNamedTag] to the provided [w], i.e. the tag ID followed by the name & payload.
+    pub fn write<W: Write>(&self, w: &mut W) -> NbtResult<()> {
+        self.write_flavored(&mut NbtWriter::new(w, NbtFlavor::Java))
+    }
+
+    /// Same as [NbtNamedTag::write] but in the flavor of the provided [w].
+    pub fn write_flavored<W: Write>(&self, w: &mut NbtWriter<W>) -> NbtResult<()> {
+        w.write_bytes(&[self.payload.get_tag_id()])?;
+
+        if matches!(self.payload, NbtTagType::TagEnd) {
+            return Ok(());
+        }
+
+        self.name.write_flavored(w)?;
+        self.payload.write_flavored(w)
+    }
 }
@@ -0,0 +1,951 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    NbtResult,
+    error::NbtError,
+    nbt_named_tag::NbtNamedTag,
+    nbt_types::{NbtCompound, NbtList, NbtString},
+    nbt_value::value::NbtValue,
+    snbt::{Snbt, SnbtFormat, SnbtParser},
+    tag_type::NbtTagType,
+};
+
+/// A path into an NBT tree in the same format as the paths of the vanilla `/data` command,
+/// e.g. `Inventory[0].components."minecraft:custom_name"`, `Items[{Slot:3b}]` or `Pos[]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NbtPath {
+    nodes: Vec<PathNode>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PathNode {
+    /// `{filter}` at the start of a path, matches the root if it matches the filter.
+    Root(NbtCompound),
+    /// `name` or `name{filter}`, the member of a compound.
+    Key {
+        name: String,
+        filter: Option<NbtCompound>,
+    },
+    /// `[index]`, an element of a list or array, negative indices count from the end.
+    Index(i32),
+    /// `[]`, all elements of a list or array.
+    All,
+    /// `[{filter}]`, all compounds in a list that match the filter.
+    Filter(NbtCompound),
+}
+
+/// Used in place of the node following the last one when merging, so missing targets become compounds.
+const MERGE_TARGET: PathNode = PathNode::Root(NbtCompound(Vec::new()));
+
+impl FromStr for NbtPath {
+    type Err = NbtError;
+
+    fn from_str(s: &str) -> NbtResult<Self> {
+        PathParser {
+            parser: SnbtParser::new(s),
+        }
+        .parse()
+    }
+}
+
+impl Display for NbtPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, node) in self.nodes.iter().enumerate() {
+            match node {
+                PathNode::Root(filter) => write!(f, "{}", compound_snbt(filter))?,
+                PathNode::Key { name, filter } => {
+                    if i > 0 {
+                        f.write_str(".")?;
+                    }
+                    if !name.is_empty() && name.chars().all(is_plain_key_char) {
+                        f.write_str(name)?;
+                    } else {
+                        write!(f, "\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))?;
+                    }
+                    if let Some(filter) = filter {
+                        write!(f, "{}", compound_snbt(filter))?;
+                    }
+                }
+                PathNode::Index(index) => write!(f, "[{index}]")?,
+                PathNode::All => f.write_str("[]")?,
+                PathNode::Filter(filter) => write!(f, "[{}]", compound_snbt(filter))?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl NbtPath {
+    /// Get (copies of) all tags matching the path, errors if nothing matches.
+    pub fn get(&self, root: &NbtTagType) -> NbtResult<Vec<NbtTagType>> {
+        let (last, parents) = self.nodes.split_last().expect("Paths are never empty");
+
+        let mut tags = vec![root];
+        for node in parents {
+            tags = tags.into_iter().flat_map(|t| select(node, t)).collect();
+        }
+
+        let found: Vec<NbtTagType> = tags
+            .into_iter()
+            .flat_map(|parent| match array_elements(parent) {
+                Some(elements) => select_elements(last, elements),
+                None => select(last, parent).into_iter().cloned().collect(),
+            })
+            .collect();
+
+        if found.is_empty() {
+            return Err(self.not_found());
+        }
+
+        Ok(found)
+    }
+
+    /// Set all tags matching the path to [value], missing compounds along the way are created.
+    /// Returns the number of tags that were set, [root] is left untouched if this fails.
+    pub fn set(&self, root: &mut NbtTagType, value: NbtTagType) -> NbtResult<usize> {
+        self.modify(root, |path, tag| path.set_all(tag, &value))
+    }
+
+    /// Remove all tags matching the path, returns the number of tags that were removed.
+    /// [root] is left untouched if this fails.
+    pub fn remove(&self, root: &mut NbtTagType) -> NbtResult<usize> {
+        self.modify(root, |path, tag| path.remove_all(tag))
+    }
+
+    /// Merge [value] into all compounds matching the path, missing compounds are created.
+    /// Nested compounds are merged recursively, everything else in [value] replaces the existing tag.
+    /// Returns the number of compounds that were merged into, [root] is left untouched if this fails.
+    pub fn merge(&self, root: &mut NbtTagType, value: &NbtCompound) -> NbtResult<usize> {
+        self.modify(root, |path, tag| path.merge_all(tag, value))
+    }
+
+    fn set_all(&self, root: &mut NbtTagType, value: &NbtTagType) -> NbtResult<usize> {
+        let (last, parents) = self.nodes.split_last().expect("Paths are never empty");
+
+        let mut count = 0;
+        for parent in self.resolve_mut(root, parents, Some(last)) {
+            count += self.set_in(last, parent, value)?;
+        }
+
+        if count == 0 {
+            return Err(self.not_found());
+        }
+
+        Ok(count)
+    }
+
+    fn remove_all(&self, root: &mut NbtTagType) -> NbtResult<usize> {
+        let (last, parents) = self.nodes.split_last().expect("Paths are never empty");
+
+        let mut count = 0;
+        for parent in self.resolve_mut(root, parents, None) {
+            count += self.remove_in(last, parent)?;
+        }
+
+        if count == 0 {
+            return Err(self.not_found());
+        }
+
+        Ok(count)
+    }
+
+    fn merge_all(&self, root: &mut NbtTagType, value: &NbtCompound) -> NbtResult<usize> {
+        // Missing compounds are created the same way as for a member that is followed by another key.
+        let targets = self.resolve_mut(root, &self.nodes, Some(&MERGE_TARGET));
+        if targets.is_empty() {
+            return Err(self.not_found());
+        }
+
+        let count = targets.len();
+        for target in targets {
+            let NbtTagType::TagCompound(compound) = target else {
+                return Err(self.mismatch(format!(
+                    "can only merge into compounds, found tag {}",
+                    target.get_tag_id()
+                )));
+            };
+            merge_compound(compound, value);
+        }
+
+        Ok(count)
+    }
+
+    /// Same as [NbtPath::get] but for an [NbtValue].
+    pub fn get_value(&self, root: &NbtValue) -> NbtResult<Vec<NbtValue>> {
+        let found = self.get(&NbtTagType::from(root.clone()))?;
+        Ok(found.into_iter().filter_map(Option::from).collect())
+    }
+
+    /// Same as [NbtPath::set] but for an [NbtValue].
+    pub fn set_value(&self, root: &mut NbtValue, value: NbtValue) -> NbtResult<usize> {
+        let value = value.into();
+        self.modify_value(root, |path, tag| path.set_all(tag, &value))
+    }
+
+    /// Same as [NbtPath::remove] but for an [NbtValue].
+    pub fn remove_value(&self, root: &mut NbtValue) -> NbtResult<usize> {
+        self.modify_value(root, |path, tag| path.remove_all(tag))
+    }
+
+    /// Same as [NbtPath::merge] but for an [NbtValue], [value] has to be a compound.
+    pub fn merge_value(&self, root: &mut NbtValue, value: NbtValue) -> NbtResult<usize> {
+        let NbtTagType::TagCompound(compound) = NbtTagType::from(value) else {
+            return Err(self.mismatch("can only merge compounds"));
+        };
+        self.modify_value(root, |path, tag| path.merge_all(tag, &compound))
+    }
+
+    /// Runs [modify] on a copy of [root] and only keeps the result if every target could be changed,
+    /// so a path that fails halfway through doesn't leave created compounds or partial edits behind.
+    fn modify(
+        &self,
+        root: &mut NbtTagType,
+        modify: impl FnOnce(&Self, &mut NbtTagType) -> NbtResult<usize>,
+    ) -> NbtResult<usize> {
+        let mut tag = root.clone();
+        let count = modify(self, &mut tag)?;
+        *root = tag;
+        Ok(count)
+    }
+
+    fn modify_value(
+        &self,
+        root: &mut NbtValue,
+        modify: impl FnOnce(&Self, &mut NbtTagType) -> NbtResult<usize>,
+    ) -> NbtResult<usize> {
+        let mut tag = NbtTagType::from(root.clone());
+        let count = modify(self, &mut tag)?;
+        *root = Option::from(tag).expect("Converted from an NbtValue so it can't be TAG_End");
+        Ok(count)
+    }
+
+    /// The path of the root tag itself, `{}`.
+    pub(crate) fn root() -> Self {
+        Self {
+            nodes: vec![PathNode::Root(NbtCompound(Vec::new()))],
+        }
+    }
+
+    /// Whether this is [NbtPath::root].
+    pub(crate) fn is_root(&self) -> bool {
+        matches!(self.nodes.as_slice(), [PathNode::Root(filter)] if filter.0.is_empty())
+    }
+
+    /// This path followed by the compound member [name].
+    pub(crate) fn key(&self, name: &str) -> Self {
+        let mut nodes = if self.is_root() {
+            Vec::new()
+        } else {
+            self.nodes.clone()
+        };
+        nodes.push(PathNode::Key {
+            name: name.to_string(),
+            filter: None,
+        });
+        Self { nodes }
+    }
+
+    /// This path followed by the list or array element at [index].
+    pub(crate) fn index(&self, index: usize) -> Self {
+        let mut nodes = self.nodes.clone();
+        nodes.push(PathNode::Index(
+            i32::try_from(index).expect("NBT lists have at most i32::MAX elements"),
+        ));
+        Self { nodes }
+    }
+
+    /// Splits `list[index]` into the path of the list & the index.
+    pub(crate) fn split_index(&self) -> Option<(Self, i32)> {
+        match self.nodes.split_last() {
+            Some((PathNode::Index(index), parents)) if !parents.is_empty() => Some((
+                Self {
+                    nodes: parents.to_vec(),
+                },
+                *index,
+            )),
+            _ => None,
+        }
+    }
+
+    fn not_found(&self) -> NbtError {
+        NbtError::NbtPathNotFound(self.to_string())
+    }
+
+    fn mismatch(&self, reason: impl Into<String>) -> NbtError {
+        NbtError::NbtPathMismatch {
+            path: self.to_string(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Follow [nodes] from [root], if [last] is provided missing compound members are created so
+    /// that [last] can be applied to them.
+    fn resolve_mut<'a>(
+        &self,
+        root: &'a mut NbtTagType,
+        nodes: &[PathNode],
+        last: Option<&PathNode>,
+    ) -> Vec<&'a mut NbtTagType> {
+        let mut tags = vec![root];
+        for (i, node) in nodes.iter().enumerate() {
+            let next = nodes.get(i + 1).or(last);
+            tags = tags
+                .into_iter()
+                .flat_map(|t| select_mut(node, t, last.and(next)))
+                .collect();
+        }
+
+        tags
+    }
+
+    fn set_in(
+        &self,
+        node: &PathNode,
+        parent: &mut NbtTagType,
+        value: &NbtTagType,
+    ) -> NbtResult<usize> {
+        match (node, parent) {
+            (PathNode::Root(_), _) => Err(self.mismatch("can't replace the root tag")),
+            (PathNode::Key { name, filter }, NbtTagType::TagCompound(compound)) => {
+                match compound.0.iter_mut().find(|t| t.name.0 == *name) {
+                    Some(tag)
+                        if filter
+                            .as_ref()
+                            .is_some_and(|f| !matches_compound(f, &tag.payload)) =>
+                    {
+                        Ok(0)
+                    }
+                    Some(tag) => {
+                        tag.payload = value.clone();
+                        Ok(1)
+                    }
+                    None => {
+                        compound.0.push(NbtNamedTag {
+                            name: NbtString(name.clone()),
+                            payload: value.clone(),
+                        });
+                        Ok(1)
+                    }
+                }
+            }
+            (PathNode::Key { .. }, parent) => Err(self.mismatch(format!(
+                "expected compound, found tag {}",
+                parent.get_tag_id()
+            ))),
+            (_, NbtTagType::TagList(list)) => {
+                let replaces_all = matches!(node, PathNode::All) || list.0.len() <= 1;
+                if !replaces_all
+                    && list
+                        .0
+                        .first()
+                        .is_some_and(|t| t.get_tag_id() != value.get_tag_id())
+                {
+                    return Err(self.mismatch(format!(
+                        "list of tag {} can't contain tag {}",
+                        list.0[0].get_tag_id(),
+                        value.get_tag_id()
+                    )));
+                }
+
+                Ok(set_elements(
+                    &mut list.0,
+                    node,
+                    value.clone(),
+                    matches_compound,
+                ))
+            }
+            (_, NbtTagType::TagByteArray(array)) => match value {
+                NbtTagType::TagByte(b) => Ok(set_elements(&mut array.0, node, b.clone(), never)),
+                _ => Err(self.mismatch("byte arrays can only contain bytes")),
+            },
+            (_, NbtTagType::TagIntArray(array)) => match value {
+                NbtTagType::TagInt(i) => Ok(set_elements(&mut array.0, node, i.clone(), never)),
+                _ => Err(self.mismatch("int arrays can only contain ints")),
+            },
+            (_, NbtTagType::TagLongArray(array)) => match value {
+                NbtTagType::TagLong(l) => Ok(set_elements(&mut array.0, node, l.clone(), never)),
+                _ => Err(self.mismatch("long arrays can only contain longs")),
+            },
+            (_, parent) => Err(self.mismatch(format!(
+                "expected list or array, found tag {}",
+                parent.get_tag_id()
+            ))),
+        }
+    }
+
+    fn remove_in(&self, node: &PathNode, parent: &mut NbtTagType) -> NbtResult<usize> {
+        let count = match (node, parent) {
+            (PathNode::Root(_), _) => return Err(self.mismatch("can't remove the root tag")),
+            (PathNode::Key { name, filter }, NbtTagType::TagCompound(compound)) => {
+                let before = compound.0.len();
+                compound.0.retain(|t| {
+                    t.name.0 != *name
+                        || filter
+                            .as_ref()
+                            .is_some_and(|f| !matches_compound(f, &t.payload))
+                });
+                before - compound.0.len()
+            }
+            (_, NbtTagType::TagList(list)) => remove_elements(&mut list.0, node, matches_compound),
+            (_, NbtTagType::TagByteArray(array)) => remove_elements(&mut array.0, node, never),
+            (_, NbtTagType::TagIntArray(array)) => remove_elements(&mut array.0, node, never),
+            (_, NbtTagType::TagLongArray(array)) => remove_elements(&mut array.0, node, never),
+            _ => 0,
+        };
+
+        Ok(count)
+    }
+}
+
+/// The element based nodes select nothing in arrays since their elements are never compounds.
+fn never<T>(_filter: &NbtCompound, _element: &T) -> bool {
+    false
+}
+
+/// Resolves a possibly negative index into a list of the provided length.
+fn resolve_index(index: i32, len: usize) -> Option<usize> {
+    let index = if index < 0 {
+        len.checked_sub(usize::try_from(index.unsigned_abs()).ok()?)?
+    } else {
+        usize::try_from(index).ok()?
+    };
+
+    (index < len).then_some(index)
+}
+
+fn select<'a>(node: &PathNode, tag: &'a NbtTagType) -> Vec<&'a NbtTagType> {
+    match (node, tag) {
+        (PathNode::Root(filter), tag) => [tag]
+            .into_iter()
+            .filter(|t| matches_compound(filter, t))
+            .collect(),
+        (PathNode::Key { name, filter }, NbtTagType::TagCompound(compound)) => compound
+            .0
+            .iter()
+            .filter(|t| t.name.0 == *name)
+            .map(|t| &t.payload)
+            .filter(|t| filter.as_ref().is_none_or(|f| matches_compound(f, t)))
+            .collect(),
+        (PathNode::Index(index), NbtTagType::TagList(list)) => resolve_index(*index, list.0.len())
+            .map(|i| &list.0[i])
+            .into_iter()
+            .collect(),
+        (PathNode::All, NbtTagType::TagList(list)) => list.0.iter().collect(),
+        (PathNode::Filter(filter), NbtTagType::TagList(list)) => list
+            .0
+            .iter()
+            .filter(|t| matches_compound(filter, t))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Same as [select] but mutable, members that don't exist are created if [next] is provided, as
+/// a compound or list depending on the node that is applied to them next.
+fn select_mut<'a>(
+    node: &PathNode,
+    tag: &'a mut NbtTagType,
+    next: Option<&PathNode>,
+) -> Vec<&'a mut NbtTagType> {
+    match (node, tag) {
+        (PathNode::Root(filter), tag) => {
+            if matches_compound(filter, tag) {
+                vec![tag]
+            } else {
+                Vec::new()
+            }
+        }
+        (PathNode::Key { name, filter }, NbtTagType::TagCompound(compound)) => {
+            let index = match compound.0.iter().position(|t| t.name.0 == *name) {
+                Some(index) => index,
+                None => {
+                    let Some(next) = next else {
+                        return Vec::new();
+                    };
+                    let payload = match (filter, next) {
+                        (Some(filter), _) => NbtTagType::TagCompound(filter.clone()),
+                        (None, PathNode::Root(_) | PathNode::Key { .. }) => {
+                            NbtTagType::TagCompound(NbtCompound(Vec::new()))
+                        }
+                        (None, _) => NbtTagType::TagList(NbtList(Vec::new())),
+                    };
+                    compound.0.push(NbtNamedTag {
+                        name: NbtString(name.clone()),
+                        payload,
+                    });
+                    compound.0.len() - 1
+                }
+            };
+
+            let child = &mut compound.0[index].payload;
+            if filter.as_ref().is_some_and(|f| !matches_compound(f, child)) {
+                return Vec::new();
+            }
+            vec![child]
+        }
+        (PathNode::Index(index), NbtTagType::TagList(list)) => {
+            match resolve_index(*index, list.0.len()) {
+                Some(i) => vec![&mut list.0[i]],
+                None => Vec::new(),
+            }
+        }
+        (PathNode::All, NbtTagType::TagList(list)) => list.0.iter_mut().collect(),
+        (PathNode::Filter(filter), NbtTagType::TagList(list)) => list
+            .0
+            .iter_mut()
+            .filter(|t| matches_compound(filter, t))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The elements of an array as tags, arrays don't store their elements as [NbtTagType]s.
+fn array_elements(tag: &NbtTagType) -> Option<Vec<NbtTagType>> {
+    Some(match tag {
+        NbtTagType::TagByteArray(array) => {
+            array.0.iter().cloned().map(NbtTagType::TagByte).collect()
+        }
+        NbtTagType::TagIntArray(array) => array.0.iter().cloned().map(NbtTagType::TagInt).collect(),
+        NbtTagType::TagLongArray(array) => {
+            array.0.iter().cloned().map(NbtTagType::TagLong).collect()
+        }
+        _ => return None,
+    })
+}
+
+fn select_elements(node: &PathNode, mut elements: Vec<NbtTagType>) -> Vec<NbtTagType> {
+    match node {
+        PathNode::Index(index) => match resolve_index(*index, elements.len()) {
+            Some(i) => vec![elements.swap_remove(i)],
+            None => Vec::new(),
+        },
+        PathNode::All => elements,
+        _ => Vec::new(),
+    }
+}
+
+/// Replace the elements selected by [node] with [value], returns how many were replaced.
+fn set_elements<T: Clone>(
+    elements: &mut [T],
+    node: &PathNode,
+    value: T,
+    matches: impl Fn(&NbtCompound, &T) -> bool,
+) -> usize {
+    match node {
+        PathNode::Index(index) => match resolve_index(*index, elements.len()) {
+            Some(i) => {
+                elements[i] = value;
+                1
+            }
+            None => 0,
+        },
+        PathNode::All => {
+            elements.fill(value);
+            elements.len()
+        }
+        PathNode::Filter(filter) => {
+            let mut count = 0;
+            for element in elements.iter_mut().filter(|e| matches(filter, e)) {
+                *element = value.clone();
+                count += 1;
+            }
+            count
+        }
+        PathNode::Root(_) | PathNode::Key { .. } => 0,
+    }
+}
+
+/// Remove the elements selected by [node], returns how many were removed.
+fn remove_elements<T>(
+    elements: &mut Vec<T>,
+    node: &PathNode,
+    matches: impl Fn(&NbtCompound, &T) -> bool,
+) -> usize {
+    let before = elements.len();
+    match node {
+        PathNode::Index(index) => {
+            if let Some(i) = resolve_index(*index, elements.len()) {
+                elements.remove(i);
+            }
+        }
+        PathNode::All => elements.clear(),
+        PathNode::Filter(filter) => elements.retain(|e| !matches(filter, e)),
+        PathNode::Root(_) | PathNode::Key { .. } => {}
+    }
+
+    before - elements.len()
+}
+
+/// Whether [tag] is a compound containing everything in [filter], same as vanilla compounds are
+/// matched partially while lists match if every filter element matches one of their elements.
+fn matches_compound(filter: &NbtCompound, tag: &NbtTagType) -> bool {
+    let NbtTagType::TagCompound(compound) = tag else {
+        return false;
+    };
+
+    filter.0.iter().all(|f| {
+        compound
+            .0
+            .iter()
+            .any(|t| t.name == f.name && matches(&f.payload, &t.payload))
+    })
+}
+
+fn matches(filter: &NbtTagType, tag: &NbtTagType) -> bool {
+    match (filter, tag) {
+        (NbtTagType::TagCompound(filter), tag) => matches_compound(filter, tag),
+        (NbtTagType::TagList(filter), NbtTagType::TagList(list)) => {
+            if filter.0.is_empty() {
+                return list.0.is_empty();
+            }
+            filter
+                .0
+                .iter()
+                .all(|f| list.0.iter().any(|t| matches(f, t)))
+        }
+        _ => filter == tag,
+    }
+}
+
+fn merge_compound(target: &mut NbtCompound, source: &NbtCompound) {
+    for tag in source.0.iter() {
+        let existing = target.0.iter_mut().find(|t| t.name == tag.name);
+        match (existing, &tag.payload) {
+            (Some(existing), NbtTagType::TagCompound(source)) => match &mut existing.payload {
+                NbtTagType::TagCompound(target) => merge_compound(target, source),
+                payload => *payload = tag.payload.clone(),
+            },
+            (Some(existing), payload) => existing.payload = payload.clone(),
+            (None, _) => target.0.push(tag.clone()),
+        }
+    }
+}
+
+fn compound_snbt(compound: &NbtCompound) -> Snbt {
+    SnbtFormat::COMPACT.format_lossy(&NbtTagType::TagCompound(compound.clone()))
+}
+
+/// Keys that are written without quotes when displaying a path, same characters as unquoted SNBT.
+fn is_plain_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "_-+".contains(c)
+}
+
+/// Same as vanilla, keys can be unquoted unless they contain whitespace, quotes or path syntax.
+fn is_unquoted_key_char(c: char) -> bool {
+    !c.is_whitespace() && !"\"'[].{}".contains(c)
+}
+
+struct PathParser {
+    parser: SnbtParser,
+}
+
+impl PathParser {
+    fn error(&self, reason: impl Into<String>) -> NbtError {
+        NbtError::InvalidNbtPath {
+            position: self.parser.pos,
+            reason: reason.into(),
+        }
+    }
+
+    fn parse(mut self) -> NbtResult<NbtPath> {
+        let mut nodes = Vec::new();
+
+        if self.parser.peek() == Some('{') {
+            nodes.push(PathNode::Root(self.parse_filter()?));
+        }
+
+        while let Some(c) = self.parser.peek() {
+            match c {
+                '[' => nodes.push(self.parse_element()?),
+                '.' if !nodes.is_empty() => {
+                    self.parser.next();
+                    nodes.push(self.parse_key()?);
+                }
+                _ if nodes.is_empty() => nodes.push(self.parse_key()?),
+                c => return Err(self.error(format!("Expected '.' or '[' but found '{c}'"))),
+            }
+        }
+
+        if nodes.is_empty() {
+            return Err(self.error("Path is empty"));
+        }
+
+        Ok(NbtPath { nodes })
+    }
+
+    fn parse_filter(&mut self) -> NbtResult<NbtCompound> {
+        let NbtTagType::TagCompound(compound) = self.parser.parse_compound()? else {
+            unreachable!("Parsing a compound always returns a compound")
+        };
+        Ok(compound)
+    }
+
+    fn parse_key(&mut self) -> NbtResult<PathNode> {
+        let name = match self.parser.peek() {
+            Some('"' | '\'') => self.parser.parse_quoted_string()?,
+            Some(c) if is_unquoted_key_char(c) => {
+                let mut name = String::new();
+                while let Some(c) = self.parser.peek().filter(|c| is_unquoted_key_char(*c)) {
+                    name.push(c);
+                    self.parser.next();
+                }
+                name
+            }
+            Some(c) => return Err(self.error(format!("Expected key but found '{c}'"))),
+            None => return Err(self.error("Expected key but found end of path")),
+        };
+
+        let filter = match self.parser.peek() {
+            Some('{') => Some(self.parse_filter()?),
+            _ => None,
+        };
+
+        Ok(PathNode::Key { name, filter })
+    }
+
+    fn parse_element(&mut self) -> NbtResult<PathNode> {
+        self.parser.next();
+
+        let node = match self.parser.peek() {
+            Some(']') => PathNode::All,
+            Some('{') => PathNode::Filter(self.parse_filter()?),
+            Some(_) => {
+                let start = self.parser.pos;
+                let mut digits = String::new();
+                while let Some(c) = self
+                    .parser
+                    .peek()
+                    .filter(|c| *c == '-' || c.is_ascii_digit())
+                {
+                    digits.push(c);
+                    self.parser.next();
+                }
+                let index = digits
+                    .parse::<i32>()
+                    .map_err(|_| NbtError::InvalidNbtPath {
+                        position: start,
+                        reason: format!("Invalid index '{digits}'"),
+                    })?;
+                PathNode::Index(index)
+            }
+            None => return Err(self.error("Expected ']' but found end of path")),
+        };
+
+        match self.parser.next() {
+            Some(']') => Ok(node),
+            Some(c) => {
+                self.parser.pos -= 1;
+                Err(self.error(format!("Expected ']' but found '{c}'")))
+            }
+            None => Err(self.error("Expected ']' but found end of path")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(snbt: &str) -> NbtTagType {
+        NbtTagType::try_from(&Snbt::from(snbt)).unwrap()
+    }
+
+    fn path(path: &str) -> NbtPath {
+        path.parse().unwrap()
+    }
+
+    fn player() -> NbtTagType {
+        tag(
+            r#"{Inventory:[{Slot:0b,id:"minecraft:stone",components:{"minecraft:custom_name":"Rock"}},{Slot:3b,id:"minecraft:dirt"}],Pos:[1.0d,2.0d,3.0d],Ids:[I;1,2,3]}"#,
+        )
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        for p in [
+            r#"Inventory[0].components."minecraft:custom_name""#,
+            "Items[{Slot:3b}]",
+            "Pos[]",
+            "Pos[-1]",
+            "{Invulnerable:1b}.Inventory",
+            "Inventory{Slot:0b}.id",
+            r#""with space"."quote\"d""#,
+        ] {
+            assert_eq!(path(p).to_string(), p);
+        }
+
+        for (p, position) in [
+            ("", 0),
+            ("a.", 2),
+            ("a[0", 3),
+            ("a[x]", 2),
+            ("a..b", 2),
+            ("a b", 1),
+        ] {
+            match p.parse::<NbtPath>() {
+                Err(NbtError::InvalidNbtPath { position: pos, .. }) => {
+                    assert_eq!(pos, position, "{p}")
+                }
+                other => panic!("Expected invalid path for {p}, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_get() {
+        let player = player();
+
+        assert_eq!(
+            path(r#"Inventory[0].components."minecraft:custom_name""#)
+                .get(&player)
+                .unwrap(),
+            vec![tag(r#""Rock""#)]
+        );
+        assert_eq!(
+            path("Inventory[{Slot:3b}].id").get(&player).unwrap(),
+            vec![tag(r#""minecraft:dirt""#)]
+        );
+        assert_eq!(
+            path("Inventory[].Slot").get(&player).unwrap(),
+            vec![tag("0b"), tag("3b")]
+        );
+        assert_eq!(path("Pos[-1]").get(&player).unwrap(), vec![tag("3.0d")]);
+        assert_eq!(path("Ids[1]").get(&player).unwrap(), vec![tag("2")]);
+        assert_eq!(path("Ids[]").get(&player).unwrap().len(), 3);
+
+        match path("Inventory[{Slot:5b}].id").get(&player) {
+            Err(NbtError::NbtPathNotFound(p)) => assert_eq!(p, "Inventory[{Slot:5b}].id"),
+            other => panic!("Expected not found, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_set() {
+        let mut player = player();
+
+        assert_eq!(
+            path("Inventory[{Slot:3b}].Count")
+                .set(&mut player, tag("2b"))
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            path("Inventory[].Slot")
+                .set(&mut player, tag("7b"))
+                .unwrap(),
+            2
+        );
+        assert_eq!(path("Ids[0]").set(&mut player, tag("9")).unwrap(), 1);
+        assert_eq!(path("a.b.c").set(&mut player, tag("1s")).unwrap(), 1);
+        assert_eq!(
+            player,
+            tag(
+                r#"{Inventory:[{Slot:7b,id:"minecraft:stone",components:{"minecraft:custom_name":"Rock"}},{Slot:7b,id:"minecraft:dirt",Count:2b}],Pos:[1.0d,2.0d,3.0d],Ids:[I;9,2,3],a:{b:{c:1s}}}"#
+            )
+        );
+
+        assert!(matches!(
+            path("Pos[0]").set(&mut player, tag("1b")),
+            Err(NbtError::NbtPathMismatch { .. })
+        ));
+        assert!(matches!(
+            path("Ids[0]").set(&mut player, tag("1L")),
+            Err(NbtError::NbtPathMismatch { .. })
+        ));
+        assert!(matches!(
+            path("Pos[5]").set(&mut player, tag("1.0d")),
+            Err(NbtError::NbtPathNotFound(_))
+        ));
+
+        // A failed set doesn't leave the compounds it created on the way behind
+        let mut empty = tag("{}");
+        assert!(matches!(
+            path("a.b[0]").set(&mut empty, tag("1")),
+            Err(NbtError::NbtPathNotFound(_))
+        ));
+        assert_eq!(empty, tag("{}"));
+
+        // Nor the targets it already changed before hitting a mismatch
+        let mut lists = tag("{a:[{v:[1.0d]},{v:[I;1]}]}");
+        assert!(matches!(
+            path("a[].v[0]").set(&mut lists, tag("2.0d")),
+            Err(NbtError::NbtPathMismatch { .. })
+        ));
+        assert_eq!(lists, tag("{a:[{v:[1.0d]},{v:[I;1]}]}"));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut player = player();
+
+        assert_eq!(path("Inventory[{Slot:0b}]").remove(&mut player).unwrap(), 1);
+        assert_eq!(path("Pos[]").remove(&mut player).unwrap(), 3);
+        assert_eq!(path("Ids[-1]").remove(&mut player).unwrap(), 1);
+        assert_eq!(
+            player,
+            tag(r#"{Inventory:[{Slot:3b,id:"minecraft:dirt"}],Pos:[],Ids:[I;1,2]}"#)
+        );
+
+        assert!(matches!(
+            path("Missing").remove(&mut player),
+            Err(NbtError::NbtPathNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut player = player();
+        let NbtTagType::TagCompound(value) =
+            tag(r#"{components:{"minecraft:damage":3},id:"minecraft:cobblestone"}"#)
+        else {
+            unreachable!()
+        };
+
+        assert_eq!(
+            path("Inventory[{Slot:0b}]")
+                .merge(&mut player, &value)
+                .unwrap(),
+            1
+        );
+        assert_eq!(path("new").merge(&mut player, &value).unwrap(), 1);
+        assert_eq!(
+            path("Inventory[0]").get(&player).unwrap(),
+            vec![tag(
+                r#"{Slot:0b,id:"minecraft:cobblestone",components:{"minecraft:custom_name":"Rock","minecraft:damage":3}}"#
+            )]
+        );
+        assert_eq!(path("new.components").get(&player).unwrap().len(), 1);
+
+        assert!(matches!(
+            path("Pos").merge(&mut player, &value),
+            Err(NbtError::NbtPathMismatch { .. })
+        ));
+
+        // The first target is left as it was when the second one can't be merged into
+        let mut targets = tag("{a:[{x:{}},{x:2}]}");
+        assert!(matches!(
+            path("a[].x").merge(&mut targets, &value),
+            Err(NbtError::NbtPathMismatch { .. })
+        ));
+        assert_eq!(targets, tag("{a:[{x:{}},{x:2}]}"));
+    }
+
+    #[test]
+    fn test_nbt_value() {
+        let mut value = Option::<NbtValue>::from(player()).unwrap();
+
+        assert_eq!(
+            path("Inventory[1].id").get_value(&value).unwrap(),
+            vec![NbtValue::String("minecraft:dirt".to_string())]
+        );
+        path("Ids[]")
+            .set_value(&mut value, NbtValue::Int(0))
+            .unwrap();
+        path("Inventory").remove_value(&mut value).unwrap();
+        assert_eq!(
+            NbtTagType::from(value),
+            tag("{Pos:[1.0d,2.0d,3.0d],Ids:[I;0,0,0]}")
+        );
+    }
+}
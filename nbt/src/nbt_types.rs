@@ -1,14 +1,37 @@
-use std::{fmt::Display, io::Read};
+use std::io::{Read, Write};
 
-use crate::{NbtResult, error::NbtError, nbt_named_tag::NbtNamedTag, tag_type::NbtTagType};
+use crate::{
+    NbtResult,
+    error::NbtError,
+    flavor::{NbtFlavor, NbtWriter},
+    limits::{NbtLimits, NbtReader},
+    nbt_named_tag::NbtNamedTag,
+    tag_type::NbtTagType,
+};
 
 /// Trait for all NbtTypes.
 pub trait NbtType {
-    /// Parse the implementing type from the provided [Read].
+    /// Parse the implementing type from the provided [Read], with the default [NbtLimits].
     fn read<R: Read>(r: &mut R) -> NbtResult<Self>
+    where
+        Self: Sized,
+    {
+        Self::read_limited(&mut NbtReader::new(r, NbtLimits::default()))
+    }
+
+    /// Parse the implementing type from the provided [NbtReader], enforcing its limits.
+    fn read_limited<R: Read>(r: &mut NbtReader<R>) -> NbtResult<Self>
     where
         Self: Sized;
 
+    /// Write the implementing type to the provided [Write] as [NbtFlavor::Java] NBT.
+    fn write<W: Write>(&self, w: &mut W) -> NbtResult<()> {
+        self.write_flavored(&mut NbtWriter::new(w, NbtFlavor::Java))
+    }
+
+    /// Write the implementing type to the provided [NbtWriter], in its flavor.
+    fn write_flavored<W: Write>(&self, w: &mut NbtWriter<W>) -> NbtResult<()>;
+
     // /// Return this type wrapped in NbtTagType.
     // fn to_tag_type(self) -> NbtTagType;
 }
@@ -18,10 +41,12 @@ pub trait NbtType {
 pub struct NbtByte(pub i8);
 
 impl NbtType for NbtByte {
-    fn read<R: Read>(r: &mut R) -> NbtResult<Self> {
-        let mut bs = [0u8; 1];
-        r.read_exact(&mut bs)?;
-        Ok(Self(i8::from_be_bytes(bs)))
+    fn read_limited<R: Read>(r: &mut NbtReader<R>) -> NbtResult<Self> {
+        Ok(Self(i8::from_be_bytes(r.read_bytes()?)))
+    }
+
+    fn write_flavored<W: Write>(&self, w: &mut NbtWriter<W>) -> NbtResult<()> {
+        w.write_bytes(&self.0.to_be_bytes())
     }
 
     // fn to_tag_type(self) -> NbtTagType {
@@ -34,10 +59,12 @@ impl NbtType for NbtByte {
 pub struct NbtShort(pub i16);
 
 impl NbtType for NbtShort {
-    fn read<R: Read>(r: &mut R) -> NbtResult<Self> {
-        let mut bs = [0u8; 2];
-        r.read_exact(&mut bs)?;
-        Ok(Self(i16::from_be_bytes(bs)))
+    fn read_limited<R: Read>(r: &mut NbtReader<R>) -> NbtResult<Self> {
+        Ok(Self(r.read_i16()?))
+    }
+
+    fn write_flavored<W: Write>(&self, w: &mut NbtWriter<W>) -> NbtResult<()> {
+        w.write_i16(self.0)
     }
 
     // fn to_tag_type(self) -> NbtTagType {
@@ -50,10 +77,12 @@ impl NbtType for NbtShort {
 pub struct NbtInt(pub i32);
 
 impl NbtType for NbtInt {
-    fn read<R: Read>(r: &mut R) -> NbtResult<Self> {
-        let mut bs = [0u8; 4];
-        r.read_exact(&mut bs)?;
-        Ok(Self(i32::from_be_bytes(bs)))
+    fn read_limited<R: Read>(r: &mut NbtReader<R>) -> NbtResult<Self> {
+        Ok(Self(r.read_i32()?))
+    }
+
+    fn write_flavored<W: Write>(&self, w: &mut NbtWriter<W>) -> NbtResult<()> {
+        w.write_i32(self.0)
     }
 
     // fn to_tag_type(self) -> NbtTagType {
@@ -66,10 +95,12 @@ impl NbtType for NbtInt {
 pub struct NbtLong(pub i64);
 
 impl NbtType for NbtLong {
-    fn read<R: Read>(r: &mut R) -> NbtResult<Self> {
-        let mut bs = [0u8; 8];
-        r.read_exact(&mut bs)?;
-        Ok(Self(i64::from_be_bytes(bs)))
+    fn read_limited<R: Read>(r: &mut NbtReader<R>) -> NbtResult<Self> {
+        Ok(Self(r.read_i64()?))
+    }
+
+    fn write_flavored<W: Write>(&self, w: &mut NbtWriter<W>) -> NbtResult<()> {
+        w.write_i64(self.0)
     }
 
     // fn to_tag_type(self) -> NbtTagType {
@@ -82,10 +113,12 @@ impl NbtType for NbtLong {
 pub struct NbtFloat(pub f32);
 
 impl NbtType for NbtFloat {
-    fn read<R: Read>(r: &mut R) -> NbtResult<Self> {
-        let mut bs = [0u8; 4];
-        r.read_exact(&mut bs)?;
-        Ok(Self(f32::from_be_bytes(bs)))
+    fn read_limited<R: Read>(r: &mut NbtReader<R>) -> NbtResult<Self> {
+        Ok(Self(r.read_f32()?))
+    }
+
+    fn write_flavored<W: Write>(&self, w: &mut NbtWriter<W>) -> NbtResult<()> {
+        w.write_f32(self.0)
     }
 
     // fn to_tag_type(self) -> NbtTagType {
@@ -98,10 +131,12 @@ impl NbtType for NbtFloat {
 pub struct NbtDouble(pub f64);
 
 impl NbtType for NbtDouble {
-    fn read<R: Read>(r: &mut R) -> NbtResult<Self> {
-        let mut bs = [0u8; 8];
-        r.read_exact(&mut bs)?;
-        Ok(Self(f64::from_be_bytes(bs)))
+    fn read_limited<R: Read>(r: &mut NbtReader<R>) -> NbtResult<Self> {
+        Ok(Self(r.read_f64()?))
+    }
+
+    fn write_flavored<W: Write>(&self, w: &mut NbtWriter<W>) -> NbtResult<()> {
+        w.write_f64(self.0)
     }
 
     // fn to_tag_type(self) -> NbtTagType {
@@ -114,17 +149,25 @@ impl NbtType for NbtDouble {
 pub struct NbtByteArray(pub Vec<NbtByte>);
 
 impl NbtType for NbtByteArray {
-    fn read<R: Read>(r: &mut R) -> NbtResult<Self> {
-        let length = NbtInt::read(r)?;
-        let length = usize::try_from(length.0)?;
+    fn read_limited<R: Read>(r: &mut NbtReader<R>) -> NbtResult<Self> {
+        let length = r.read_array_length()?;
 
-        let mut buffer = Vec::with_capacity(length * size_of::<NbtByte>());
+        let mut buffer = Vec::with_capacity(r.capacity_for(length, size_of::<i8>()));
         for _ in 0..length {
-            buffer.push(NbtByte::read(r)?);
+            buffer.push(NbtByte::read_limited(r)?);
         }
         Ok(Self(buffer))
     }
 
+    fn write_flavored<W: Write>(&self, w: &mut NbtWriter<W>) -> NbtResult<()> {
+        w.write_array_length(self.0.len())?;
+        for b in self.0.iter() {
+            b.write_flavored(w)?;
+        }
+
+        Ok(())
+    }
+
     // fn to_tag_type(self) -> NbtTagType {
     //     NbtTagType::TagByteArray(self)
     // }
@@ -135,16 +178,12 @@ impl NbtType for NbtByteArray {
 pub struct NbtString(pub String);
 
 impl NbtType for NbtString {
-    fn read<R: Read>(r: &mut R) -> NbtResult<Self> {
-        let length = NbtShort::read(r)?;
-        let length = usize::try_from(length.0)?;
-
-        let mut string_buffer = vec![0; length];
-        r.read_exact(&mut string_buffer)?;
-
-        let s = String::from_utf8(string_buffer)?;
+    fn read_limited<R: Read>(r: &mut NbtReader<R>) -> NbtResult<Self> {
+        Ok(Self(r.read_string()?))
+    }
 
-        Ok(Self(s))
+    fn write_flavored<W: Write>(&self, w: &mut NbtWriter<W>) -> NbtResult<()> {
+        w.write_str(&self.0)
     }
 
     // fn to_tag_type(self) -> NbtTagType {
@@ -157,19 +196,25 @@ impl NbtType for NbtString {
 pub struct NbtList(pub Vec<NbtTagType>);
 
 impl NbtType for NbtList {
-    fn read<R: Read>(r: &mut R) -> NbtResult<Self> {
-        let tag_id = NbtByte::read(r)?;
-        let tag_id = u8::try_from(tag_id.0)?;
-
-        let length = NbtInt::read(r)?;
-        let length = usize::try_from(length.0)?;
+    fn read_limited<R: Read>(r: &mut NbtReader<R>) -> NbtResult<Self> {
+        let [tag_id] = r.read_bytes()?;
+        let length = r.read_array_length()?;
 
-        let mut buffer = Vec::new();
-        for _ in 0..length {
-            let t = NbtTagType::read(tag_id, r)?;
-            buffer.push(t);
+        if tag_id == NbtTagType::TagEnd.get_tag_id() && length > 0 {
+            return Err(NbtError::MalformedNbt(format!(
+                "List of type {tag_id} contained {length} elements"
+            )));
         }
 
+        let buffer = r.nested(|r| {
+            // Every element takes up at least a byte, e.g. an empty compound.
+            let mut buffer = Vec::with_capacity(r.capacity_for(length, 1));
+            for _ in 0..length {
+                buffer.push(NbtTagType::read_limited(tag_id, r)?);
+            }
+            NbtResult::Ok(buffer)
+        })?;
+
         if let Some(t) = buffer.iter().find(|e| e.get_tag_id() != tag_id) {
             return Err(NbtError::MalformedNbt(format!(
                 "List of type {tag_id} contained element with id {}",
@@ -180,6 +225,26 @@ impl NbtType for NbtList {
         Ok(Self(buffer))
     }
 
+    /// Empty lists are written with the element type TAG_End, same as vanilla does.
+    fn write_flavored<W: Write>(&self, w: &mut NbtWriter<W>) -> NbtResult<()> {
+        let tag_id = self.0.first().map_or(0, NbtTagType::get_tag_id);
+
+        if let Some(t) = self.0.iter().find(|e| e.get_tag_id() != tag_id) {
+            return Err(NbtError::MalformedNbt(format!(
+                "List of type {tag_id} contained element with id {}",
+                t.get_tag_id()
+            )));
+        }
+
+        w.write_bytes(&[tag_id])?;
+        w.write_array_length(self.0.len())?;
+        for t in self.0.iter() {
+            t.write_flavored(w)?;
+        }
+
+        Ok(())
+    }
+
     // fn to_tag_type(self) -> NbtTagType {
     //     NbtTagType::TagList(self)
     // }
@@ -190,20 +255,27 @@ impl NbtType for NbtList {
 pub struct NbtCompound(pub Vec<NbtNamedTag>);
 
 impl NbtType for NbtCompound {
-    fn read<R: Read>(r: &mut R) -> NbtResult<Self>
+    fn read_limited<R: Read>(r: &mut NbtReader<R>) -> NbtResult<Self>
     where
         Self: Sized,
     {
-        let mut buffer = Vec::new();
-        loop {
-            let Some(t) = NbtNamedTag::read(r)? else {
-                break;
-            };
+        r.nested(|r| {
+            let mut buffer = Vec::new();
+            while let Some(t) = NbtNamedTag::read_limited(r)? {
+                buffer.push(t);
+            }
+
+            Ok(Self(buffer))
+        })
+    }
 
-            buffer.push(t);
+    fn write_flavored<W: Write>(&self, w: &mut NbtWriter<W>) -> NbtResult<()> {
+        for t in self.0.iter() {
+            t.write_flavored(w)?;
         }
+        w.write_bytes(&[NbtTagType::TagEnd.get_tag_id()])?;
 
-        Ok(Self(buffer))
+        Ok(())
     }
 
     // fn to_tag_type(self) -> NbtTagType {
@@ -216,18 +288,26 @@ impl NbtType for NbtCompound {
 pub struct NbtIntArray(pub Vec<NbtInt>);
 
 impl NbtType for NbtIntArray {
-    fn read<R: Read>(r: &mut R) -> NbtResult<Self> {
-        let length = NbtInt::read(r)?;
-        let length = usize::try_from(length.0)?;
+    fn read_limited<R: Read>(r: &mut NbtReader<R>) -> NbtResult<Self> {
+        let length = r.read_array_length()?;
 
-        let mut buffer = Vec::with_capacity(length * size_of::<NbtInt>());
+        let mut buffer = Vec::with_capacity(r.capacity_for(length, size_of::<i32>()));
         for _ in 0..length {
-            buffer.push(NbtInt::read(r)?);
+            buffer.push(NbtInt::read_limited(r)?);
         }
 
         Ok(Self(buffer))
     }
 
+    fn write_flavored<W: Write>(&self, w: &mut NbtWriter<W>) -> NbtResult<()> {
+        w.write_array_length(self.0.len())?;
+        for i in self.0.iter() {
+            i.write_flavored(w)?;
+        }
+
+        Ok(())
+    }
+
     // fn to_tag_type(self) -> NbtTagType {
     //     NbtTagType::TagIntArray(self)
     // }
@@ -238,19 +318,32 @@ impl NbtType for NbtIntArray {
 pub struct NbtLongArray(pub Vec<NbtLong>);
 
 impl NbtType for NbtLongArray {
-    fn read<R: Read>(r: &mut R) -> NbtResult<Self> {
-        let length = NbtInt::read(r)?;
-        let length = usize::try_from(length.0)?;
+    fn read_limited<R: Read>(r: &mut NbtReader<R>) -> NbtResult<Self> {
+        let length = r.read_array_length()?;
 
-        let mut buffer = Vec::with_capacity(length * size_of::<NbtLong>());
+        let mut buffer = Vec::with_capacity(r.capacity_for(length, size_of::<i64>()));
         for _ in 0..length {
-            buffer.push(NbtLong::read(r)?);
+            buffer.push(NbtLong::read_limited(r)?);
         }
 
         Ok(Self(buffer))
     }
 
+    fn write_flavored<W: Write>(&self, w: &mut NbtWriter<W>) -> NbtResult<()> {
+        w.write_array_length(self.0.len())?;
+        for l in self.0.iter() {
+            l.write_flavored(w)?;
+        }
+
+        Ok(())
+    }
+
     // fn to_tag_type(self) -> NbtTagType {
     //     NbtTagType::TagLongArray(self)
     // }
 }
+
+/// Writes the provided [s] in the NBT string format, i.e. Modified UTF-8 prefixed by its length as an u16.
+pub(crate) fn write_str<W: Write>(s: &str, w: &mut W) -> NbtResult<()> {
+    NbtWriter::new(w, NbtFlavor::Java).write_str(s)
+}
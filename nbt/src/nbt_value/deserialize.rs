@@ -1,6 +1,10 @@
-use std::collections::HashMap;
-
-use serde::{Deserialize, de::Visitor};
+use serde::{
+    Deserialize, Deserializer,
+    de::{
+        DeserializeSeed, EnumAccess, Error, Unexpected, VariantAccess, Visitor,
+        value::StrDeserializer,
+    },
+};
 
 use crate::nbt_value::value::NbtValue;
 
@@ -150,13 +154,38 @@ impl<'de> Visitor<'de> for NbtValueVisitor {
     where
         A: serde::de::MapAccess<'de>,
     {
-        let mut inner_map = HashMap::new();
+        let mut entries = Vec::new();
 
-        while let Some((key, value)) = map.next_entry()? {
-            inner_map.insert(key, value);
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
         }
 
-        Ok(NbtValue::Compound(inner_map))
+        Ok(NbtValue::Compound(entries))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // Deserializers that don't know about NbtValue::SERDE_NAME.
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        // Only used by the NBT deserializers to hand over arrays, see NbtValue::SERDE_NAME.
+        let (variant, access) = data.variant::<String>()?;
+        match variant.as_str() {
+            "ByteArray" => access.newtype_variant().map(NbtValue::ByteArray),
+            "IntArray" => access.newtype_variant().map(NbtValue::IntArray),
+            "LongArray" => access.newtype_variant().map(NbtValue::LongArray),
+            v => Err(A::Error::unknown_variant(
+                v,
+                &["ByteArray", "IntArray", "LongArray"],
+            )),
+        }
     }
 }
 
@@ -165,6 +194,76 @@ impl<'de> Deserialize<'de> for NbtValue {
     where
         D: serde::Deserializer<'de>,
     {
-        deserializer.deserialize_any(NbtValueVisitor)
+        deserializer.deserialize_newtype_struct(NbtValue::SERDE_NAME, NbtValueVisitor)
+    }
+}
+
+/// Hands an NBT array to a visitor as a newtype variant named after its NbtValue variant.
+pub(crate) struct ArrayVariantAccess<D> {
+    variant: &'static str,
+    deserializer: D,
+}
+
+impl<D> ArrayVariantAccess<D> {
+    pub(crate) fn new(variant: &'static str, deserializer: D) -> Self {
+        Self {
+            variant,
+            deserializer,
+        }
+    }
+}
+
+impl<'de, D: Deserializer<'de>> EnumAccess<'de> for ArrayVariantAccess<D> {
+    type Error = D::Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(StrDeserializer::<D::Error>::new(self.variant))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, D: Deserializer<'de>> VariantAccess<'de> for ArrayVariantAccess<D> {
+    type Error = D::Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Err(D::Error::invalid_type(
+            Unexpected::NewtypeVariant,
+            &"unit variant",
+        ))
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.deserializer)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(D::Error::invalid_type(
+            Unexpected::NewtypeVariant,
+            &"tuple variant",
+        ))
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(D::Error::invalid_type(
+            Unexpected::NewtypeVariant,
+            &"struct variant",
+        ))
     }
 }
@@ -1,5 +1,3 @@
-use std::collections::HashMap;
-
 use serde::{
     Deserializer,
     de::{
@@ -9,7 +7,8 @@ use serde::{
 };
 
 use crate::{
-    nbt_value::{NbtValueError, value::NbtValue},
+    nbt_value::{NbtValueError, deserialize::ArrayVariantAccess, value::NbtValue},
+    ser::array::{BYTE_ARRAY_NAME, INT_ARRAY_NAME, LONG_ARRAY_NAME},
     unsupported_value,
 };
 
@@ -260,15 +259,15 @@ impl<'de> Deserializer<'de> for NbtValue {
     where
         V: serde::de::Visitor<'de>,
     {
-        let hash_map = match self {
+        let entries = match self {
             NbtValue::String(s) => return visitor.visit_enum(s.into_deserializer()),
-            NbtValue::Compound(hash_map) => hash_map,
+            NbtValue::Compound(entries) => entries,
             _ => {
                 return Err(Self::Error::Unexpected("Expected compound for enum"));
             }
         };
 
-        let mut iter = hash_map.into_iter();
+        let mut iter = entries.into_iter();
         let (key, val) = match iter.next() {
             Some((k, v)) => (k, v),
             None => {
@@ -315,11 +314,12 @@ impl<'de> Deserializer<'de> for NbtValue {
         unsupported_value!("Byte buf")
     }
 
-    fn deserialize_option<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        unsupported_value!("Option")
+        // Same as the other deserializers, missing values never reach the deserializer.
+        visitor.visit_some(self)
     }
 
     fn deserialize_unit<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
@@ -342,13 +342,31 @@ impl<'de> Deserializer<'de> for NbtValue {
 
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
-        _visitor: V,
+        name: &'static str,
+        visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        unsupported_value!("Newtype struct")
+        match (name, &self) {
+            (NbtValue::SERDE_NAME, NbtValue::ByteArray(_)) => {
+                visitor.visit_enum(ArrayVariantAccess::new("ByteArray", self))
+            }
+            (NbtValue::SERDE_NAME, NbtValue::IntArray(_)) => {
+                visitor.visit_enum(ArrayVariantAccess::new("IntArray", self))
+            }
+            (NbtValue::SERDE_NAME, NbtValue::LongArray(_)) => {
+                visitor.visit_enum(ArrayVariantAccess::new("LongArray", self))
+            }
+            (NbtValue::SERDE_NAME, _) => self.deserialize_any(visitor),
+            (BYTE_ARRAY_NAME, NbtValue::ByteArray(_))
+            | (INT_ARRAY_NAME, NbtValue::IntArray(_))
+            | (LONG_ARRAY_NAME, NbtValue::LongArray(_))
+            | (BYTE_ARRAY_NAME | INT_ARRAY_NAME | LONG_ARRAY_NAME, NbtValue::List(_)) => {
+                visitor.visit_newtype_struct(self)
+            }
+            _ => unsupported_value!("Newtype struct"),
+        }
     }
 
     fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
@@ -371,15 +389,15 @@ impl<'de> Deserializer<'de> for NbtValue {
     }
 }
 
+/// Hands out the entries of a compound in order, they are stored reversed so they can be popped.
 struct NbtValueCompoundvisitor {
     contents: Vec<(String, NbtValue)>,
 }
 
 impl NbtValueCompoundvisitor {
-    fn new(map: HashMap<String, NbtValue>) -> Self {
-        Self {
-            contents: map.into_iter().collect(),
-        }
+    fn new(mut entries: Vec<(String, NbtValue)>) -> Self {
+        entries.reverse();
+        Self { contents: entries }
     }
 }
 
@@ -390,7 +408,7 @@ impl<'de> MapAccess<'de> for NbtValueCompoundvisitor {
     where
         K: serde::de::DeserializeSeed<'de>,
     {
-        let Some((key, _)) = self.contents.first() else {
+        let Some((key, _)) = self.contents.last() else {
             return Ok(None);
         };
 
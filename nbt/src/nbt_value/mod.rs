@@ -4,6 +4,8 @@ use std::fmt::Display;
 pub mod deserialize;
 /// Deserializer implementation for NbtValue.
 pub mod deserializer;
+/// Serialize implementation for NbtValue.
+pub mod serialize;
 /// The actual NbtValue type.
 pub mod value;
 
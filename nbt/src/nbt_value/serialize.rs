@@ -0,0 +1,38 @@
+use serde::{Serialize, Serializer, ser::SerializeMap};
+
+use crate::{
+    nbt_value::value::NbtValue,
+    ser::array::{BYTE_ARRAY_NAME, INT_ARRAY_NAME, LONG_ARRAY_NAME},
+};
+
+impl Serialize for NbtValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            NbtValue::Byte(b) => serializer.serialize_i8(*b),
+            NbtValue::Short(s) => serializer.serialize_i16(*s),
+            NbtValue::Int(i) => serializer.serialize_i32(*i),
+            NbtValue::Long(l) => serializer.serialize_i64(*l),
+            NbtValue::Float(f) => serializer.serialize_f32(*f),
+            NbtValue::Double(d) => serializer.serialize_f64(*d),
+            NbtValue::ByteArray(bytes) => {
+                serializer.serialize_newtype_struct(BYTE_ARRAY_NAME, bytes)
+            }
+            NbtValue::String(s) => serializer.serialize_str(s),
+            NbtValue::List(values) => values.serialize(serializer),
+            NbtValue::Compound(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            NbtValue::IntArray(ints) => serializer.serialize_newtype_struct(INT_ARRAY_NAME, ints),
+            NbtValue::LongArray(longs) => {
+                serializer.serialize_newtype_struct(LONG_ARRAY_NAME, longs)
+            }
+        }
+    }
+}
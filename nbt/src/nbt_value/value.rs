@@ -1,12 +1,19 @@
 //! Generic NBT deserialized value, intended to function similar to serde_json's Value type.
 
-use std::{collections::HashMap, fmt::Display};
+use std::fmt::Display;
 
-use crate::{nbt_named_tag::NbtNamedTag, tag_type::NbtTagType};
+use crate::{
+    nbt_named_tag::NbtNamedTag,
+    nbt_types::{
+        NbtByte, NbtByteArray, NbtCompound, NbtDouble, NbtFloat, NbtInt, NbtIntArray, NbtList,
+        NbtLong, NbtLongArray, NbtShort, NbtString,
+    },
+    tag_type::NbtTagType,
+};
 
-/// Generic NBT value.
+/// Generic NBT value, compounds keep the order of their entries.
 #[allow(missing_docs)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum NbtValue {
     Byte(i8),
     Short(i16),
@@ -17,11 +24,16 @@ pub enum NbtValue {
     ByteArray(Vec<i8>),
     String(String),
     List(Vec<NbtValue>),
-    Compound(HashMap<String, NbtValue>),
+    Compound(Vec<(String, NbtValue)>),
     IntArray(Vec<i32>),
     LongArray(Vec<i64>),
 }
 
+impl NbtValue {
+    /// Newtype name the NBT deserializers use to recognize NbtValues, which lets them tell arrays apart from lists.
+    pub const SERDE_NAME: &str = "NbtValue";
+}
+
 impl Display for NbtValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -43,8 +55,8 @@ impl Display for NbtValue {
                     let vs = nbt_values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
                     format!("[{}]", vs.join(", "))
                 }
-                NbtValue::Compound(hash_map) => {
-                    let vs = hash_map
+                NbtValue::Compound(entries) => {
+                    let vs = entries
                         .iter()
                         .map(|(a, b)| format!("\"{a}\": {}", b))
                         .collect::<Vec<_>>();
@@ -65,11 +77,11 @@ impl Display for NbtValue {
 
 impl From<NbtNamedTag> for NbtValue {
     fn from(value: NbtNamedTag) -> Self {
-        let mut map = HashMap::new();
+        let mut entries = Vec::new();
         if let Some(payload) = value.payload.into() {
-            map.insert(value.name.0, payload);
+            entries.push((value.name.0, payload));
         }
-        Self::Compound(map)
+        Self::Compound(entries)
     }
 }
 
@@ -87,14 +99,9 @@ impl From<NbtTagType> for Option<NbtValue> {
                 NbtValue::ByteArray(nbt_byte_array.0.into_iter().map(|i| i.0).collect())
             }
             NbtTagType::TagString(nbt_string) => NbtValue::String(nbt_string.0),
-            NbtTagType::TagList(nbt_list) => NbtValue::List(
-                nbt_list
-                    .0
-                    .into_iter()
-                    .map(|l| l.into())
-                    .filter_map(|l| l)
-                    .collect(),
-            ),
+            NbtTagType::TagList(nbt_list) => {
+                NbtValue::List(nbt_list.0.into_iter().filter_map(|l| l.into()).collect())
+            }
             NbtTagType::TagCompound(nbt_compound) => NbtValue::Compound(
                 nbt_compound
                     .0
@@ -114,3 +121,38 @@ impl From<NbtTagType> for Option<NbtValue> {
         })
     }
 }
+
+impl From<NbtValue> for NbtTagType {
+    fn from(value: NbtValue) -> Self {
+        match value {
+            NbtValue::Byte(b) => NbtTagType::TagByte(NbtByte(b)),
+            NbtValue::Short(s) => NbtTagType::TagShort(NbtShort(s)),
+            NbtValue::Int(i) => NbtTagType::TagInt(NbtInt(i)),
+            NbtValue::Long(l) => NbtTagType::TagLong(NbtLong(l)),
+            NbtValue::Float(f) => NbtTagType::TagFloat(NbtFloat(f)),
+            NbtValue::Double(d) => NbtTagType::TagDouble(NbtDouble(d)),
+            NbtValue::ByteArray(bytes) => {
+                NbtTagType::TagByteArray(NbtByteArray(bytes.into_iter().map(NbtByte).collect()))
+            }
+            NbtValue::String(s) => NbtTagType::TagString(NbtString(s)),
+            NbtValue::List(values) => {
+                NbtTagType::TagList(NbtList(values.into_iter().map(NbtTagType::from).collect()))
+            }
+            NbtValue::Compound(entries) => NbtTagType::TagCompound(NbtCompound(
+                entries
+                    .into_iter()
+                    .map(|(name, value)| NbtNamedTag {
+                        name: NbtString(name),
+                        payload: value.into(),
+                    })
+                    .collect(),
+            )),
+            NbtValue::IntArray(ints) => {
+                NbtTagType::TagIntArray(NbtIntArray(ints.into_iter().map(NbtInt).collect()))
+            }
+            NbtValue::LongArray(longs) => {
+                NbtTagType::TagLongArray(NbtLongArray(longs.into_iter().map(NbtLong).collect()))
+            }
+        }
+    }
+}
@@ -0,0 +1,620 @@
+use std::{fmt::Display, fs, path::Path};
+
+use serde_json::Value;
+
+use crate::{NbtResult, error::NbtError, nbt_path::NbtPath, tag_type::NbtTagType};
+
+/// The expected shape of an NBT tag, validate tags against it with [NbtSchema::validate].
+/// Schemas can be written by hand or inferred from samples, e.g. the JSON files the vanilla data
+/// generator puts in `generated/data`, see [NbtSchema::infer_json].
+#[allow(missing_docs)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtSchema {
+    /// Matches every tag.
+    Any,
+    Byte,
+    Short,
+    Int,
+    Long,
+    Float,
+    Double,
+    /// Any of the numeric tags, booleans are bytes in NBT.
+    Numeric,
+    String,
+    ByteArray,
+    IntArray,
+    LongArray,
+    /// A list with elements matching the schema, numeric arrays match as well if their elements do.
+    List(Box<NbtSchema>),
+    Compound(CompoundSchema),
+    /// Matches if any of the schemas match.
+    OneOf(Vec<NbtSchema>),
+}
+
+/// A member of a [CompoundSchema].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSchema {
+    /// Name of the member.
+    pub name: String,
+    /// Schema of the member's value.
+    pub schema: NbtSchema,
+    /// Whether the member has to be present.
+    pub required: bool,
+}
+
+/// The expected members of a compound.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompoundSchema {
+    /// The known members.
+    pub fields: Vec<FieldSchema>,
+    /// Whether members that aren't in [CompoundSchema::fields] are allowed.
+    pub allow_unknown: bool,
+}
+
+impl CompoundSchema {
+    /// A compound without any members.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a member that has to be present.
+    pub fn required(mut self, name: &str, schema: NbtSchema) -> Self {
+        self.fields.push(FieldSchema {
+            name: name.to_string(),
+            schema,
+            required: true,
+        });
+        self
+    }
+
+    /// Add a member that may be missing.
+    pub fn optional(mut self, name: &str, schema: NbtSchema) -> Self {
+        self.fields.push(FieldSchema {
+            name: name.to_string(),
+            schema,
+            required: false,
+        });
+        self
+    }
+
+    /// Allow members that aren't part of the schema.
+    pub fn allow_unknown(mut self) -> Self {
+        self.allow_unknown = true;
+        self
+    }
+
+    fn field(&self, name: &str) -> Option<&FieldSchema> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+}
+
+impl From<CompoundSchema> for NbtSchema {
+    fn from(value: CompoundSchema) -> Self {
+        NbtSchema::Compound(value)
+    }
+}
+
+/// A tag that doesn't match its [NbtSchema].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaViolation {
+    /// Path of the offending tag, or of the compound for missing & unknown members.
+    pub path: NbtPath,
+    /// What is wrong with it.
+    pub kind: ViolationKind,
+}
+
+/// The ways a tag can violate an [NbtSchema].
+#[allow(missing_docs)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViolationKind {
+    MissingKey(String),
+    UnknownKey(String),
+    WrongType {
+        expected: String,
+        found: &'static str,
+    },
+}
+
+impl Display for SchemaViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ViolationKind::MissingKey(key) => {
+                write!(f, "{}: missing required key {key}", self.path)
+            }
+            ViolationKind::UnknownKey(key) => write!(f, "{}: unknown key {key}", self.path),
+            ViolationKind::WrongType { expected, found } => {
+                write!(f, "{}: expected {expected} but found {found}", self.path)
+            }
+        }
+    }
+}
+
+impl Display for NbtSchema {
+    /// A short description of the expected tag, used in violations.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NbtSchema::Any => f.write_str("any tag"),
+            NbtSchema::Byte => f.write_str("byte"),
+            NbtSchema::Short => f.write_str("short"),
+            NbtSchema::Int => f.write_str("int"),
+            NbtSchema::Long => f.write_str("long"),
+            NbtSchema::Float => f.write_str("float"),
+            NbtSchema::Double => f.write_str("double"),
+            NbtSchema::Numeric => f.write_str("number"),
+            NbtSchema::String => f.write_str("string"),
+            NbtSchema::ByteArray => f.write_str("byte_array"),
+            NbtSchema::IntArray => f.write_str("int_array"),
+            NbtSchema::LongArray => f.write_str("long_array"),
+            NbtSchema::List(element) => write!(f, "list of {element}"),
+            NbtSchema::Compound(_) => f.write_str("compound"),
+            NbtSchema::OneOf(schemas) => {
+                let schemas: Vec<String> = schemas.iter().map(|s| s.to_string()).collect();
+                f.write_str(&schemas.join(" or "))
+            }
+        }
+    }
+}
+
+impl NbtSchema {
+    /// Check [tag] against this schema, returns every violation found (so nothing if it matches).
+    pub fn validate(&self, tag: &NbtTagType) -> Vec<SchemaViolation> {
+        let mut violations = Vec::new();
+        self.validate_at(&NbtPath::root(), tag, &mut violations);
+        violations
+    }
+
+    fn validate_at(&self, path: &NbtPath, tag: &NbtTagType, violations: &mut Vec<SchemaViolation>) {
+        let wrong_type = || SchemaViolation {
+            path: path.clone(),
+            kind: ViolationKind::WrongType {
+                expected: self.to_string(),
+                found: tag.type_name(),
+            },
+        };
+
+        match (self, tag) {
+            (NbtSchema::Any, _)
+            | (NbtSchema::Byte, NbtTagType::TagByte(_))
+            | (NbtSchema::Short, NbtTagType::TagShort(_))
+            | (NbtSchema::Int, NbtTagType::TagInt(_))
+            | (NbtSchema::Long, NbtTagType::TagLong(_))
+            | (NbtSchema::Float, NbtTagType::TagFloat(_))
+            | (NbtSchema::Double, NbtTagType::TagDouble(_))
+            | (NbtSchema::String, NbtTagType::TagString(_))
+            | (NbtSchema::ByteArray, NbtTagType::TagByteArray(_))
+            | (NbtSchema::IntArray, NbtTagType::TagIntArray(_))
+            | (NbtSchema::LongArray, NbtTagType::TagLongArray(_)) => {}
+            (NbtSchema::Numeric, tag) if (1..=6).contains(&tag.get_tag_id()) => {}
+            (NbtSchema::List(element), NbtTagType::TagList(list)) => {
+                for (i, item) in list.0.iter().enumerate() {
+                    element.validate_at(&path.index(i), item, violations);
+                }
+            }
+            (NbtSchema::List(element), tag) => match (element.as_ref(), tag) {
+                (
+                    NbtSchema::Byte | NbtSchema::Numeric | NbtSchema::Any,
+                    NbtTagType::TagByteArray(_),
+                )
+                | (
+                    NbtSchema::Int | NbtSchema::Numeric | NbtSchema::Any,
+                    NbtTagType::TagIntArray(_),
+                )
+                | (
+                    NbtSchema::Long | NbtSchema::Numeric | NbtSchema::Any,
+                    NbtTagType::TagLongArray(_),
+                ) => {}
+                _ => violations.push(wrong_type()),
+            },
+            (NbtSchema::Compound(schema), NbtTagType::TagCompound(compound)) => {
+                for field in &schema.fields {
+                    match compound.0.iter().find(|t| t.name.0 == field.name) {
+                        Some(member) => field.schema.validate_at(
+                            &path.key(&field.name),
+                            &member.payload,
+                            violations,
+                        ),
+                        None if field.required => violations.push(SchemaViolation {
+                            path: path.clone(),
+                            kind: ViolationKind::MissingKey(field.name.clone()),
+                        }),
+                        None => {}
+                    }
+                }
+                if !schema.allow_unknown {
+                    for member in compound
+                        .0
+                        .iter()
+                        .filter(|t| schema.field(&t.name.0).is_none())
+                    {
+                        violations.push(SchemaViolation {
+                            path: path.clone(),
+                            kind: ViolationKind::UnknownKey(member.name.0.clone()),
+                        });
+                    }
+                }
+            }
+            (NbtSchema::OneOf(schemas), tag) => {
+                // Report the violations of the closest match if none match.
+                let best = schemas
+                    .iter()
+                    .map(|schema| {
+                        let mut found = Vec::new();
+                        schema.validate_at(path, tag, &mut found);
+                        found
+                    })
+                    .min_by_key(Vec::len);
+                match best {
+                    Some(found) if found.is_empty() => {}
+                    Some(found) if schemas.iter().any(|s| s.accepts_type(tag)) => {
+                        violations.extend(found)
+                    }
+                    _ => violations.push(wrong_type()),
+                }
+            }
+            _ => violations.push(wrong_type()),
+        }
+    }
+
+    /// Whether this schema accepts the type of [tag], without looking at its contents.
+    fn accepts_type(&self, tag: &NbtTagType) -> bool {
+        let mut violations = Vec::new();
+        match (self, tag) {
+            (NbtSchema::Compound(_), NbtTagType::TagCompound(_))
+            | (NbtSchema::List(_), NbtTagType::TagList(_)) => true,
+            (schema, tag) => {
+                schema.validate_at(&NbtPath::root(), tag, &mut violations);
+                violations.is_empty()
+            }
+        }
+    }
+
+    /// The schema [tag] exactly matches: every member is required & has the same type.
+    pub fn infer(tag: &NbtTagType) -> Self {
+        match tag {
+            NbtTagType::TagEnd => NbtSchema::Any,
+            NbtTagType::TagByte(_) => NbtSchema::Byte,
+            NbtTagType::TagShort(_) => NbtSchema::Short,
+            NbtTagType::TagInt(_) => NbtSchema::Int,
+            NbtTagType::TagLong(_) => NbtSchema::Long,
+            NbtTagType::TagFloat(_) => NbtSchema::Float,
+            NbtTagType::TagDouble(_) => NbtSchema::Double,
+            NbtTagType::TagByteArray(_) => NbtSchema::ByteArray,
+            NbtTagType::TagString(_) => NbtSchema::String,
+            NbtTagType::TagList(list) => NbtSchema::List(Box::new(Self::infer_elements(
+                list.0.iter().map(Self::infer),
+            ))),
+            NbtTagType::TagCompound(compound) => NbtSchema::Compound(CompoundSchema {
+                fields: compound
+                    .0
+                    .iter()
+                    .map(|t| FieldSchema {
+                        name: t.name.0.clone(),
+                        schema: Self::infer(&t.payload),
+                        required: true,
+                    })
+                    .collect(),
+                allow_unknown: false,
+            }),
+            NbtTagType::TagIntArray(_) => NbtSchema::IntArray,
+            NbtTagType::TagLongArray(_) => NbtSchema::LongArray,
+        }
+    }
+
+    /// Same as [NbtSchema::infer] but from JSON, e.g. the registry entries written by the vanilla data
+    /// generator. JSON doesn't know about tag types so numbers & booleans become [NbtSchema::Numeric].
+    pub fn infer_json(value: &Value) -> Self {
+        match value {
+            Value::Null => NbtSchema::Any,
+            Value::Bool(_) | Value::Number(_) => NbtSchema::Numeric,
+            Value::String(_) => NbtSchema::String,
+            Value::Array(values) => NbtSchema::List(Box::new(Self::infer_elements(
+                values.iter().map(Self::infer_json),
+            ))),
+            Value::Object(map) => NbtSchema::Compound(CompoundSchema {
+                fields: map
+                    .iter()
+                    .map(|(name, value)| FieldSchema {
+                        name: name.clone(),
+                        schema: Self::infer_json(value),
+                        required: true,
+                    })
+                    .collect(),
+                allow_unknown: false,
+            }),
+        }
+    }
+
+    /// Infer the schema of a registry from the entries the vanilla data generator wrote to [dir], e.g.
+    /// `generated/data/minecraft/damage_type`. Every `.json` file directly inside [dir] is an entry,
+    /// entries sent in `RegistryData` should match the returned schema.
+    pub fn infer_registry(dir: &Path) -> NbtResult<Self> {
+        let mut schemas = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "json") {
+                let value: Value = serde_json::from_str(&fs::read_to_string(&path)?)
+                    .map_err(|e| NbtError::InvalidJson(format!("{}: {e}", path.display())))?;
+                schemas.push(Self::infer_json(&value));
+            }
+        }
+
+        Ok(Self::infer_elements(schemas.into_iter()))
+    }
+
+    /// An empty list tells nothing about its elements, so they can be anything.
+    fn infer_elements(schemas: impl Iterator<Item = Self>) -> Self {
+        schemas.reduce(Self::merge).unwrap_or(NbtSchema::Any)
+    }
+
+    /// A schema matching everything either schema matches, so schemas inferred from several
+    /// samples can be combined. Members missing from one of the compounds become optional.
+    pub fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (NbtSchema::List(a), NbtSchema::List(b)) => match (*a, *b) {
+                // Only inferred from empty lists.
+                (NbtSchema::Any, element) | (element, NbtSchema::Any) => {
+                    NbtSchema::List(Box::new(element))
+                }
+                (a, b) => NbtSchema::List(Box::new(a.merge(b))),
+            },
+            (NbtSchema::Compound(a), NbtSchema::Compound(b)) => {
+                let mut fields = Vec::new();
+                for field in &a.fields {
+                    fields.push(match b.field(&field.name) {
+                        Some(other) => FieldSchema {
+                            name: field.name.clone(),
+                            schema: field.schema.clone().merge(other.schema.clone()),
+                            required: field.required && other.required,
+                        },
+                        None => FieldSchema {
+                            required: false,
+                            ..field.clone()
+                        },
+                    });
+                }
+                for field in b.fields.iter().filter(|f| a.field(&f.name).is_none()) {
+                    fields.push(FieldSchema {
+                        required: false,
+                        ..field.clone()
+                    });
+                }
+
+                NbtSchema::Compound(CompoundSchema {
+                    fields,
+                    allow_unknown: a.allow_unknown || b.allow_unknown,
+                })
+            }
+            (NbtSchema::Any, _) | (_, NbtSchema::Any) => NbtSchema::Any,
+            (a, b) if a.is_numeric() && b.is_numeric() => NbtSchema::Numeric,
+            (NbtSchema::OneOf(mut schemas), other) | (other, NbtSchema::OneOf(mut schemas)) => {
+                let others = match other {
+                    NbtSchema::OneOf(others) => others,
+                    other => vec![other],
+                };
+                for other in others {
+                    if !schemas.contains(&other) {
+                        schemas.push(other);
+                    }
+                }
+                NbtSchema::OneOf(schemas)
+            }
+            (a, b) => NbtSchema::OneOf(vec![a, b]),
+        }
+    }
+
+    fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            NbtSchema::Byte
+                | NbtSchema::Short
+                | NbtSchema::Int
+                | NbtSchema::Long
+                | NbtSchema::Float
+                | NbtSchema::Double
+                | NbtSchema::Numeric
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use serde_json::json;
+
+    use crate::{
+        json::{JsonMode, from_json},
+        snbt::Snbt,
+    };
+
+    use super::*;
+
+    fn parse(s: &str) -> NbtTagType {
+        NbtTagType::try_from(&Snbt::from(s)).unwrap()
+    }
+
+    fn violations(schema: &NbtSchema, snbt: &str) -> Vec<String> {
+        schema
+            .validate(&parse(snbt))
+            .iter()
+            .map(|v| v.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_validate() {
+        let section = CompoundSchema::new()
+            .required("Y", NbtSchema::Byte)
+            .optional("BlockLight", NbtSchema::ByteArray)
+            .allow_unknown();
+        let chunk: NbtSchema = CompoundSchema::new()
+            .required("DataVersion", NbtSchema::Int)
+            .required("Status", NbtSchema::String)
+            .required("sections", NbtSchema::List(Box::new(section.into())))
+            .optional(
+                "Heightmaps",
+                NbtSchema::Compound(CompoundSchema::new().allow_unknown()),
+            )
+            .into();
+
+        assert!(
+            violations(
+                &chunk,
+                r#"{DataVersion:4189,Status:"full",sections:[{Y:0b,biomes:{}},{Y:1b,BlockLight:[B;]}]}"#
+            )
+            .is_empty()
+        );
+        assert_eq!(
+            violations(
+                &chunk,
+                r#"{DataVersion:4189L,sections:[{Y:0b},{BlockLight:[I;]}],Heightmaps:[],extra:1}"#
+            ),
+            [
+                "DataVersion: expected int but found long",
+                "{}: missing required key Status",
+                "sections[1]: missing required key Y",
+                "sections[1].BlockLight: expected byte_array but found int_array",
+                "Heightmaps: expected compound but found list",
+                "{}: unknown key extra",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_infer() {
+        // Two entries of `generated/data/minecraft/damage_type`.
+        let schema = NbtSchema::infer_json(&json!({
+            "exhaustion": 0.1,
+            "message_id": "arrow",
+            "scaling": "when_caused_by_living_non_player"
+        }))
+        .merge(NbtSchema::infer_json(&json!({
+            "death_message_type": "intentional_game_design",
+            "exhaustion": 0.1,
+            "message_id": "badRespawnPoint",
+            "scaling": "always"
+        })));
+
+        // The way the entries are sent in RegistryData.
+        assert!(
+            violations(
+                &schema,
+                r#"{exhaustion:0.1f,message_id:"arrow",scaling:"when_caused_by_living_non_player"}"#
+            )
+            .is_empty()
+        );
+        assert_eq!(
+            violations(&schema, r#"{exhaustion:"0.1",death_message_type:1b}"#),
+            [
+                "exhaustion: expected number but found string",
+                "{}: missing required key message_id",
+                "{}: missing required key scaling",
+                "death_message_type: expected string but found byte",
+            ]
+        );
+
+        let schema = NbtSchema::infer(&parse(r#"{list:[],values:[1,2]}"#))
+            .merge(NbtSchema::infer(&parse(r#"{list:["a"],values:[3L]}"#)))
+            .merge(NbtSchema::infer(&parse(r#"{list:["b"],values:[I;4]}"#)));
+        assert_eq!(
+            schema,
+            CompoundSchema::new()
+                .required("list", NbtSchema::List(Box::new(NbtSchema::String)))
+                .required(
+                    "values",
+                    NbtSchema::OneOf(vec![
+                        NbtSchema::List(Box::new(NbtSchema::Numeric)),
+                        NbtSchema::IntArray
+                    ])
+                )
+                .into()
+        );
+
+        assert_eq!(
+            NbtSchema::OneOf(vec![NbtSchema::String, NbtSchema::IntArray]).merge(NbtSchema::OneOf(
+                vec![NbtSchema::IntArray, NbtSchema::LongArray]
+            )),
+            NbtSchema::OneOf(vec![
+                NbtSchema::String,
+                NbtSchema::IntArray,
+                NbtSchema::LongArray
+            ])
+        );
+        assert_eq!(NbtSchema::String.merge(NbtSchema::Any), NbtSchema::Any);
+        assert_eq!(
+            NbtSchema::OneOf(vec![NbtSchema::String, NbtSchema::IntArray]).merge(NbtSchema::Any),
+            NbtSchema::Any
+        );
+    }
+
+    #[test]
+    fn test_infer_registry() {
+        let dir = env::temp_dir().join(format!("nbt-schema-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("arrow.json"),
+            r#"{"exhaustion":0.1,"message_id":"arrow","scaling":"always"}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("fall.json"),
+            r#"{"death_message_type":"fall_variants","exhaustion":0.0,"message_id":"fall","scaling":"always"}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("README.md"), "not an entry").unwrap();
+
+        let schema = NbtSchema::infer_registry(&dir).unwrap();
+        fs::write(dir.join("broken.json"), "{").unwrap();
+        let broken = NbtSchema::infer_registry(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(
+            violations(
+                &schema,
+                r#"{exhaustion:0.1f,message_id:"arrow",scaling:"always"}"#
+            )
+            .is_empty()
+        );
+        assert_eq!(
+            violations(&schema, r#"{exhaustion:0.1f,scaling:"always"}"#),
+            ["{}: missing required key message_id"]
+        );
+        assert!(matches!(broken, Err(NbtError::InvalidJson(_))));
+    }
+
+    /// The registries the client needs in `RegistryData` before it can join, converted to NBT the way
+    /// they are sent have to match the schema inferred from the data generator output.
+    #[ignore = "Reads from the non-commited output of `make generate`"]
+    #[test]
+    fn test_generated_registries() {
+        let data = Path::new("../generated/data/minecraft");
+        for registry in [
+            "banner_pattern",
+            "chat_type",
+            "damage_type",
+            "dimension_type",
+            "painting_variant",
+            "trim_material",
+            "trim_pattern",
+            "wolf_variant",
+            "worldgen/biome",
+        ] {
+            let dir = data.join(registry);
+            let schema = NbtSchema::infer_registry(&dir).unwrap();
+
+            for entry in fs::read_dir(&dir).unwrap() {
+                let path = entry.unwrap().path();
+                let value: Value =
+                    serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+                let tag = from_json(&value, JsonMode::Lossy).unwrap();
+                let found: Vec<String> = schema
+                    .validate(&tag)
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect();
+                assert!(found.is_empty(), "{}: {found:?}", path.display());
+            }
+        }
+    }
+}
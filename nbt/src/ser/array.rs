@@ -0,0 +1,179 @@
+use std::{
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Visitor};
+
+use super::{
+    Error, TAG_BYTE, TAG_BYTE_ARRAY, TAG_INT, TAG_INT_ARRAY, TAG_LIST, TAG_LONG, TAG_LONG_ARRAY,
+};
+use crate::unsupported;
+
+/// Newtype struct name for sequences (de)serialized as a TAG_Byte_Array instead of a TAG_List.
+pub const BYTE_ARRAY_NAME: &str = "__nbt_byte_array";
+/// Newtype struct name for sequences (de)serialized as a TAG_Int_Array instead of a TAG_List.
+pub const INT_ARRAY_NAME: &str = "__nbt_int_array";
+/// Newtype struct name for sequences (de)serialized as a TAG_Long_Array instead of a TAG_List.
+pub const LONG_ARRAY_NAME: &str = "__nbt_long_array";
+
+/// The tag ID of the array type the newtype struct [name] stands for, if any.
+pub(crate) fn array_tag_id(name: &str) -> Option<u8> {
+    match name {
+        BYTE_ARRAY_NAME => Some(TAG_BYTE_ARRAY),
+        INT_ARRAY_NAME => Some(TAG_INT_ARRAY),
+        LONG_ARRAY_NAME => Some(TAG_LONG_ARRAY),
+        _ => None,
+    }
+}
+
+/// The tag ID of the elements of the array with the provided tag ID.
+pub(crate) fn array_element_id(tag_id: u8) -> Option<u8> {
+    match tag_id {
+        TAG_BYTE_ARRAY => Some(TAG_BYTE),
+        TAG_INT_ARRAY => Some(TAG_INT),
+        TAG_LONG_ARRAY => Some(TAG_LONG),
+        _ => None,
+    }
+}
+
+/// Newtype structs other than [NbtValue::SERDE_NAME](crate::nbt_value::value::NbtValue::SERDE_NAME)
+/// for the binary NBT deserializers, one of the array names requires the payload to be that array
+/// type (or a TAG_List, which older data may contain).
+pub(crate) fn deserialize_array_newtype<'de, D, V>(
+    deserializer: D,
+    tag_id: u8,
+    name: &'static str,
+    visitor: V,
+) -> Result<V::Value, Error>
+where
+    D: Deserializer<'de, Error = Error>,
+    V: Visitor<'de>,
+{
+    match array_tag_id(name) {
+        Some(array_id) if array_id == tag_id || tag_id == TAG_LIST => {
+            visitor.visit_newtype_struct(deserializer)
+        }
+        Some(_) => Err(Error::Unexpected("array of the newtype's array type")),
+        None => unsupported!("newtype_struct"),
+    }
+}
+
+/// Visitor for the array adaptors, accepts the array newtype as well as a plain sequence so both
+/// NBT arrays & lists (and non NBT formats) can be deserialized.
+struct ArrayVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for ArrayVisitor<T> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an NBT array or list")
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::deserialize(deserializer)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(v) = seq.next_element()? {
+            values.push(v);
+        }
+
+        Ok(values)
+    }
+}
+
+macro_rules! nbt_array {
+    ($(#[$doc:meta])* $name:ident, $module:ident, $element:ty, $serde_name:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Default, PartialEq, Eq)]
+        pub struct $name(pub Vec<$element>);
+
+        impl Deref for $name {
+            type Target = Vec<$element>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl DerefMut for $name {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.0
+            }
+        }
+
+        impl From<Vec<$element>> for $name {
+            fn from(value: Vec<$element>) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for Vec<$element> {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                $module::serialize(&self.0, serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                $module::deserialize(deserializer).map(Self)
+            }
+        }
+
+        #[doc = concat!("Adaptor for `#[serde(with = \"...\")]` on a `Vec<", stringify!($element), ">` field, same as [", stringify!($name), "].")]
+        pub mod $module {
+            use super::*;
+
+            /// Serialize the values as the array type regardless of the serializer's default for sequences.
+            pub fn serialize<S: Serializer>(
+                value: &[$element],
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                serializer.serialize_newtype_struct($serde_name, value)
+            }
+
+            /// Deserialize the values from either the array type or a list.
+            pub fn deserialize<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Vec<$element>, D::Error> {
+                deserializer.deserialize_newtype_struct($serde_name, ArrayVisitor(PhantomData))
+            }
+        }
+    };
+}
+
+nbt_array!(
+    /// A `Vec<i8>` that is serialized as a TAG_Byte_Array instead of a TAG_List of bytes.
+    ByteArray,
+    byte_array,
+    i8,
+    BYTE_ARRAY_NAME
+);
+nbt_array!(
+    /// A `Vec<i32>` that is serialized as a TAG_Int_Array instead of a TAG_List of ints.
+    IntArray,
+    int_array,
+    i32,
+    INT_ARRAY_NAME
+);
+nbt_array!(
+    /// A `Vec<i64>` that is serialized as a TAG_Long_Array instead of a TAG_List of longs.
+    LongArray,
+    long_array,
+    i64,
+    LONG_ARRAY_NAME
+);
@@ -1,6 +1,6 @@
 use crate::{
     nbt_named_tag::NbtNamedTag,
-    ser::{Error, Result},
+    ser::{self, Error, Result},
     tag_type::NbtTagType,
     unsupported,
 };
@@ -29,10 +29,18 @@ impl IntoDeserializer<'_, Error> for NbtTagType {
     }
 }
 
+/// Hands out the tags of a compound in order, they are stored reversed so they can be popped.
 struct CompoundVisitor {
     contents: Vec<NbtNamedTag>,
 }
 
+impl CompoundVisitor {
+    fn new(mut contents: Vec<NbtNamedTag>) -> Self {
+        contents.reverse();
+        Self { contents }
+    }
+}
+
 impl<'de> MapAccess<'de> for CompoundVisitor {
     type Error = Error;
 
@@ -92,13 +100,8 @@ impl<'de> de::Deserializer<'de> for Deserializer {
             return Err(Error::Unexpected("Expected boolean (byte)"));
         };
 
-        let b = match i.0 {
-            0 => true,
-            1 => false,
-            _ => return Err(Error::Unexpected("Expected valid bool value for byte")),
-        };
-
-        visitor.visit_bool(b)
+        // Any non-zero byte is true, same as vanilla & the binary NBT deserializers.
+        visitor.visit_bool(i.0 != 0)
     }
 
     fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value>
@@ -256,11 +259,12 @@ impl<'de> de::Deserializer<'de> for Deserializer {
         unsupported!("unit_struct")
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, _visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        unsupported!("newtype_struct")
+        let tag_id = self.input.get_tag_id();
+        ser::deserialize_newtype_struct(self, tag_id, name, visitor)
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
@@ -311,7 +315,7 @@ impl<'de> de::Deserializer<'de> for Deserializer {
             return Err(Error::Unexpected("Expected compound"));
         };
 
-        visitor.visit_map(CompoundVisitor { contents: comp.0 })
+        visitor.visit_map(CompoundVisitor::new(comp.0))
     }
 
     fn deserialize_struct<V>(
@@ -1,9 +1,37 @@
-use std::fmt::Display;
+use std::{fmt::Display, io};
 
+use crate::{
+    error::NbtError,
+    nbt_value::{deserialize::ArrayVariantAccess, value::NbtValue},
+};
+
+/// Wrapper types & serde adaptors for sequences that are (de)serialized as NBT arrays.
+pub mod array;
 /// Deserializer implementation.
 pub mod deserializer;
 /// Serializer implementation.
 pub mod serializer;
+/// Deserializer borrowing strings & arrays directly from a binary NBT slice.
+pub mod slice_deserializer;
+/// Deserializer reading binary NBT directly, without building an NbtTagType tree first.
+pub mod stream_deserializer;
+/// Serializer writing binary NBT directly, without building an NbtTagType tree first.
+pub mod stream_serializer;
+
+// Tag IDs used by the serializers & deserializers working on binary NBT directly.
+pub(crate) const TAG_END: u8 = 0;
+pub(crate) const TAG_BYTE: u8 = 1;
+pub(crate) const TAG_SHORT: u8 = 2;
+pub(crate) const TAG_INT: u8 = 3;
+pub(crate) const TAG_LONG: u8 = 4;
+pub(crate) const TAG_FLOAT: u8 = 5;
+pub(crate) const TAG_DOUBLE: u8 = 6;
+pub(crate) const TAG_BYTE_ARRAY: u8 = 7;
+pub(crate) const TAG_STRING: u8 = 8;
+pub(crate) const TAG_LIST: u8 = 9;
+pub(crate) const TAG_COMPOUND: u8 = 10;
+pub(crate) const TAG_INT_ARRAY: u8 = 11;
+pub(crate) const TAG_LONG_ARRAY: u8 = 12;
 
 /// Macro for handling cases that are not supported by the deserializer & serializer.
 #[macro_export]
@@ -24,6 +52,19 @@ pub enum Error {
     InvalidMapKey,
     MissingValueForKey,
     MissingKeyForValue,
+    Nbt(NbtError),
+}
+
+impl From<NbtError> for Error {
+    fn from(value: NbtError) -> Self {
+        Self::Nbt(value)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        Self::Nbt(NbtError::IoError(value))
+    }
 }
 
 impl std::error::Error for Error {}
@@ -58,8 +99,42 @@ impl Display for Error {
             Error::InvalidMapKey => f.write_str("Invalid key for map"),
             Error::MissingKeyForValue => f.write_str("Got map value without a key"),
             Error::MissingValueForKey => f.write_str("Got map key without a value"),
+            Error::Nbt(err) => f.write_str(&format!("Invalid NBT: {err}")),
         }
     }
 }
 
 type Result<T> = std::result::Result<T, Error>;
+
+/// The NbtValue variant name of the array with the provided tag ID.
+pub(crate) fn array_variant(tag_id: u8) -> Option<&'static str> {
+    match tag_id {
+        TAG_BYTE_ARRAY => Some("ByteArray"),
+        TAG_INT_ARRAY => Some("IntArray"),
+        TAG_LONG_ARRAY => Some("LongArray"),
+        _ => None,
+    }
+}
+
+/// Shared by the binary NBT deserializers for newtype structs, [NbtValue::SERDE_NAME] hands arrays
+/// to the visitor as an enum so the array type isn't lost, the array names are left to
+/// [array::deserialize_array_newtype].
+pub(crate) fn deserialize_newtype_struct<'de, D, V>(
+    deserializer: D,
+    tag_id: u8,
+    name: &'static str,
+    visitor: V,
+) -> Result<V::Value>
+where
+    D: serde::Deserializer<'de, Error = Error>,
+    V: serde::de::Visitor<'de>,
+{
+    if name == NbtValue::SERDE_NAME {
+        return match array_variant(tag_id) {
+            Some(variant) => visitor.visit_enum(ArrayVariantAccess::new(variant, deserializer)),
+            None => deserializer.deserialize_any(visitor),
+        };
+    }
+
+    array::deserialize_array_newtype(deserializer, tag_id, name, visitor)
+}
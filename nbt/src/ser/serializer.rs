@@ -3,9 +3,11 @@ use serde::{Serialize, ser};
 
 use crate::nbt_named_tag::NbtNamedTag;
 use crate::nbt_types::{
-    NbtByte, NbtCompound, NbtDouble, NbtFloat, NbtInt, NbtList, NbtLong, NbtShort, NbtString,
+    NbtByte, NbtByteArray, NbtCompound, NbtDouble, NbtFloat, NbtInt, NbtIntArray, NbtList, NbtLong,
+    NbtLongArray, NbtShort, NbtString,
 };
-use crate::ser::{Error, Result};
+use crate::ser::array::array_tag_id;
+use crate::ser::{Error, Result, TAG_BYTE_ARRAY, TAG_INT_ARRAY, TAG_LONG_ARRAY};
 use crate::tag_type::NbtTagType;
 use crate::unsupported;
 
@@ -125,11 +127,18 @@ impl ser::Serializer for &mut Serializer {
         unsupported!("unit_variant")
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, _value: &T) -> Result<Self::Ok>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
-        unsupported!("newtype_struct")
+        let Some(tag_id) = array_tag_id(name) else {
+            unsupported!("newtype_struct")
+        };
+        let Some(NbtTagType::TagList(list)) = value.serialize(&mut Serializer::new())? else {
+            return Err(Error::Unexpected("sequence for NBT array"));
+        };
+
+        list_to_array(tag_id, list).map(Some)
     }
 
     fn serialize_newtype_variant<T>(
@@ -191,6 +200,44 @@ impl ser::Serializer for &mut Serializer {
     }
 }
 
+/// Converts a list serialized for one of the array newtypes into the array with the provided tag ID.
+fn list_to_array(tag_id: u8, list: NbtList) -> Result<NbtTagType> {
+    let mismatch = || Error::Unexpected("array elements matching the array type");
+
+    let array = match tag_id {
+        TAG_BYTE_ARRAY => NbtTagType::TagByteArray(NbtByteArray(
+            list.0
+                .into_iter()
+                .map(|t| match t {
+                    NbtTagType::TagByte(b) => Ok(b),
+                    _ => Err(mismatch()),
+                })
+                .collect::<Result<_>>()?,
+        )),
+        TAG_INT_ARRAY => NbtTagType::TagIntArray(NbtIntArray(
+            list.0
+                .into_iter()
+                .map(|t| match t {
+                    NbtTagType::TagInt(i) => Ok(i),
+                    _ => Err(mismatch()),
+                })
+                .collect::<Result<_>>()?,
+        )),
+        TAG_LONG_ARRAY => NbtTagType::TagLongArray(NbtLongArray(
+            list.0
+                .into_iter()
+                .map(|t| match t {
+                    NbtTagType::TagLong(l) => Ok(l),
+                    _ => Err(mismatch()),
+                })
+                .collect::<Result<_>>()?,
+        )),
+        _ => unreachable!("Only called with array tag IDs"),
+    };
+
+    Ok(array)
+}
+
 /// Sequence serializer for NbtTagType handling serialization of list types.
 pub struct SeqSerializer {
     values: Vec<NbtTagType>,
@@ -245,8 +292,6 @@ impl SerializeMap for CompoundMapSerializer {
             return Err(Error::InvalidMapKey);
         };
 
-        println!("Serializing key for map {s:?}");
-
         self.new_name = Some(s);
 
         Ok(())
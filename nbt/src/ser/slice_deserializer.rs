@@ -0,0 +1,601 @@
+use std::{borrow::Cow, fmt, io};
+
+use serde::de::{
+    self, Deserialize, DeserializeSeed, MapAccess, SeqAccess,
+    value::{BorrowedStrDeserializer, StringDeserializer},
+};
+
+use crate::{
+    error::NbtError,
+    limits::NbtLimits,
+    mutf8,
+    ser::{
+        self, Error, Result, TAG_BYTE, TAG_BYTE_ARRAY, TAG_COMPOUND, TAG_DOUBLE, TAG_END,
+        TAG_FLOAT, TAG_INT, TAG_INT_ARRAY, TAG_LIST, TAG_LONG, TAG_LONG_ARRAY, TAG_SHORT,
+        TAG_STRING,
+    },
+    unsupported,
+};
+
+/// Deserialize a value from the binary NBT root tag in [bytes], the name of the root tag is ignored.
+/// Strings (`&str`), byte arrays (`&[u8]`) and long arrays ([LongArrayView]) can be borrowed from [bytes]
+/// instead of being copied, owned types are deserialized the same way as with the stream deserializer.
+/// Strings containing nulls or characters outside the Basic Multilingual Plane can't be borrowed, as
+/// their Modified UTF-8 encoding differs from UTF-8, use `Cow<str>` for those.
+/// The [NbtLimits::default] apply, see [from_slice_with_limits].
+pub fn from_slice<'de, T: Deserialize<'de>>(bytes: &'de [u8]) -> Result<T> {
+    from_slice_with_limits(bytes, NbtLimits::default())
+}
+
+/// Same as [from_slice], but enforcing the provided [limits].
+pub fn from_slice_with_limits<'de, T: Deserialize<'de>>(
+    bytes: &'de [u8],
+    limits: NbtLimits,
+) -> Result<T> {
+    // Nothing past max_bytes is ever looked at, running into the end of it means the NBT is larger.
+    let truncated = bytes.len() > limits.max_bytes;
+    let mut input = &bytes[..bytes.len().min(limits.max_bytes)];
+
+    let result = (|| {
+        let tag_id = read_u8(&mut input)?;
+        if tag_id == TAG_END {
+            return Err(Error::Unexpected("root tag, found TAG_End"));
+        }
+        read_str(&mut input)?;
+
+        T::deserialize(SliceDeserializer {
+            input: &mut input,
+            tag_id,
+            limits,
+            depth: 0,
+        })
+    })();
+
+    match result {
+        Err(Error::Nbt(NbtError::IoError(err)))
+            if truncated && err.kind() == io::ErrorKind::UnexpectedEof =>
+        {
+            Err(NbtError::LimitExceeded {
+                limit: "size in bytes",
+                max: limits.max_bytes,
+            }
+            .into())
+        }
+        result => result,
+    }
+}
+
+/// A borrowed view over the big-endian payload of a TAG_Long_Array, elements are decoded when accessed.
+/// Only the [SliceDeserializer] can produce it, as other deserializers have no input to borrow from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LongArrayView<'de>(&'de [u8]);
+
+impl<'de> LongArrayView<'de> {
+    /// Newtype name the slice deserializer uses to recognize long array views.
+    pub const SERDE_NAME: &'static str = "LongArrayView";
+
+    /// Number of longs in the array.
+    pub fn len(&self) -> usize {
+        self.0.len() / size_of::<i64>()
+    }
+
+    /// Whether the array is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Decode the long at [index], if it is within the array.
+    pub fn get(&self, index: usize) -> Option<i64> {
+        let start = index.checked_mul(size_of::<i64>())?;
+        let bs = self.0.get(start..start.checked_add(size_of::<i64>())?)?;
+        Some(i64::from_be_bytes(bs.try_into().ok()?))
+    }
+
+    /// Iterate over the decoded longs of the array.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = i64> + 'de {
+        self.0
+            .chunks_exact(size_of::<i64>())
+            .map(|bs| i64::from_be_bytes(bs.try_into().expect("Chunks are always 8 bytes")))
+    }
+
+    /// Copy the decoded longs into a vector.
+    pub fn to_vec(&self) -> Vec<i64> {
+        self.iter().collect()
+    }
+}
+
+struct LongArrayViewVisitor;
+
+impl<'de> de::Visitor<'de> for LongArrayViewVisitor {
+    type Value = LongArrayView<'de>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("borrowed long array")
+    }
+
+    fn visit_borrowed_bytes<E: de::Error>(
+        self,
+        v: &'de [u8],
+    ) -> std::result::Result<Self::Value, E> {
+        if !v.len().is_multiple_of(size_of::<i64>()) {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+
+        Ok(LongArrayView(v))
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for LongArrayView<'a> {
+    fn deserialize<D: de::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(Self::SERDE_NAME, LongArrayViewVisitor)
+    }
+}
+
+/// Deserializer for a single binary NBT payload with a known tag ID, borrowing from the input slice.
+pub struct SliceDeserializer<'a, 'de> {
+    input: &'a mut &'de [u8],
+    tag_id: u8,
+    limits: NbtLimits,
+    /// Number of lists & compounds this payload is nested in.
+    depth: usize,
+}
+
+/// Split [n] bytes off the front of [input].
+fn take<'de>(input: &mut &'de [u8], n: usize) -> Result<&'de [u8]> {
+    if input.len() < n {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+
+    let (taken, rest) = input.split_at(n);
+    *input = rest;
+    Ok(taken)
+}
+
+macro_rules! read_number {
+    ($input:expr, $type:ty) => {{
+        let bs = take($input, size_of::<$type>())?;
+        <$type>::from_be_bytes(bs.try_into().expect("Took exactly the size of the number"))
+    }};
+}
+
+fn read_u8(input: &mut &[u8]) -> Result<u8> {
+    Ok(read_number!(input, u8))
+}
+
+fn read_length(input: &mut &[u8]) -> Result<usize> {
+    Ok(usize::try_from(read_number!(input, i32)).map_err(NbtError::from)?)
+}
+
+/// Strings are only borrowed if their Modified UTF-8 encoding is also valid UTF-8.
+fn read_str<'de>(input: &mut &'de [u8]) -> Result<Cow<'de, str>> {
+    let length = usize::from(read_number!(input, u16));
+    let bs = take(input, length)?;
+
+    Ok(mutf8::decode(bs)?)
+}
+
+/// Take the payload of an array with elements of [element_size] bytes.
+fn take_array<'de>(input: &mut &'de [u8], element_size: usize) -> Result<&'de [u8]> {
+    let length = read_length(input)?;
+    let size = length
+        .checked_mul(element_size)
+        .ok_or(Error::Unexpected("array length within bounds"))?;
+    take(input, size)
+}
+
+impl SliceDeserializer<'_, '_> {
+    fn expect(&self, tag_id: u8, msg: &'static str) -> Result<()> {
+        if self.tag_id != tag_id {
+            return Err(Error::Unexpected(msg));
+        }
+
+        Ok(())
+    }
+
+    /// The depth of the payloads inside this list or compound, if that's within the limits.
+    fn nested_depth(&self) -> Result<usize> {
+        if self.depth >= self.limits.max_depth {
+            return Err(NbtError::LimitExceeded {
+                limit: "nesting depth",
+                max: self.limits.max_depth,
+            }
+            .into());
+        }
+
+        Ok(self.depth + 1)
+    }
+}
+
+impl<'de> de::Deserializer<'de> for SliceDeserializer<'_, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.tag_id {
+            TAG_BYTE => self.deserialize_i8(visitor),
+            TAG_SHORT => self.deserialize_i16(visitor),
+            TAG_INT => self.deserialize_i32(visitor),
+            TAG_LONG => self.deserialize_i64(visitor),
+            TAG_FLOAT => self.deserialize_f32(visitor),
+            TAG_DOUBLE => self.deserialize_f64(visitor),
+            TAG_STRING => self.deserialize_str(visitor),
+            TAG_BYTE_ARRAY | TAG_LIST | TAG_INT_ARRAY | TAG_LONG_ARRAY => {
+                self.deserialize_seq(visitor)
+            }
+            TAG_COMPOUND => self.deserialize_map(visitor),
+            t => Err(Error::Nbt(NbtError::InvalidNbtTag(t))),
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_BYTE, "boolean (byte)")?;
+        visitor.visit_bool(read_number!(self.input, i8) != 0)
+    }
+
+    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_BYTE, "i8")?;
+        visitor.visit_i8(read_number!(self.input, i8))
+    }
+
+    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_SHORT, "i16")?;
+        visitor.visit_i16(read_number!(self.input, i16))
+    }
+
+    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_INT, "i32")?;
+        visitor.visit_i32(read_number!(self.input, i32))
+    }
+
+    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_LONG, "i64")?;
+        visitor.visit_i64(read_number!(self.input, i64))
+    }
+
+    fn deserialize_u8<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("u8")
+    }
+
+    fn deserialize_u16<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("u16")
+    }
+
+    fn deserialize_u32<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("u32")
+    }
+
+    fn deserialize_u64<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("u64")
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_FLOAT, "f32")?;
+        visitor.visit_f32(read_number!(self.input, f32))
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_DOUBLE, "f64")?;
+        visitor.visit_f64(read_number!(self.input, f64))
+    }
+
+    fn deserialize_char<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("char")
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_STRING, "string")?;
+        match read_str(self.input)? {
+            Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
+            Cow::Owned(s) => visitor.visit_string(s),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_BYTE_ARRAY, "byte array")?;
+        visitor.visit_borrowed_bytes(take_array(self.input, size_of::<i8>())?)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        // Should only be called if the value exists.
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("unit")
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("unit_struct")
+    }
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if name != LongArrayView::SERDE_NAME {
+            let tag_id = self.tag_id;
+            return ser::deserialize_newtype_struct(self, tag_id, name, visitor);
+        }
+
+        self.expect(TAG_LONG_ARRAY, "long array")?;
+        visitor.visit_borrowed_bytes(take_array(self.input, size_of::<i64>())?)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let element_id = match self.tag_id {
+            TAG_BYTE_ARRAY => TAG_BYTE,
+            TAG_INT_ARRAY => TAG_INT,
+            TAG_LONG_ARRAY => TAG_LONG,
+            TAG_LIST => read_u8(self.input)?,
+            _ => return Err(Error::Unexpected("list")),
+        };
+        let remaining = read_length(self.input)?;
+        if remaining > self.limits.max_array_length {
+            return Err(NbtError::LimitExceeded {
+                limit: "array length",
+                max: self.limits.max_array_length,
+            }
+            .into());
+        }
+
+        let mut access = ListAccess {
+            depth: self.nested_depth()?,
+            input: self.input,
+            element_id,
+            remaining,
+            limits: self.limits,
+        };
+        let value = visitor.visit_seq(&mut access)?;
+
+        // Skip whatever the visitor didn't consume so the input stays in sync.
+        while access
+            .next_element_seed(std::marker::PhantomData::<de::IgnoredAny>)?
+            .is_some()
+        {}
+
+        Ok(value)
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("tuple")
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        _visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("tuple_struct")
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_COMPOUND, "compound")?;
+
+        let mut access = CompoundAccess {
+            depth: self.nested_depth()?,
+            input: self.input,
+            value_id: None,
+            done: false,
+            limits: self.limits,
+        };
+        let value = visitor.visit_map(&mut access)?;
+
+        // Skip whatever the visitor didn't consume so the input stays in sync.
+        while access
+            .next_entry_seed(
+                std::marker::PhantomData::<de::IgnoredAny>,
+                std::marker::PhantomData::<de::IgnoredAny>,
+            )?
+            .is_some()
+        {}
+
+        Ok(value)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("enum")
+    }
+
+    fn deserialize_identifier<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("identifier")
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+}
+
+/// Reads the elements of a list or array.
+struct ListAccess<'a, 'de> {
+    input: &'a mut &'de [u8],
+    element_id: u8,
+    remaining: usize,
+    limits: NbtLimits,
+    depth: usize,
+}
+
+impl<'de> SeqAccess<'de> for ListAccess<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+
+        seed.deserialize(SliceDeserializer {
+            input: &mut *self.input,
+            tag_id: self.element_id,
+            limits: self.limits,
+            depth: self.depth,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// Reads the named tags of a compound until TAG_End.
+struct CompoundAccess<'a, 'de> {
+    input: &'a mut &'de [u8],
+    value_id: Option<u8>,
+    done: bool,
+    limits: NbtLimits,
+    depth: usize,
+}
+
+impl<'de> MapAccess<'de> for CompoundAccess<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.done {
+            return Ok(None);
+        }
+
+        let tag_id = read_u8(self.input)?;
+        if tag_id == TAG_END {
+            self.done = true;
+            return Ok(None);
+        }
+
+        let name = read_str(self.input)?;
+        self.value_id = Some(tag_id);
+        match name {
+            Cow::Borrowed(name) => seed.deserialize(BorrowedStrDeserializer::new(name)),
+            Cow::Owned(name) => seed.deserialize(StringDeserializer::new(name)),
+        }
+        .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let Some(tag_id) = self.value_id.take() else {
+            return Err(Error::MissingKeyForValue);
+        };
+
+        seed.deserialize(SliceDeserializer {
+            input: &mut *self.input,
+            tag_id,
+            limits: self.limits,
+            depth: self.depth,
+        })
+    }
+}
@@ -0,0 +1,417 @@
+use std::io::Read;
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess};
+
+use crate::{
+    error::NbtError,
+    limits::{NbtLimits, NbtReader},
+    ser::{
+        self, Error, Result, TAG_BYTE, TAG_BYTE_ARRAY, TAG_COMPOUND, TAG_DOUBLE, TAG_END,
+        TAG_FLOAT, TAG_INT, TAG_INT_ARRAY, TAG_LIST, TAG_LONG, TAG_LONG_ARRAY, TAG_SHORT,
+        TAG_STRING,
+    },
+    unsupported,
+};
+
+/// Deserialize a value from the binary NBT root tag read from [r], the name of the root tag is ignored.
+/// Reads directly from [r] instead of building an NbtTagType tree first, i.e. it accepts the same
+/// input as reading an [crate::nbt_named_tag::NbtNamedTag] and using [crate::ser::deserializer::Deserializer].
+/// The [NbtLimits::default] apply, see [from_reader_with_limits].
+pub fn from_reader<R: Read, T: DeserializeOwned>(r: &mut R) -> Result<T> {
+    from_reader_with_limits(r, NbtLimits::default())
+}
+
+/// Same as [from_reader], but enforcing the provided [limits].
+pub fn from_reader_with_limits<R: Read, T: DeserializeOwned>(
+    r: &mut R,
+    limits: NbtLimits,
+) -> Result<T> {
+    let r = &mut NbtReader::new(r, limits);
+    let [tag_id] = r.read_bytes()?;
+    if tag_id == TAG_END {
+        return Err(Error::Unexpected("root tag, found TAG_End"));
+    }
+    r.read_string()?;
+
+    T::deserialize(StreamDeserializer { r, tag_id })
+}
+
+/// Deserializer for a single binary NBT payload with a known tag ID.
+pub struct StreamDeserializer<'a, R: Read> {
+    r: &'a mut NbtReader<R>,
+    tag_id: u8,
+}
+
+impl<R: Read> StreamDeserializer<'_, R> {
+    fn expect(&self, tag_id: u8, msg: &'static str) -> Result<()> {
+        if self.tag_id != tag_id {
+            return Err(Error::Unexpected(msg));
+        }
+
+        Ok(())
+    }
+}
+
+impl<'de, R: Read> de::Deserializer<'de> for StreamDeserializer<'_, R> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.tag_id {
+            TAG_BYTE => self.deserialize_i8(visitor),
+            TAG_SHORT => self.deserialize_i16(visitor),
+            TAG_INT => self.deserialize_i32(visitor),
+            TAG_LONG => self.deserialize_i64(visitor),
+            TAG_FLOAT => self.deserialize_f32(visitor),
+            TAG_DOUBLE => self.deserialize_f64(visitor),
+            TAG_STRING => self.deserialize_string(visitor),
+            TAG_BYTE_ARRAY | TAG_LIST | TAG_INT_ARRAY | TAG_LONG_ARRAY => {
+                self.deserialize_seq(visitor)
+            }
+            TAG_COMPOUND => self.deserialize_map(visitor),
+            t => Err(Error::Nbt(NbtError::InvalidNbtTag(t))),
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_BYTE, "boolean (byte)")?;
+        visitor.visit_bool(i8::from_be_bytes(self.r.read_bytes()?) != 0)
+    }
+
+    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_BYTE, "i8")?;
+        visitor.visit_i8(i8::from_be_bytes(self.r.read_bytes()?))
+    }
+
+    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_SHORT, "i16")?;
+        visitor.visit_i16(self.r.read_i16()?)
+    }
+
+    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_INT, "i32")?;
+        visitor.visit_i32(self.r.read_i32()?)
+    }
+
+    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_LONG, "i64")?;
+        visitor.visit_i64(self.r.read_i64()?)
+    }
+
+    fn deserialize_u8<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("u8")
+    }
+
+    fn deserialize_u16<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("u16")
+    }
+
+    fn deserialize_u32<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("u32")
+    }
+
+    fn deserialize_u64<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("u64")
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_FLOAT, "f32")?;
+        visitor.visit_f32(self.r.read_f32()?)
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_DOUBLE, "f64")?;
+        visitor.visit_f64(self.r.read_f64()?)
+    }
+
+    fn deserialize_char<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("char")
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_STRING, "string")?;
+        visitor.visit_string(self.r.read_string()?)
+    }
+
+    fn deserialize_bytes<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("bytes")
+    }
+
+    fn deserialize_byte_buf<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("byte_buf")
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        // Should only be called if the value exists.
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("unit")
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("unit_struct")
+    }
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let tag_id = self.tag_id;
+        ser::deserialize_newtype_struct(self, tag_id, name, visitor)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let element_id = match self.tag_id {
+            TAG_BYTE_ARRAY => TAG_BYTE,
+            TAG_INT_ARRAY => TAG_INT,
+            TAG_LONG_ARRAY => TAG_LONG,
+            TAG_LIST => u8::from_be_bytes(self.r.read_bytes()?),
+            _ => return Err(Error::Unexpected("list")),
+        };
+        let remaining = self.r.read_array_length()?;
+
+        self.r.nested(|r| {
+            let mut access = ListAccess {
+                r,
+                element_id,
+                remaining,
+            };
+            let value = visitor.visit_seq(&mut access)?;
+
+            // Skip whatever the visitor didn't consume so the reader stays in sync.
+            while access
+                .next_element_seed(std::marker::PhantomData::<de::IgnoredAny>)?
+                .is_some()
+            {}
+
+            Ok(value)
+        })
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("tuple")
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        _visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("tuple_struct")
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_COMPOUND, "compound")?;
+
+        self.r.nested(|r| {
+            let mut access = CompoundAccess {
+                r,
+                value_id: None,
+                done: false,
+            };
+            let value = visitor.visit_map(&mut access)?;
+
+            // Skip whatever the visitor didn't consume so the reader stays in sync.
+            while access
+                .next_entry_seed(
+                    std::marker::PhantomData::<de::IgnoredAny>,
+                    std::marker::PhantomData::<de::IgnoredAny>,
+                )?
+                .is_some()
+            {}
+
+            Ok(value)
+        })
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("enum")
+    }
+
+    fn deserialize_identifier<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        unsupported!("identifier")
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+}
+
+/// Reads the elements of a list or array.
+struct ListAccess<'a, R: Read> {
+    r: &'a mut NbtReader<R>,
+    element_id: u8,
+    remaining: usize,
+}
+
+impl<'de, R: Read> SeqAccess<'de> for ListAccess<'_, R> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+
+        seed.deserialize(StreamDeserializer {
+            r: &mut *self.r,
+            tag_id: self.element_id,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// Reads the named tags of a compound until TAG_End.
+struct CompoundAccess<'a, R: Read> {
+    r: &'a mut NbtReader<R>,
+    value_id: Option<u8>,
+    done: bool,
+}
+
+impl<'de, R: Read> MapAccess<'de> for CompoundAccess<'_, R> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.done {
+            return Ok(None);
+        }
+
+        let [tag_id] = self.r.read_bytes()?;
+        if tag_id == TAG_END {
+            self.done = true;
+            return Ok(None);
+        }
+
+        let name = self.r.read_string()?;
+        self.value_id = Some(tag_id);
+        seed.deserialize(name.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let Some(tag_id) = self.value_id.take() else {
+            return Err(Error::MissingKeyForValue);
+        };
+
+        seed.deserialize(StreamDeserializer {
+            r: &mut *self.r,
+            tag_id,
+        })
+    }
+}
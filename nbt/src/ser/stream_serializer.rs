@@ -0,0 +1,566 @@
+use std::io::Write;
+
+use serde::ser::{Impossible, SerializeMap, SerializeSeq, SerializeStruct};
+use serde::{Serialize, Serializer};
+
+use crate::nbt_types::write_str;
+use crate::ser::{
+    Error, Result, TAG_BYTE, TAG_COMPOUND, TAG_DOUBLE, TAG_END, TAG_FLOAT, TAG_INT, TAG_LIST,
+    TAG_LONG, TAG_SHORT, TAG_STRING,
+    array::{array_element_id, array_tag_id},
+};
+use crate::unsupported;
+
+/// Serialize the provided value as a binary NBT root tag (with an empty name) directly to [w],
+/// without building an intermediate NbtTagType tree.
+/// The output is the same as writing the result of [crate::ser::serializer::to_nbt_tag_type].
+pub fn to_writer<W: Write, T: Serialize>(w: &mut W, value: &T) -> Result<()> {
+    to_writer_named(w, "", value)
+}
+
+/// Same as [to_writer] but uses the provided [name] for the root tag.
+pub fn to_writer_named<W: Write, T: Serialize>(w: &mut W, name: &str, value: &T) -> Result<()> {
+    value.serialize(StreamSerializer {
+        w,
+        position: Position::Named(name),
+    })
+}
+
+/// Where the value being serialized ends up, decides what is written in front of its payload.
+enum Position<'a> {
+    /// A named tag (the root or a compound member), written as tag ID, name & payload.
+    Named(&'a str),
+    /// An element in a list, the list header is written together with the first element.
+    ListElement(&'a mut ListHeader),
+    /// The sequence inside one of the array newtypes, the array header has already been written.
+    Array(u8),
+}
+
+/// The element type & length of a list, only known once the first element is serialized.
+struct ListHeader {
+    length: i32,
+    tag_id: Option<u8>,
+}
+
+/// Serializer writing a single value as binary NBT to the wrapped writer.
+pub struct StreamSerializer<'a, W: Write> {
+    w: &'a mut W,
+    position: Position<'a>,
+}
+
+impl<W: Write> StreamSerializer<'_, W> {
+    /// Writes whatever precedes a payload with the provided tag ID.
+    fn header(&mut self, tag_id: u8) -> Result<()> {
+        match &mut self.position {
+            Position::Named(name) => {
+                self.w.write_all(&[tag_id]).map_err(Error::from)?;
+                write_str(name, self.w)?;
+            }
+            Position::ListElement(header) => match header.tag_id {
+                None => {
+                    self.w.write_all(&[tag_id]).map_err(Error::from)?;
+                    self.w
+                        .write_all(&header.length.to_be_bytes())
+                        .map_err(Error::from)?;
+                    header.tag_id = Some(tag_id);
+                }
+                Some(list_id) if list_id != tag_id => {
+                    return Err(Error::SerdeCustom(format!(
+                        "List of type {list_id} can't contain element of type {tag_id}"
+                    )));
+                }
+                Some(_) => {}
+            },
+            Position::Array(_) => return Err(Error::Unexpected("sequence for NBT array")),
+        }
+
+        Ok(())
+    }
+
+    fn write_payload(&mut self, tag_id: u8, payload: &[u8]) -> Result<()> {
+        self.header(tag_id)?;
+        self.w.write_all(payload).map_err(Error::from)
+    }
+}
+
+impl<'a, W: Write> Serializer for StreamSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = ListSerializer<'a, W>;
+    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = CompoundSerializer<'a, W>;
+    type SerializeStruct = CompoundSerializer<'a, W>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_bool(mut self, v: bool) -> Result<Self::Ok> {
+        self.write_payload(TAG_BYTE, &[v as u8])
+    }
+
+    fn serialize_i8(mut self, v: i8) -> Result<Self::Ok> {
+        self.write_payload(TAG_BYTE, &v.to_be_bytes())
+    }
+
+    fn serialize_i16(mut self, v: i16) -> Result<Self::Ok> {
+        self.write_payload(TAG_SHORT, &v.to_be_bytes())
+    }
+
+    fn serialize_i32(mut self, v: i32) -> Result<Self::Ok> {
+        self.write_payload(TAG_INT, &v.to_be_bytes())
+    }
+
+    fn serialize_i64(mut self, v: i64) -> Result<Self::Ok> {
+        self.write_payload(TAG_LONG, &v.to_be_bytes())
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Self::Ok> {
+        unsupported!("u8")
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok> {
+        unsupported!("u16")
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Self::Ok> {
+        unsupported!("u32")
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Self::Ok> {
+        unsupported!("u64")
+    }
+
+    fn serialize_f32(mut self, v: f32) -> Result<Self::Ok> {
+        self.write_payload(TAG_FLOAT, &v.to_be_bytes())
+    }
+
+    fn serialize_f64(mut self, v: f64) -> Result<Self::Ok> {
+        self.write_payload(TAG_DOUBLE, &v.to_be_bytes())
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Self::Ok> {
+        unsupported!("char")
+    }
+
+    fn serialize_str(mut self, v: &str) -> Result<Self::Ok> {
+        self.header(TAG_STRING)?;
+        write_str(v, self.w)?;
+        Ok(())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok> {
+        unsupported!("bytes")
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        // Same as the tree serializer, None values are left out.
+        match self.position {
+            Position::Named(_) => Ok(()),
+            Position::ListElement(_) | Position::Array(_) => {
+                Err(Error::Unsupported("none in list"))
+            }
+        }
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        unsupported!("unit")
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        unsupported!("unit_struct")
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok> {
+        unsupported!("unit_variant")
+    }
+
+    fn serialize_newtype_struct<T>(mut self, name: &'static str, value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        let Some(tag_id) = array_tag_id(name) else {
+            unsupported!("newtype_struct")
+        };
+        let element_id = array_element_id(tag_id).expect("Array tag IDs have an element type");
+
+        self.header(tag_id)?;
+        value.serialize(StreamSerializer {
+            w: self.w,
+            position: Position::Array(element_id),
+        })
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        unsupported!("newtype_variant")
+    }
+
+    fn serialize_seq(mut self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        let Some(len) = len else {
+            return Err(Error::Unsupported("seq of unknown length"));
+        };
+        let length = i32::try_from(len)
+            .map_err(|_| Error::SerdeCustom(format!("List of length {len} is too long")))?;
+
+        let tag_id = match self.position {
+            // Arrays have no element type in their header & the elements are checked against it.
+            Position::Array(element_id) => {
+                self.w
+                    .write_all(&length.to_be_bytes())
+                    .map_err(Error::from)?;
+                Some(element_id)
+            }
+            _ => {
+                self.header(TAG_LIST)?;
+                None
+            }
+        };
+
+        Ok(ListSerializer {
+            w: self.w,
+            header: ListHeader { length, tag_id },
+            written: 0,
+        })
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        unsupported!("tuple")
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        unsupported!("tuple_struct")
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        unsupported!("tuple_variant")
+    }
+
+    fn serialize_map(mut self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        self.header(TAG_COMPOUND)?;
+        Ok(CompoundSerializer {
+            w: self.w,
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        mut self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct> {
+        self.header(TAG_COMPOUND)?;
+        Ok(CompoundSerializer {
+            w: self.w,
+            key: None,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        unsupported!("struct_variant")
+    }
+}
+
+/// Serializer for the elements of a TAG_List.
+pub struct ListSerializer<'a, W: Write> {
+    w: &'a mut W,
+    header: ListHeader,
+    /// Number of elements serialized so far, has to end up matching the length in the header.
+    written: i32,
+}
+
+impl<W: Write> SerializeSeq for ListSerializer<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        if self.written >= self.header.length {
+            return Err(Error::Unexpected(
+                "as many list elements as the provided length",
+            ));
+        }
+
+        value.serialize(StreamSerializer {
+            w: &mut *self.w,
+            position: Position::ListElement(&mut self.header),
+        })?;
+        self.written += 1;
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        // The length has already been written, so the elements have to match it.
+        if self.written != self.header.length {
+            return Err(Error::Unexpected(
+                "as many list elements as the provided length",
+            ));
+        }
+
+        if self.header.tag_id.is_none() {
+            // Nothing has been written for the list yet, same as vanilla empty lists are TAG_End.
+            self.w.write_all(&[TAG_END]).map_err(Error::from)?;
+            self.w.write_all(&0i32.to_be_bytes()).map_err(Error::from)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Serializer for the members of a TAG_Compound, used for both maps & structs.
+pub struct CompoundSerializer<'a, W: Write> {
+    w: &'a mut W,
+    key: Option<String>,
+}
+
+impl<W: Write> SerializeMap for CompoundSerializer<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let Some(key) = self.key.take() else {
+            return Err(Error::MissingKeyForValue);
+        };
+
+        value.serialize(StreamSerializer {
+            w: &mut *self.w,
+            position: Position::Named(&key),
+        })
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        if self.key.is_some() {
+            return Err(Error::KeyWithoutValue);
+        }
+
+        self.w.write_all(&[TAG_END]).map_err(Error::from)
+    }
+}
+
+impl<W: Write> SerializeStruct for CompoundSerializer<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(StreamSerializer {
+            w: &mut *self.w,
+            position: Position::Named(key),
+        })
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        SerializeMap::end(self)
+    }
+}
+
+/// Serializer only accepting strings, used for the keys of maps.
+struct KeySerializer;
+
+impl Serializer for KeySerializer {
+    type Ok = String;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<String, Error>;
+    type SerializeTuple = Impossible<String, Error>;
+    type SerializeTupleStruct = Impossible<String, Error>;
+    type SerializeTupleVariant = Impossible<String, Error>;
+    type SerializeMap = Impossible<String, Error>;
+    type SerializeStruct = Impossible<String, Error>;
+    type SerializeStructVariant = Impossible<String, Error>;
+
+    fn serialize_str(self, v: &str) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_char(self, _v: char) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_none(self) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_unit(self) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _value: &T,
+    ) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(Error::InvalidMapKey)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Error::InvalidMapKey)
+    }
+}
@@ -1,27 +1,72 @@
 use std::fmt::Display;
 
-use crate::{nbt_named_tag::NbtNamedTag, tag_type::NbtTagType};
-
-// TODO: Implement From<&Snbt> for NbtNamedTag
+use crate::{
+    NbtResult,
+    error::NbtError,
+    limits::NbtLimits,
+    nbt_named_tag::NbtNamedTag,
+    nbt_types::{
+        NbtByte, NbtByteArray, NbtCompound, NbtDouble, NbtFloat, NbtInt, NbtIntArray, NbtList,
+        NbtLong, NbtLongArray, NbtShort, NbtString,
+    },
+    nbt_value::value::NbtValue,
+    tag_type::NbtTagType,
+};
 
 /// SNBT type (Stringified Named Binary Tag)
 #[derive(PartialEq, Debug, Clone)]
 pub struct Snbt(String);
 
-impl From<&NbtNamedTag> for Snbt {
-    fn from(value: &NbtNamedTag) -> Self {
-        let name = &value.name.0;
+impl From<String> for Snbt {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
 
-        let name = if name
-            .chars()
-            .all(|c| c.is_ascii_alphanumeric() || "_-+.".contains(c))
-        {
-            name
-        } else {
-            &format!(r#""{name}""#)
-        };
-        let inner: Snbt = (&value.payload).into();
-        Self(format!("{}:{}", name, inner.0))
+impl From<&str> for Snbt {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl TryFrom<&Snbt> for NbtNamedTag {
+    type Error = NbtError;
+
+    /// Parses `name:value`, a bare compound or list (e.g. `{a:1b}`) is parsed as a tag with an empty name.
+    fn try_from(value: &Snbt) -> NbtResult<Self> {
+        let mut parser = SnbtParser::new(&value.0);
+        let tag = parser.parse_named_tag()?;
+        parser.finish()?;
+
+        Ok(tag)
+    }
+}
+
+impl TryFrom<&Snbt> for NbtTagType {
+    type Error = NbtError;
+
+    fn try_from(value: &Snbt) -> NbtResult<Self> {
+        let mut parser = SnbtParser::new(&value.0);
+        let tag = parser.parse_value()?;
+        parser.finish()?;
+
+        Ok(tag)
+    }
+}
+
+impl TryFrom<&NbtNamedTag> for Snbt {
+    type Error = NbtError;
+
+    fn try_from(value: &NbtNamedTag) -> NbtResult<Self> {
+        SnbtFormat::COMPACT.format_named(value)
+    }
+}
+
+impl TryFrom<&NbtValue> for Snbt {
+    type Error = NbtError;
+
+    fn try_from(value: &NbtValue) -> NbtResult<Self> {
+        (&NbtTagType::from(value.clone())).try_into()
     }
 }
 
@@ -31,63 +76,948 @@ impl Display for Snbt {
     }
 }
 
-impl From<&NbtTagType> for Snbt {
-    fn from(value: &NbtTagType) -> Self {
-        match value {
-            NbtTagType::TagEnd => Self("".to_string()),
-            NbtTagType::TagByte(nbt_byte) => Self(format!("{}b", nbt_byte.0)),
-            NbtTagType::TagShort(nbt_short) => Self(format!("{}s", nbt_short.0)),
-            NbtTagType::TagInt(nbt_int) => Self(format!("{}", nbt_int.0)),
-            NbtTagType::TagLong(nbt_long) => Self(format!("{}l", nbt_long.0)),
-            NbtTagType::TagFloat(nbt_float) => Self(format!("{}f", nbt_float.0)),
-            NbtTagType::TagDouble(nbt_double) => Self(format!("{}d", nbt_double.0)),
+impl TryFrom<&NbtTagType> for Snbt {
+    type Error = NbtError;
+
+    fn try_from(value: &NbtTagType) -> NbtResult<Self> {
+        SnbtFormat::COMPACT.format(value)
+    }
+}
+
+/// The quotes used around strings & keys that need them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SnbtQuote {
+    /// Always use double quotes.
+    #[default]
+    Double,
+    /// Always use single quotes.
+    Single,
+    /// Double quotes unless the string contains some, same as vanilla.
+    Minimal,
+}
+
+/// Options for turning NBT into SNBT, the output can always be parsed back into the same NBT.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SnbtFormat {
+    /// Spaces to indent nested lists & compounds with, everything is put on a single line if `None`.
+    pub indent: Option<usize>,
+    /// Write the keys of compounds in alphabetical order rather than the order they are stored in.
+    pub sort_keys: bool,
+    /// The quotes to use.
+    pub quote: SnbtQuote,
+}
+
+impl SnbtFormat {
+    /// Everything on a single line without any whitespace.
+    pub const COMPACT: Self = Self {
+        indent: None,
+        sort_keys: false,
+        quote: SnbtQuote::Double,
+    };
+
+    /// Indented by 4 spaces, similar to the output of `/data get`.
+    pub const PRETTY: Self = Self {
+        indent: Some(4),
+        sort_keys: false,
+        quote: SnbtQuote::Minimal,
+    };
+
+    /// Format [tag] as SNBT, errors if it contains NaN or infinite numbers as SNBT has no way to write them.
+    pub fn format(&self, tag: &NbtTagType) -> NbtResult<Snbt> {
+        check_finite(tag)?;
+        Ok(self.format_lossy(tag))
+    }
+
+    /// Format [tag] as `name:value`, errors if it contains NaN or infinite numbers.
+    pub fn format_named(&self, tag: &NbtNamedTag) -> NbtResult<Snbt> {
+        check_finite(&tag.payload)?;
+        let mut out = String::new();
+        self.write_key(&mut out, &tag.name.0);
+        self.write_tag(&mut out, &tag.payload, 0);
+        Ok(Snbt(out))
+    }
+
+    /// Same as [SnbtFormat::format] but writes NaN & infinite numbers the way `/data get` shows them
+    /// (e.g. `NaNf`), which parse back as strings. Only meant for showing NBT to people.
+    pub fn format_lossy(&self, tag: &NbtTagType) -> Snbt {
+        let mut out = String::new();
+        self.write_tag(&mut out, tag, 0);
+        Snbt(out)
+    }
+
+    fn write_key(&self, out: &mut String, key: &str) {
+        if !key.is_empty() && key.chars().all(is_unquoted_char) {
+            out.push_str(key);
+        } else {
+            self.write_quoted(out, key);
+        }
+        out.push(':');
+        if self.indent.is_some() {
+            out.push(' ');
+        }
+    }
+
+    fn write_quoted(&self, out: &mut String, s: &str) {
+        let quote = match self.quote {
+            SnbtQuote::Double => '"',
+            SnbtQuote::Single => '\'',
+            SnbtQuote::Minimal if s.contains('"') => '\'',
+            SnbtQuote::Minimal => '"',
+        };
+
+        out.push(quote);
+        for c in s.chars() {
+            match c {
+                '\\' => out.push_str("\\\\"),
+                c if c == quote => {
+                    out.push('\\');
+                    out.push(c);
+                }
+                '\n' => out.push_str("\\n"),
+                '\t' => out.push_str("\\t"),
+                '\r' => out.push_str("\\r"),
+                '\u{8}' => out.push_str("\\b"),
+                '\u{c}' => out.push_str("\\f"),
+                c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+                c => out.push(c),
+            }
+        }
+        out.push(quote);
+    }
+
+    fn separator(&self) -> &'static str {
+        if self.indent.is_some() { ", " } else { "," }
+    }
+
+    /// Write [items] between [open] & [close], one per line if indenting.
+    fn write_block<T>(
+        &self,
+        out: &mut String,
+        (open, close): (&str, &str),
+        items: &[T],
+        depth: usize,
+        mut write_item: impl FnMut(&mut String, &T),
+    ) {
+        out.push_str(open);
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            if let Some(indent) = self.indent {
+                out.push('\n');
+                out.push_str(&" ".repeat(indent * (depth + 1)));
+            }
+            write_item(out, item);
+        }
+        if let Some(indent) = self.indent.filter(|_| !items.is_empty()) {
+            out.push('\n');
+            out.push_str(&" ".repeat(indent * depth));
+        }
+        out.push_str(close);
+    }
+
+    fn write_array<T: Display>(&self, out: &mut String, prefix: char, items: &[T], suffix: &str) {
+        out.push('[');
+        out.push(prefix);
+        out.push(';');
+        for (i, item) in items.iter().enumerate() {
+            out.push_str(match i {
+                0 if self.indent.is_some() => " ",
+                0 => "",
+                _ => self.separator(),
+            });
+            out.push_str(&format!("{item}{suffix}"));
+        }
+        out.push(']');
+    }
+
+    fn write_tag(&self, out: &mut String, tag: &NbtTagType, depth: usize) {
+        match tag {
+            NbtTagType::TagEnd => {}
+            NbtTagType::TagByte(nbt_byte) => out.push_str(&format!("{}b", nbt_byte.0)),
+            NbtTagType::TagShort(nbt_short) => out.push_str(&format!("{}s", nbt_short.0)),
+            NbtTagType::TagInt(nbt_int) => out.push_str(&nbt_int.0.to_string()),
+            NbtTagType::TagLong(nbt_long) => out.push_str(&format!("{}l", nbt_long.0)),
+            NbtTagType::TagFloat(nbt_float) => out.push_str(&format!("{}f", nbt_float.0)),
+            NbtTagType::TagDouble(nbt_double) => out.push_str(&format!("{}d", nbt_double.0)),
             NbtTagType::TagByteArray(nbt_byte_array) => {
-                let content = nbt_byte_array
-                    .0
-                    .iter()
-                    .map(|b| format!("{}b", b.0))
-                    .collect::<Vec<String>>();
-                let content = content.join(",");
-                Self(format!("[B;{content}]"))
-            }
-            NbtTagType::TagString(nbt_string) => Self(format!(r#""{}""#, nbt_string.0)),
+                let bytes: Vec<_> = nbt_byte_array.0.iter().map(|b| b.0).collect();
+                self.write_array(out, 'B', &bytes, "b");
+            }
+            NbtTagType::TagString(nbt_string) => self.write_quoted(out, &nbt_string.0),
             NbtTagType::TagList(nbt_list) => {
-                let list = nbt_list
-                    .0
-                    .iter()
-                    .map(|v| Snbt::from(v).0)
-                    .collect::<Vec<String>>();
-                let list = list.join(",");
-
-                Self(format!("[{list}]"))
+                self.write_block(out, ("[", "]"), &nbt_list.0, depth, |out, v| {
+                    self.write_tag(out, v, depth + 1)
+                });
             }
             NbtTagType::TagCompound(nbt_compound) => {
-                let members = nbt_compound
-                    .0
-                    .iter()
-                    .map(|c| Snbt::from(c).0)
-                    .collect::<Vec<String>>();
-                let members = members.join(",");
-                Self(format!("{{{members}}}"))
+                let mut members: Vec<_> = nbt_compound.0.iter().collect();
+                if self.sort_keys {
+                    members.sort_by(|a, b| a.name.0.cmp(&b.name.0));
+                }
+                self.write_block(out, ("{", "}"), &members, depth, |out, c| {
+                    self.write_key(out, &c.name.0);
+                    self.write_tag(out, &c.payload, depth + 1);
+                });
             }
             NbtTagType::TagIntArray(nbt_int_array) => {
-                let content = nbt_int_array
-                    .0
-                    .iter()
-                    .map(|i| i.0.to_string())
-                    .collect::<Vec<String>>();
-                let content = content.join(",");
-                Self(format!("[I;{content}]"))
+                let ints: Vec<_> = nbt_int_array.0.iter().map(|i| i.0).collect();
+                self.write_array(out, 'I', &ints, "");
             }
             NbtTagType::TagLongArray(nbt_long_array) => {
-                let content = nbt_long_array
-                    .0
-                    .iter()
-                    .map(|b| format!("{}l", b.0))
-                    .collect::<Vec<String>>();
-                let content = content.join(",");
-                Self(format!("[L;{content}]"))
+                let longs: Vec<_> = nbt_long_array.0.iter().map(|l| l.0).collect();
+                self.write_array(out, 'L', &longs, "l");
             }
         }
     }
 }
+
+/// Errors if [tag] contains a float or double that SNBT can't represent.
+fn check_finite(tag: &NbtTagType) -> NbtResult<()> {
+    let finite = match tag {
+        NbtTagType::TagFloat(nbt_float) => nbt_float.0.is_finite(),
+        NbtTagType::TagDouble(nbt_double) => nbt_double.0.is_finite(),
+        NbtTagType::TagList(nbt_list) => return nbt_list.0.iter().try_for_each(check_finite),
+        NbtTagType::TagCompound(nbt_compound) => {
+            return nbt_compound
+                .0
+                .iter()
+                .try_for_each(|member| check_finite(&member.payload));
+        }
+        _ => true,
+    };
+
+    if !finite {
+        return Err(NbtError::NonFiniteSnbt(tag.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Recursive descent parser for SNBT, positions in errors are character (not byte) offsets into the input.
+pub(crate) struct SnbtParser {
+    pub(crate) chars: Vec<char>,
+    pub(crate) pos: usize,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl SnbtParser {
+    pub(crate) fn new(input: &str) -> Self {
+        Self {
+            chars: input.chars().collect(),
+            pos: 0,
+            depth: 0,
+            max_depth: NbtLimits::default().max_depth,
+        }
+    }
+
+    /// Run [f] one level deeper into the NBT tree, so deeply nested input results in an error
+    /// instead of overflowing the stack.
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> NbtResult<T>) -> NbtResult<T> {
+        if self.depth >= self.max_depth {
+            return Err(NbtError::LimitExceeded {
+                limit: "nesting depth",
+                max: self.max_depth,
+            });
+        }
+
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+
+        result
+    }
+
+    fn error_at(position: usize, reason: impl Into<String>) -> NbtError {
+        NbtError::InvalidSnbt {
+            position,
+            reason: reason.into(),
+        }
+    }
+
+    fn error(&self, reason: impl Into<String>) -> NbtError {
+        Self::error_at(self.pos, reason)
+    }
+
+    pub(crate) fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    pub(crate) fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> NbtResult<()> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("Expected '{expected}' but found '{c}'"))),
+            None => Err(self.error(format!("Expected '{expected}' but found end of input"))),
+        }
+    }
+
+    /// Ensures that only whitespace remains after the parsed value.
+    fn finish(&mut self) -> NbtResult<()> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c) => Err(self.error(format!("Unexpected trailing character '{c}'"))),
+            None => Ok(()),
+        }
+    }
+
+    fn parse_named_tag(&mut self) -> NbtResult<NbtNamedTag> {
+        self.skip_whitespace();
+        let name = match self.peek() {
+            Some('{' | '[') => String::new(),
+            _ => {
+                let name = self.parse_key()?;
+                self.expect(':')?;
+                name
+            }
+        };
+
+        Ok(NbtNamedTag {
+            name: NbtString(name),
+            payload: self.parse_value()?,
+        })
+    }
+
+    fn parse_value(&mut self) -> NbtResult<NbtTagType> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.nested(Self::parse_compound),
+            Some('[') => self.nested(Self::parse_list),
+            Some('"' | '\'') => Ok(NbtTagType::TagString(NbtString(
+                self.parse_quoted_string()?,
+            ))),
+            Some(c) if is_unquoted_char(c) => {
+                let start = self.pos;
+                let token = self.parse_unquoted_string();
+                parse_literal(token, start)
+            }
+            Some(c) => Err(self.error(format!("Unexpected character '{c}'"))),
+            None => Err(self.error("Expected value but found end of input")),
+        }
+    }
+
+    fn parse_key(&mut self) -> NbtResult<String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"' | '\'') => self.parse_quoted_string(),
+            Some(c) if is_unquoted_char(c) => Ok(self.parse_unquoted_string()),
+            Some(c) => Err(self.error(format!("Expected key but found '{c}'"))),
+            None => Err(self.error("Expected key but found end of input")),
+        }
+    }
+
+    pub(crate) fn parse_compound(&mut self) -> NbtResult<NbtTagType> {
+        self.expect('{')?;
+        let mut members: Vec<NbtNamedTag> = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(NbtTagType::TagCompound(NbtCompound(members)));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key_start = self.pos;
+            let name = self.parse_key()?;
+            if members.iter().any(|m| m.name.0 == name) {
+                return Err(Self::error_at(key_start, format!("Duplicate key '{name}'")));
+            }
+
+            self.expect(':')?;
+            let payload = self.parse_value()?;
+            members.push(NbtNamedTag {
+                name: NbtString(name),
+                payload,
+            });
+
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => break,
+                Some(c) => {
+                    return Err(Self::error_at(
+                        self.pos - 1,
+                        format!("Expected ',' or '}}' but found '{c}'"),
+                    ));
+                }
+                None => return Err(self.error("Unterminated compound, expected '}'")),
+            }
+        }
+
+        Ok(NbtTagType::TagCompound(NbtCompound(members)))
+    }
+
+    fn parse_list(&mut self) -> NbtResult<NbtTagType> {
+        self.expect('[')?;
+
+        if let (Some(kind @ ('B' | 'I' | 'L')), Some(';')) = (
+            self.chars.get(self.pos).copied(),
+            self.chars.get(self.pos + 1).copied(),
+        ) {
+            self.pos += 2;
+            return self.parse_typed_array(kind);
+        }
+
+        let mut elements: Vec<NbtTagType> = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(NbtTagType::TagList(NbtList(elements)));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let element_start = self.pos;
+            let element = self.parse_value()?;
+            if let Some(first) = elements.first()
+                && first.get_tag_id() != element.get_tag_id()
+            {
+                return Err(Self::error_at(
+                    element_start,
+                    format!(
+                        "List of type {} cannot contain element of type {}",
+                        first.get_tag_id(),
+                        element.get_tag_id()
+                    ),
+                ));
+            }
+            elements.push(element);
+
+            if self.list_separator()? {
+                break;
+            }
+        }
+
+        Ok(NbtTagType::TagList(NbtList(elements)))
+    }
+
+    /// Parses the elements of `[B;..]`, `[I;..]` or `[L;..]`, the prefix has already been consumed.
+    fn parse_typed_array(&mut self, kind: char) -> NbtResult<NbtTagType> {
+        let mut elements = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+        } else {
+            loop {
+                self.skip_whitespace();
+                let element_start = self.pos;
+                let token = self.parse_unquoted_string();
+                if token.is_empty() {
+                    return Err(self.error("Expected number in typed array"));
+                }
+
+                elements.push((element_start, parse_literal(token, element_start)?));
+
+                if self.list_separator()? {
+                    break;
+                }
+            }
+        }
+
+        Ok(match kind {
+            'B' => NbtTagType::TagByteArray(NbtByteArray(
+                elements
+                    .into_iter()
+                    .map(|(start, e)| match e {
+                        NbtTagType::TagByte(b) => Ok(b),
+                        NbtTagType::TagInt(i) => i8::try_from(i.0)
+                            .map(NbtByte)
+                            .map_err(|_| Self::error_at(start, "Value out of range for byte")),
+                        _ => Err(Self::error_at(start, "Byte array can only contain bytes")),
+                    })
+                    .collect::<NbtResult<_>>()?,
+            )),
+            'I' => NbtTagType::TagIntArray(NbtIntArray(
+                elements
+                    .into_iter()
+                    .map(|(start, e)| match e {
+                        NbtTagType::TagInt(i) => Ok(i),
+                        _ => Err(Self::error_at(start, "Int array can only contain ints")),
+                    })
+                    .collect::<NbtResult<_>>()?,
+            )),
+            _ => NbtTagType::TagLongArray(NbtLongArray(
+                elements
+                    .into_iter()
+                    .map(|(start, e)| match e {
+                        NbtTagType::TagLong(l) => Ok(l),
+                        NbtTagType::TagInt(i) => Ok(NbtLong(i64::from(i.0))),
+                        _ => Err(Self::error_at(start, "Long array can only contain longs")),
+                    })
+                    .collect::<NbtResult<_>>()?,
+            )),
+        })
+    }
+
+    /// Consumes the separator after a list element, returns true if the list was closed.
+    fn list_separator(&mut self) -> NbtResult<bool> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(',') => Ok(false),
+            Some(']') => Ok(true),
+            Some(c) => Err(Self::error_at(
+                self.pos - 1,
+                format!("Expected ',' or ']' but found '{c}'"),
+            )),
+            None => Err(self.error("Unterminated list, expected ']'")),
+        }
+    }
+
+    fn parse_unquoted_string(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(is_unquoted_char) {
+            self.pos += 1;
+        }
+
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    pub(crate) fn parse_quoted_string(&mut self) -> NbtResult<String> {
+        let start = self.pos;
+        let quote = self.next().expect("Caller checked for a quote");
+        let mut s = String::new();
+
+        loop {
+            match self.next() {
+                None => return Err(Self::error_at(start, "Unterminated string")),
+                Some(c) if c == quote => return Ok(s),
+                Some('\\') => s.push(self.parse_escape()?),
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    /// Parses an escape sequence, the leading backslash has already been consumed.
+    fn parse_escape(&mut self) -> NbtResult<char> {
+        let start = self.pos - 1;
+        let c = match self.next() {
+            Some(c @ ('\\' | '"' | '\'')) => c,
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('s') => ' ',
+            Some('x') => self.parse_unicode_escape(2, start)?,
+            Some('u') => self.parse_unicode_escape(4, start)?,
+            Some('U') => self.parse_unicode_escape(8, start)?,
+            Some(c) => {
+                return Err(Self::error_at(
+                    start,
+                    format!("Invalid escape sequence '\\{c}'"),
+                ));
+            }
+            None => return Err(Self::error_at(start, "Unterminated escape sequence")),
+        };
+
+        Ok(c)
+    }
+
+    fn parse_unicode_escape(&mut self, digits: usize, start: usize) -> NbtResult<char> {
+        let hex = self
+            .chars
+            .get(self.pos..self.pos + digits)
+            .map(|h| h.iter().collect::<String>())
+            .filter(|h| h.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| {
+                Self::error_at(start, format!("Expected {digits} hex digits in escape"))
+            })?;
+        self.pos += digits;
+
+        u32::from_str_radix(&hex, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| Self::error_at(start, format!("Invalid unicode escape '{hex}'")))
+    }
+}
+
+fn is_unquoted_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "_-+.".contains(c)
+}
+
+/// Turns an unquoted token into a number, boolean or (if it is neither) a string, same as vanilla.
+fn parse_literal(token: String, start: usize) -> NbtResult<NbtTagType> {
+    let out_of_range =
+        |kind: &str| SnbtParser::error_at(start, format!("Value out of range for {kind}"));
+
+    match token.as_str() {
+        "true" => return Ok(NbtTagType::TagByte(NbtByte(1))),
+        "false" => return Ok(NbtTagType::TagByte(NbtByte(0))),
+        _ => {}
+    }
+
+    let (body, suffix) = match token.char_indices().last() {
+        Some((i, c)) if "bBsSlLfFdD".contains(c) => (&token[..i], Some(c.to_ascii_lowercase())),
+        _ => (token.as_str(), None),
+    };
+
+    if is_integer(body) {
+        return Ok(match suffix {
+            None => NbtTagType::TagInt(NbtInt(body.parse().map_err(|_| out_of_range("int"))?)),
+            Some('b') => {
+                NbtTagType::TagByte(NbtByte(body.parse().map_err(|_| out_of_range("byte"))?))
+            }
+            Some('s') => {
+                NbtTagType::TagShort(NbtShort(body.parse().map_err(|_| out_of_range("short"))?))
+            }
+            Some('l') => {
+                NbtTagType::TagLong(NbtLong(body.parse().map_err(|_| out_of_range("long"))?))
+            }
+            Some('f') => {
+                NbtTagType::TagFloat(NbtFloat(body.parse().map_err(|_| out_of_range("float"))?))
+            }
+            _ => {
+                NbtTagType::TagDouble(NbtDouble(body.parse().map_err(|_| out_of_range("double"))?))
+            }
+        });
+    }
+
+    if is_decimal(body) {
+        match suffix {
+            Some('f') => {
+                return Ok(NbtTagType::TagFloat(NbtFloat(
+                    body.parse().map_err(|_| out_of_range("float"))?,
+                )));
+            }
+            Some('d') => {
+                return Ok(NbtTagType::TagDouble(NbtDouble(
+                    body.parse().map_err(|_| out_of_range("double"))?,
+                )));
+            }
+            // Doubles without a suffix need a decimal point, otherwise e.g. `1e5` is a string.
+            None if body.contains('.') => {
+                return Ok(NbtTagType::TagDouble(NbtDouble(
+                    body.parse().map_err(|_| out_of_range("double"))?,
+                )));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(NbtTagType::TagString(NbtString(token)))
+}
+
+/// Matches `[-+]?[0-9]+`.
+fn is_integer(s: &str) -> bool {
+    let digits = s.strip_prefix(['-', '+']).unwrap_or(s);
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Matches `[-+]?(?:[0-9]+[.]?|[0-9]*[.][0-9]+)(?:e[-+]?[0-9]+)?`.
+fn is_decimal(s: &str) -> bool {
+    let s = s.strip_prefix(['-', '+']).unwrap_or(s);
+    let (mantissa, exponent) = match s.find(['e', 'E']) {
+        Some(i) => (&s[..i], Some(&s[i + 1..])),
+        None => (s, None),
+    };
+
+    let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let valid_mantissa = !(int_part.is_empty() && frac_part.is_empty())
+        && int_part.chars().all(|c| c.is_ascii_digit())
+        && frac_part.chars().all(|c| c.is_ascii_digit());
+
+    valid_mantissa && exponent.is_none_or(is_integer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> NbtResult<NbtTagType> {
+        NbtTagType::try_from(&Snbt::from(s))
+    }
+
+    fn error_position(s: &str) -> usize {
+        match parse(s) {
+            Err(NbtError::InvalidSnbt { position, .. }) => position,
+            other => panic!("Expected SNBT error for {s}, got {other:?}"),
+        }
+    }
+
+    fn compound(members: Vec<(&str, NbtTagType)>) -> NbtTagType {
+        NbtTagType::TagCompound(NbtCompound(
+            members
+                .into_iter()
+                .map(|(name, payload)| NbtNamedTag {
+                    name: NbtString(name.to_string()),
+                    payload,
+                })
+                .collect(),
+        ))
+    }
+
+    #[test]
+    fn test_parse_typed_values() {
+        let parsed =
+            parse("{b:1b, s:-2S, i:3, l:4l, f:1.5f, d:2.5d, d2:.5, t:true, n:1e5, str:a.b-c}")
+                .unwrap();
+
+        assert_eq!(
+            parsed,
+            compound(vec![
+                ("b", NbtTagType::TagByte(NbtByte(1))),
+                ("s", NbtTagType::TagShort(NbtShort(-2))),
+                ("i", NbtTagType::TagInt(NbtInt(3))),
+                ("l", NbtTagType::TagLong(NbtLong(4))),
+                ("f", NbtTagType::TagFloat(NbtFloat(1.5))),
+                ("d", NbtTagType::TagDouble(NbtDouble(2.5))),
+                ("d2", NbtTagType::TagDouble(NbtDouble(0.5))),
+                ("t", NbtTagType::TagByte(NbtByte(1))),
+                ("n", NbtTagType::TagString(NbtString("1e5".to_string()))),
+                ("str", NbtTagType::TagString(NbtString("a.b-c".to_string()))),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_arrays_and_lists() {
+        assert_eq!(
+            parse("[B;1b,-2b]").unwrap(),
+            NbtTagType::TagByteArray(NbtByteArray(vec![NbtByte(1), NbtByte(-2)]))
+        );
+        assert_eq!(
+            parse("[I; 1, 2 ,3]").unwrap(),
+            NbtTagType::TagIntArray(NbtIntArray(vec![NbtInt(1), NbtInt(2), NbtInt(3)]))
+        );
+        assert_eq!(
+            parse("[L;1l,2]").unwrap(),
+            NbtTagType::TagLongArray(NbtLongArray(vec![NbtLong(1), NbtLong(2)]))
+        );
+        assert_eq!(
+            parse("[I;]").unwrap(),
+            NbtTagType::TagIntArray(NbtIntArray(vec![]))
+        );
+        assert_eq!(
+            parse("[[],[{}]]").unwrap(),
+            NbtTagType::TagList(NbtList(vec![
+                NbtTagType::TagList(NbtList(vec![])),
+                NbtTagType::TagList(NbtList(vec![compound(vec![])])),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_parse_quoted_keys_and_strings() {
+        let parsed =
+            parse(r#"{"minecraft:id":'it\'s', 'a b':"\"q\"\\\n\u00e9", "":"\x41"}"#).unwrap();
+
+        assert_eq!(
+            parsed,
+            compound(vec![
+                (
+                    "minecraft:id",
+                    NbtTagType::TagString(NbtString("it's".to_string()))
+                ),
+                (
+                    "a b",
+                    NbtTagType::TagString(NbtString("\"q\"\\\né".to_string()))
+                ),
+                ("", NbtTagType::TagString(NbtString("A".to_string()))),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_named_tag() {
+        let tag =
+            NbtNamedTag::try_from(&Snbt::from(r#""hello world":{name:"Bananrama"}"#)).unwrap();
+        assert_eq!(tag.name.0, "hello world");
+
+        let tag = NbtNamedTag::try_from(&Snbt::from("{}")).unwrap();
+        assert_eq!(tag.name.0, "");
+        assert_eq!(tag.payload, compound(vec![]));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let tag = NbtNamedTag {
+            name: NbtString("root".to_string()),
+            payload: compound(vec![
+                ("byte", NbtTagType::TagByte(NbtByte(-1))),
+                ("short", NbtTagType::TagShort(NbtShort(300))),
+                ("int", NbtTagType::TagInt(NbtInt(-70000))),
+                ("long", NbtTagType::TagLong(NbtLong(i64::MAX))),
+                ("float", NbtTagType::TagFloat(NbtFloat(0.25))),
+                ("double", NbtTagType::TagDouble(NbtDouble(-1.0))),
+                (
+                    "bytes",
+                    NbtTagType::TagByteArray(NbtByteArray(vec![NbtByte(1)])),
+                ),
+                ("string", NbtTagType::TagString(NbtString("hi".to_string()))),
+                (
+                    "list",
+                    NbtTagType::TagList(NbtList(vec![NbtTagType::TagInt(NbtInt(1))])),
+                ),
+                ("", compound(vec![])),
+                (
+                    "ints",
+                    NbtTagType::TagIntArray(NbtIntArray(vec![NbtInt(7)])),
+                ),
+                (
+                    "longs",
+                    NbtTagType::TagLongArray(NbtLongArray(vec![NbtLong(8)])),
+                ),
+            ]),
+        };
+
+        let snbt = Snbt::try_from(&tag).unwrap();
+        assert_eq!(NbtNamedTag::try_from(&snbt).unwrap(), tag);
+    }
+
+    #[test]
+    fn test_non_finite_numbers() {
+        for value in [
+            NbtTagType::TagFloat(NbtFloat(f32::NAN)),
+            NbtTagType::TagFloat(NbtFloat(f32::INFINITY)),
+            NbtTagType::TagDouble(NbtDouble(f64::NEG_INFINITY)),
+        ] {
+            let tag = compound(vec![(
+                "pos",
+                NbtTagType::TagList(NbtList(vec![value.clone()])),
+            )]);
+            assert!(matches!(
+                Snbt::try_from(&tag),
+                Err(NbtError::NonFiniteSnbt(_))
+            ));
+            assert!(matches!(
+                SnbtFormat::PRETTY.format_named(&NbtNamedTag {
+                    name: NbtString("root".to_string()),
+                    payload: tag,
+                }),
+                Err(NbtError::NonFiniteSnbt(_))
+            ));
+        }
+
+        assert_eq!(
+            SnbtFormat::COMPACT
+                .format_lossy(&NbtTagType::TagDouble(NbtDouble(f64::NAN)))
+                .0,
+            "NaNd"
+        );
+
+        // The largest finite values still round trip
+        let max = compound(vec![
+            ("f", NbtTagType::TagFloat(NbtFloat(f32::MAX))),
+            ("d", NbtTagType::TagDouble(NbtDouble(f64::MIN))),
+        ]);
+        let snbt = Snbt::try_from(&max).unwrap();
+        assert_eq!(NbtTagType::try_from(&snbt).unwrap(), max);
+    }
+
+    #[test]
+    fn test_escaped_strings() {
+        let strings = [
+            r#"say "hi""#,
+            r"C:\path\",
+            "it's",
+            "both \" and '",
+            "line\nbreak\ttab\r",
+            "unicode é 😀 \u{1}",
+            "",
+        ];
+        let tag = compound(
+            strings
+                .iter()
+                .map(|s| (*s, NbtTagType::TagString(NbtString(s.to_string()))))
+                .collect(),
+        );
+
+        for quote in [SnbtQuote::Double, SnbtQuote::Single, SnbtQuote::Minimal] {
+            for indent in [None, Some(2)] {
+                let format = SnbtFormat {
+                    indent,
+                    sort_keys: false,
+                    quote,
+                };
+                let snbt = format.format(&tag).unwrap();
+                assert_eq!(NbtTagType::try_from(&snbt).unwrap(), tag, "{snbt}");
+            }
+        }
+
+        let snbt = |s: &str, quote| {
+            let format = SnbtFormat {
+                quote,
+                ..SnbtFormat::COMPACT
+            };
+            format
+                .format(&NbtTagType::TagString(NbtString(s.to_string())))
+                .unwrap()
+                .0
+        };
+        assert_eq!(snbt(r#"a"b\c"#, SnbtQuote::Double), r#""a\"b\\c""#);
+        assert_eq!(snbt(r#"a"b'c"#, SnbtQuote::Minimal), r#"'a"b\'c'"#);
+        assert_eq!(snbt("a\nb", SnbtQuote::Single), r"'a\nb'");
+    }
+
+    #[test]
+    fn test_pretty() {
+        let tag = parse(r#"{b:[{"a b":1b}],a:{},l:[L;1l,2l],e:[]}"#).unwrap();
+
+        assert_eq!(
+            SnbtFormat::COMPACT.format(&tag).unwrap().0,
+            r#"{b:[{"a b":1b}],a:{},l:[L;1l,2l],e:[]}"#
+        );
+        let format = SnbtFormat {
+            sort_keys: true,
+            ..SnbtFormat::PRETTY
+        };
+        assert_eq!(
+            format.format(&tag).unwrap().0,
+            r#"{
+    a: {},
+    b: [
+        {
+            "a b": 1b
+        }
+    ],
+    e: [],
+    l: [L; 1l, 2l]
+}"#
+        );
+    }
+
+    #[test]
+    fn test_nesting_depth() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(parse(&nested(512)).is_ok());
+        assert!(matches!(
+            parse(&nested(513)),
+            Err(NbtError::LimitExceeded { max: 512, .. })
+        ));
+
+        // Far deeper than the stack could handle without the limit.
+        assert!(matches!(
+            parse(&"[{a:".repeat(100_000)),
+            Err(NbtError::LimitExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_error_positions() {
+        assert_eq!(error_position("{a:1b,a:2b}"), 6);
+        assert_eq!(error_position("[1,2b]"), 3);
+        assert_eq!(error_position("{a:\"abc"), 3);
+        assert_eq!(error_position("{a:1}x"), 5);
+        assert_eq!(error_position("{a:1 b:2}"), 5);
+        assert_eq!(error_position("[B;1b,300]"), 6);
+        assert_eq!(error_position("{a:'\\q'}"), 4);
+        assert_eq!(error_position("{\"é\":1,"), 7);
+    }
+}
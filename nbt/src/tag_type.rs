@@ -1,6 +1,6 @@
 use std::{
     fmt::{self, Display},
-    io::Read,
+    io::{Read, Write},
 };
 
 use serde::de::{self, Visitor};
@@ -8,6 +8,8 @@ use serde::de::{self, Visitor};
 use crate::{
     NbtResult,
     error::NbtError,
+    flavor::{NbtFlavor, NbtWriter},
+    limits::{NbtLimits, NbtReader},
     nbt_named_tag::NbtNamedTag,
     nbt_types::{
         NbtByte, NbtByteArray, NbtCompound, NbtDouble, NbtFloat, NbtInt, NbtIntArray, NbtList,
@@ -37,22 +39,47 @@ pub enum NbtTagType {
 impl NbtTagType {
     /// Parse an NbtTagType from the provided [r].
     pub fn read<R: Read>(tag_id: u8, r: &mut R) -> NbtResult<Self> {
-        Ok(match tag_id {
-            0 => NbtTagType::TagEnd,
-            1 => NbtTagType::TagByte(NbtByte::read(r)?),
-            2 => NbtTagType::TagShort(NbtShort::read(r)?),
-            3 => NbtTagType::TagInt(NbtInt::read(r)?),
-            4 => NbtTagType::TagLong(NbtLong::read(r)?),
-            5 => NbtTagType::TagFloat(NbtFloat::read(r)?),
-            6 => NbtTagType::TagDouble(NbtDouble::read(r)?),
-            7 => NbtTagType::TagByteArray(NbtByteArray::read(r)?),
-            8 => NbtTagType::TagString(NbtString::read(r)?),
-            9 => NbtTagType::TagList(NbtList::read(r)?),
-            10 => NbtTagType::TagCompound(NbtCompound::read(r)?),
-            11 => NbtTagType::TagIntArray(NbtIntArray::read(r)?),
-            12 => NbtTagType::TagLongArray(NbtLongArray::read(r)?),
-            b => return Err(NbtError::InvalidNbtTag(b)),
-        })
+        Self::read_limited(tag_id, &mut NbtReader::new(r, NbtLimits::default()))
+    }
+
+    /// Parse an NbtTagType from the provided [r], enforcing its limits.
+    pub fn read_limited<R: Read>(tag_id: u8, r: &mut NbtReader<R>) -> NbtResult<Self> {
+        match tag_id {
+            0 => Ok(NbtTagType::TagEnd),
+            1 => NbtByte::read_limited(r).map(NbtTagType::TagByte),
+            2 => NbtShort::read_limited(r).map(NbtTagType::TagShort),
+            3 => NbtInt::read_limited(r).map(NbtTagType::TagInt),
+            4 => NbtLong::read_limited(r).map(NbtTagType::TagLong),
+            5 => NbtFloat::read_limited(r).map(NbtTagType::TagFloat),
+            6 => NbtDouble::read_limited(r).map(NbtTagType::TagDouble),
+            7 => NbtByteArray::read_limited(r).map(NbtTagType::TagByteArray),
+            8 => NbtString::read_limited(r).map(NbtTagType::TagString),
+            9 => NbtList::read_limited(r).map(NbtTagType::TagList),
+            10 => NbtCompound::read_limited(r).map(NbtTagType::TagCompound),
+            11 => NbtIntArray::read_limited(r).map(NbtTagType::TagIntArray),
+            12 => NbtLongArray::read_limited(r).map(NbtTagType::TagLongArray),
+            b => Err(NbtError::InvalidNbtTag(b)),
+        }
+    }
+
+    /// Parse a nameless root tag from the provided [r], i.e. the tag ID followed by the payload.
+    /// This is how NBT is sent over the network since 1.20.2, a TAG_End root means no NBT is present.
+    pub fn read_network<R: Read>(r: &mut R) -> NbtResult<Self> {
+        Self::read_network_limited(&mut NbtReader::new(r, NbtLimits::default()))
+    }
+
+    /// Same as [NbtTagType::read_network] but enforcing the limits of the provided [r], use this for
+    /// NBT received from clients.
+    pub fn read_network_limited<R: Read>(r: &mut NbtReader<R>) -> NbtResult<Self> {
+        let [tag_id] = r.read_bytes()?;
+
+        Self::read_limited(tag_id, r)
+    }
+
+    /// Write this tag as a nameless root tag to the provided [w], see [NbtTagType::read_network].
+    pub fn write_network<W: Write>(&self, w: &mut W) -> NbtResult<()> {
+        w.write_all(&[self.get_tag_id()])?;
+        self.write(w)
     }
 
     /// Get the ID for this tag.
@@ -74,18 +101,48 @@ impl NbtTagType {
         }
     }
 
-    // TODO: Finish
-    // fn write<W: Write>(&self, w: &mut W) -> NbtResult<()> {
-    //     let b: u8 = self.get_tag_id();
-    //     w.write(&[b])?;
+    /// The snake case name of this tag's type, e.g. `long_array`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            NbtTagType::TagEnd => "end",
+            NbtTagType::TagByte(_) => "byte",
+            NbtTagType::TagShort(_) => "short",
+            NbtTagType::TagInt(_) => "int",
+            NbtTagType::TagLong(_) => "long",
+            NbtTagType::TagFloat(_) => "float",
+            NbtTagType::TagDouble(_) => "double",
+            NbtTagType::TagByteArray(_) => "byte_array",
+            NbtTagType::TagString(_) => "string",
+            NbtTagType::TagList(_) => "list",
+            NbtTagType::TagCompound(_) => "compound",
+            NbtTagType::TagIntArray(_) => "int_array",
+            NbtTagType::TagLongArray(_) => "long_array",
+        }
+    }
 
-    //     match self {
-    //         NbtTagType::TagEnd => {}
-    //         t => todo!("Writing of nbttype {t:?} not implemented"),
-    //     }
+    /// Write the payload of this tag to the provided [w], the tag ID is not written.
+    pub fn write<W: Write>(&self, w: &mut W) -> NbtResult<()> {
+        self.write_flavored(&mut NbtWriter::new(w, NbtFlavor::Java))
+    }
 
-    //     Ok(())
-    // }
+    /// Same as [NbtTagType::write] but in the flavor of the provided [w].
+    pub fn write_flavored<W: Write>(&self, w: &mut NbtWriter<W>) -> NbtResult<()> {
+        match self {
+            NbtTagType::TagEnd => Ok(()),
+            NbtTagType::TagByte(nbt_byte) => nbt_byte.write_flavored(w),
+            NbtTagType::TagShort(nbt_short) => nbt_short.write_flavored(w),
+            NbtTagType::TagInt(nbt_int) => nbt_int.write_flavored(w),
+            NbtTagType::TagLong(nbt_long) => nbt_long.write_flavored(w),
+            NbtTagType::TagFloat(nbt_float) => nbt_float.write_flavored(w),
+            NbtTagType::TagDouble(nbt_double) => nbt_double.write_flavored(w),
+            NbtTagType::TagByteArray(nbt_byte_array) => nbt_byte_array.write_flavored(w),
+            NbtTagType::TagString(nbt_string) => nbt_string.write_flavored(w),
+            NbtTagType::TagList(nbt_list) => nbt_list.write_flavored(w),
+            NbtTagType::TagCompound(nbt_compound) => nbt_compound.write_flavored(w),
+            NbtTagType::TagIntArray(nbt_int_array) => nbt_int_array.write_flavored(w),
+            NbtTagType::TagLongArray(nbt_long_array) => nbt_long_array.write_flavored(w),
+        }
+    }
 }
 
 struct NbtTagTypeVisitor;
@@ -177,42 +234,39 @@ impl<'de> serde::Deserialize<'de> for NbtTagType {
 
 impl Display for NbtTagType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&format!(
-            "{}",
-            match self {
-                NbtTagType::TagEnd => "".to_string(),
-                NbtTagType::TagByte(nbt_byte) => nbt_byte.0.to_string(),
-                NbtTagType::TagShort(nbt_short) => nbt_short.0.to_string(),
-                NbtTagType::TagInt(nbt_int) => nbt_int.0.to_string(),
-                NbtTagType::TagLong(nbt_long) => nbt_long.0.to_string(),
-                NbtTagType::TagFloat(nbt_float) => nbt_float.0.to_string(),
-                NbtTagType::TagDouble(nbt_double) => nbt_double.0.to_string(),
-                NbtTagType::TagByteArray(nbt_byte_array) => {
-                    let s: Vec<String> = nbt_byte_array.0.iter().map(|b| b.0.to_string()).collect();
-                    format!("[{}]", s.join(", "))
-                }
-                NbtTagType::TagString(nbt_string) => nbt_string.0.clone(),
-                NbtTagType::TagList(nbt_list) => {
-                    let s: Vec<String> = nbt_list.0.iter().map(|b| b.to_string()).collect();
-                    format!("[{}]", s.join(", "))
-                }
-                NbtTagType::TagCompound(nbt_compound) => {
-                    let s: Vec<String> = nbt_compound
-                        .0
-                        .iter()
-                        .map(|tag| format!("{}: {}", tag.name.0.clone(), tag.payload.to_string()))
-                        .collect();
-                    format!("{{{}}}", s.join(", "))
-                }
-                NbtTagType::TagIntArray(nbt_int_array) => {
-                    let s: Vec<String> = nbt_int_array.0.iter().map(|b| b.0.to_string()).collect();
-                    format!("[{}]", s.join(", "))
-                }
-                NbtTagType::TagLongArray(nbt_long_array) => {
-                    let s: Vec<String> = nbt_long_array.0.iter().map(|b| b.0.to_string()).collect();
-                    format!("[{}]", s.join(", "))
-                }
+        f.write_str(&match self {
+            NbtTagType::TagEnd => "".to_string(),
+            NbtTagType::TagByte(nbt_byte) => nbt_byte.0.to_string(),
+            NbtTagType::TagShort(nbt_short) => nbt_short.0.to_string(),
+            NbtTagType::TagInt(nbt_int) => nbt_int.0.to_string(),
+            NbtTagType::TagLong(nbt_long) => nbt_long.0.to_string(),
+            NbtTagType::TagFloat(nbt_float) => nbt_float.0.to_string(),
+            NbtTagType::TagDouble(nbt_double) => nbt_double.0.to_string(),
+            NbtTagType::TagByteArray(nbt_byte_array) => {
+                let s: Vec<String> = nbt_byte_array.0.iter().map(|b| b.0.to_string()).collect();
+                format!("[{}]", s.join(", "))
             }
-        ))
+            NbtTagType::TagString(nbt_string) => nbt_string.0.clone(),
+            NbtTagType::TagList(nbt_list) => {
+                let s: Vec<String> = nbt_list.0.iter().map(|b| b.to_string()).collect();
+                format!("[{}]", s.join(", "))
+            }
+            NbtTagType::TagCompound(nbt_compound) => {
+                let s: Vec<String> = nbt_compound
+                    .0
+                    .iter()
+                    .map(|tag| format!("{}: {}", tag.name.0, tag.payload))
+                    .collect();
+                format!("{{{}}}", s.join(", "))
+            }
+            NbtTagType::TagIntArray(nbt_int_array) => {
+                let s: Vec<String> = nbt_int_array.0.iter().map(|b| b.0.to_string()).collect();
+                format!("[{}]", s.join(", "))
+            }
+            NbtTagType::TagLongArray(nbt_long_array) => {
+                let s: Vec<String> = nbt_long_array.0.iter().map(|b| b.0.to_string()).collect();
+                format!("[{}]", s.join(", "))
+            }
+        })
     }
 }
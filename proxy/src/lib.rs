@@ -3,48 +3,26 @@
 
 //! Crate for handling proxying to another Minecraft server.
 
-use std::io::{self, Cursor};
+use std::{env, fs, io, path::PathBuf};
 
 use log::{error, info, warn};
 use mc_coms::{
     client_state::ClientState,
-    codec::{json_string::JsonString, prefixed_array::PrefixedArray, var_int::VarInt},
+    codec::prefixed_array::PrefixedArray,
     key_store::{EncryptionError, KeyStore},
     messages::{
-        McPacketError, McPacketRead,
-        clientbound::{
-            configuration::{
-                clientbound_keep_alive::ClientboundKeepAlive,
-                clientbound_known_packs::ClientboundKnownPacks,
-                clientbound_plugin_message::ClientboundPluginMessage, feature_flags::FeatureFlags,
-                registry_data::RegistryData, update_tags::UpdateTags,
-            },
-            login::encryption_request::EncryptionRequest,
-            play::{
-                change_difficulty::ChangeDifficulty, login::Login,
-                player_abilities::PlayerAbilities, set_held_item::SetHeldItem,
-                update_recipes::UpdateRecipes,
-            },
-            status::{pong_response::PongResponse, status_response::ServerStatus},
-        },
-        serverbound::{
-            configuration::{
-                client_information::ClientInformation,
-                serverbound_known_packs::ServerboundKnownPacks,
-                serverbound_plugin_message::ServerboundPluginMessage,
-            },
-            handshaking::handshake::Handshake,
-            login::{encryption_response::EncryptionResponse, login_start::LoginStart},
-            status::ping_request::PingRequest,
-        },
+        McPacketError,
+        clientbound::login::encryption_request::EncryptionRequest,
+        registry::{ClientboundPacket, PacketDirection, ServerboundPacket, lookup},
+        serverbound::login::encryption_response::EncryptionResponse,
     },
-    packet_reader::{NetworkReader, PacketReadError, RawPacket},
+    packet_reader::{NetworkReader, PacketLimits, PacketReadError, RawPacket},
     packet_writer::{NetworkWriter, PacketWriteError},
-    ser::{NetworkWriteExt, ReadingError, WritingError},
+    protocol_version::ProtocolVersion,
+    ser::{ReadingError, WritingError},
 };
 use owo_colors::OwoColorize;
 use rand::Rng;
-use serde::Deserialize;
 use tokio::{
     io::{BufReader, BufWriter},
     net::{
@@ -87,9 +65,14 @@ pub struct ProxyHandler<'key> {
     server_writer: NetworkWriter<BufWriter<OwnedWriteHalf>>,
     key_store: &'key KeyStore,
     state: ClientState,
+    protocol_version: ProtocolVersion,
     handling_packet: bool,
+    capture_dir: Option<PathBuf>,
 }
 
+/// Directory to save the data of the first packet of every kind to, see [ProxyHandler::capture].
+const CAPTURE_DIR_ENV: &str = "MC_PROXY_CAPTURE_DIR";
+
 impl<'key> ProxyHandler<'key> {
     /// Creates a [ProxyHandler] from the provided [stream].
     pub async fn new(
@@ -107,7 +90,8 @@ impl<'key> ProxyHandler<'key> {
         info!("Connection setup to {target}");
 
         let (server_reader, server_writer) = out_stream.into_split();
-        let server_reader = NetworkReader::new(BufReader::new(server_reader));
+        let server_reader =
+            NetworkReader::with_limits(BufReader::new(server_reader), PacketLimits::clientbound());
         let server_writer = NetworkWriter::new(BufWriter::new(server_writer));
 
         Ok(ProxyHandler {
@@ -117,7 +101,9 @@ impl<'key> ProxyHandler<'key> {
             server_writer,
             key_store,
             state: ClientState::Handshaking,
+            protocol_version: ProtocolVersion::LATEST,
             handling_packet: false,
+            capture_dir: env::var_os(CAPTURE_DIR_ENV).map(PathBuf::from),
         })
     }
 
@@ -138,6 +124,7 @@ impl<'key> ProxyHandler<'key> {
 
                     self.log_server_bound(packet.id, format!("Packet to server {packet:02x?} (total read {} bytes)", self.client_reader.get_total_read()).green().to_string().as_str());
                     self.handling_packet = true;
+                    self.capture(PacketDirection::Serverbound, &packet);
 
                     match self.parse_and_log_server_bound_packet(packet.clone()).await {
                         Ok(true) => { /* The server has been dealt with */ }
@@ -170,6 +157,7 @@ impl<'key> ProxyHandler<'key> {
                     };
                     self.log_client_bound(packet.id, format!("Packet to client, {} (total read {} bytes)", data_to_print, self.server_reader.get_total_read()).bright_blue().to_string().as_str());
                     self.handling_packet = true;
+                    self.capture(PacketDirection::Clientbound, &packet);
 
                     match self.parse_and_log_client_bound_packet(packet.clone()).await {
                         Ok(true) => { /* The client has been dealt with */ }
@@ -186,14 +174,56 @@ impl<'key> ProxyHandler<'key> {
                 }
             }
             self.handling_packet = false;
+
+            self.client_writer.flush().await?;
+            self.server_writer.flush().await?;
+        }
+    }
+
+    /// Save the data of the first packet of every kind to `<MC_PROXY_CAPTURE_DIR>/<direction>/<name>.bin`.
+    /// Copied to `coms/captures`, the captures are round-trip tested against the codec of every packet.
+    fn capture(&self, direction: PacketDirection, packet: &RawPacket) {
+        let Some(capture_dir) = &self.capture_dir else {
+            return;
+        };
+        // The captures are tested with the latest packet IDs.
+        if self.protocol_version != ProtocolVersion::LATEST {
+            return;
         }
+        let Some(entry) = lookup(self.state, direction, packet.id) else {
+            return;
+        };
+
+        let dir = capture_dir.join(match direction {
+            PacketDirection::Serverbound => "serverbound",
+            PacketDirection::Clientbound => "clientbound",
+        });
+        let path = dir.join(format!("{}.bin", entry.name));
+        if path.exists() {
+            return;
+        }
+        match fs::create_dir_all(&dir).and_then(|_| fs::write(&path, &packet.data)) {
+            Ok(()) => info!("Captured {} to {}", entry.name, path.display()),
+            Err(err) => warn!(
+                "Failed to capture {} to {}, err: {err}",
+                entry.name,
+                path.display()
+            ),
+        }
+    }
+
+    /// Move both sides of the connection to [state].
+    fn set_state(&mut self, state: ClientState) {
+        self.state = state;
+        self.client_reader.set_state(state);
+        self.server_reader.set_state(state);
     }
 
     async fn parse_and_log_server_bound_packet(
         &mut self,
         packet: RawPacket,
     ) -> Result<bool, ProxyError> {
-        let packet_id = packet.id.clone();
+        let packet_id = packet.id;
 
         self.log_server_bound(
             packet_id,
@@ -202,12 +232,31 @@ impl<'key> ProxyHandler<'key> {
                 packet_id, self.state,
             ),
         );
-        match (&self.state, packet_id) {
-            (&ClientState::Handshaking, 0) => {
+        let packet =
+            match ServerboundPacket::decode_versioned(self.protocol_version, self.state, packet) {
+                Ok(packet) => packet,
+                Err(McPacketError::UnknownPacket { state, id, .. }) => {
+                    warn!(
+                        "Unsupported packet ID ({id}) for state {state:?} in server-bound packets"
+                    );
+                    return Ok(false);
+                }
+                Err(err) => return Err(err.into()),
+            };
+
+        match packet {
+            ServerboundPacket::Handshake(handshake) => {
                 self.log_server_bound(packet_id, "Server-bound packet is handshake");
-                let handshake = Handshake::deserialize(&mut packet.get_deserializer())?;
                 self.log_server_bound(packet_id, &format!("Handshake packet: {handshake:?}"));
 
+                match ProtocolVersion::from_protocol(handshake.protocol_version.0) {
+                    Some(version) => self.protocol_version = version,
+                    None => warn!(
+                        "Unsupported protocol version {}, parsing packets as {}",
+                        handshake.protocol_version.0, self.protocol_version
+                    ),
+                }
+
                 let new_state = match handshake.intent.0 {
                     1 => ClientState::Status,
                     2 => ClientState::Login,
@@ -217,30 +266,26 @@ impl<'key> ProxyHandler<'key> {
                     }
                 };
                 self.log_server_bound(packet_id, &format!("Setting state to {new_state}"));
-                self.state = new_state;
+                self.set_state(new_state);
             }
-            (&ClientState::Status, 0) => {
+            ServerboundPacket::StatusRequest(_) => {
                 self.log_server_bound(packet_id, "Status request");
             }
-            (&ClientState::Status, 0x1) => {
+            ServerboundPacket::PingRequest(ping_request) => {
                 self.log_server_bound(packet_id, "Ping request");
-                let ping_request = PingRequest::deserialize(&mut packet.get_deserializer())?;
                 self.log_server_bound(
                     packet_id,
                     &format!("Ping request content: {ping_request:?}"),
                 );
             }
-            (&ClientState::Login, 0x0) => {
+            ServerboundPacket::LoginStart(login_start) => {
                 self.log_server_bound(packet_id, "Login start");
-                let login_start = LoginStart::deserialize(&mut packet.get_deserializer())?;
                 self.log_server_bound(packet_id, &format!("Login start: {login_start:?}"));
             }
-            (&ClientState::Login, 0x1) => {
+            ServerboundPacket::EncryptionResponse(encryption_response) => {
                 self.log_server_bound(packet_id, "Encryption response");
 
                 // Finalize client encryption
-                let encryption_response =
-                    EncryptionResponse::deserialize(&mut packet.get_deserializer())?;
 
                 let shared_secret = self
                     .key_store
@@ -267,38 +312,34 @@ impl<'key> ProxyHandler<'key> {
 
                 return Ok(true);
             }
-            (&ClientState::Login, 0x3) => {
+            ServerboundPacket::LoginAcknowledged(_) => {
                 self.log_server_bound(packet_id, "Login Acknowledged");
                 self.log_server_bound(
                     packet_id,
                     &format!("Setting state to {}", ClientState::Configuration),
                 );
-                self.state = ClientState::Configuration;
+                self.set_state(ClientState::Configuration);
             }
-            (&ClientState::Configuration, 0x0) => {
+            ServerboundPacket::ClientInformation(client_info) => {
                 self.log_server_bound(packet_id, "Client information");
-                let client_info = ClientInformation::deserialize(&mut packet.get_deserializer())?;
                 self.log_server_bound(packet_id, &format!("Client info: {client_info:?}"));
             }
-            (&ClientState::Configuration, 0x2) => {
+            ServerboundPacket::ServerboundPluginMessage(plugin_message) => {
                 self.log_server_bound(packet_id, "Plugin message");
-                let plugin_message = ServerboundPluginMessage::read(packet)?;
 
                 self.log_server_bound(packet_id, &format!("Plugin message: {plugin_message:?}"));
             }
-            (&ClientState::Configuration, 0x3) => {
+            ServerboundPacket::AcknowledgeFinishConfiguration(_) => {
                 self.log_server_bound(packet_id, "Finish configuration");
                 self.log_server_bound(packet_id, "Transitioning to state Play");
-                self.state = ClientState::Play;
+                self.set_state(ClientState::Play);
             }
-            (&ClientState::Configuration, 0x7) => {
+            ServerboundPacket::ServerboundKnownPacks(known_packs) => {
                 self.log_server_bound(packet_id, "Serverbound known packs");
-                let known_packs =
-                    ServerboundKnownPacks::deserialize(&mut packet.get_deserializer())?;
                 self.log_server_bound(packet_id, &format!("Client supports: {known_packs:?}"));
             }
-            (state, id) => {
-                warn!("Unsupported packet ID ({id}) for state {state:?} in server-bound packets");
+            packet => {
+                self.log_server_bound(packet_id, &format!("{} (not inspected)", packet.name()));
             }
         }
 
@@ -309,7 +350,7 @@ impl<'key> ProxyHandler<'key> {
         &mut self,
         packet: RawPacket,
     ) -> Result<bool, ProxyError> {
-        let packet_id = packet.id.clone();
+        let packet_id = packet.id;
 
         self.log_client_bound(
             packet_id,
@@ -318,35 +359,42 @@ impl<'key> ProxyHandler<'key> {
                 packet_id, self.state,
             ),
         );
-        match (&self.state, packet_id) {
-            (&ClientState::Status, 0) => {
+        let packet = match ClientboundPacket::decode_versioned(
+            self.protocol_version,
+            self.state,
+            packet,
+        ) {
+            Ok(packet) => packet,
+            Err(McPacketError::UnknownPacket { state, id, .. }) => {
+                warn!(
+                    "Unsupported packet ID ({id:02x}) for state {state:?} in client-bound packets"
+                );
+                return Ok(false);
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        match packet {
+            ClientboundPacket::StatusResponse(status_response) => {
                 self.log_client_bound(packet_id, "Status response");
-                let server_status =
-                    JsonString::<ServerStatus, 27512>::deserialize(&mut packet.get_deserializer())?;
-                let server_status = server_status.into_inner();
-                self.log_client_bound(packet_id, &format!("Server status: {server_status:?}"));
+                self.log_client_bound(packet_id, &format!("Server status: {status_response:?}"));
             }
-            (&ClientState::Status, 0x01) => {
+            ClientboundPacket::PongResponse(pong_response) => {
                 self.log_client_bound(packet_id, "Pong response");
-                let pong_response = PongResponse::deserialize(&mut packet.get_deserializer())?;
                 self.log_client_bound(
                     packet_id,
                     &format!("Pong response content: {pong_response:?}"),
                 );
             }
-            (&ClientState::Login, 0x0) => {
+            ClientboundPacket::Disconnect(disconnect) => {
                 self.log_client_bound(packet_id, "Login Disconnect");
-                let message = String::deserialize(&mut packet.get_deserializer())?;
-                self.log_client_bound(packet_id, &format!("Disconnect reason: {message}"));
+                self.log_client_bound(packet_id, &format!("Disconnect: {disconnect:?}"));
             }
-            (&ClientState::Login, 0x01) => {
+            ClientboundPacket::EncryptionRequest(incoming_encryption_request) => {
                 self.log_client_bound(packet_id, "Encryption request");
 
                 // Since we want to be a middle-man we need to handle this request ourselves whilst also sending a request of our own to the client.
 
-                let incoming_encryption_request =
-                    EncryptionRequest::deserialize(&mut packet.get_deserializer())?;
-
                 let mut rng = rand::thread_rng();
                 let secret: [u8; 16] = rng.r#gen();
 
@@ -390,47 +438,49 @@ impl<'key> ProxyHandler<'key> {
 
                 return Ok(true);
             }
-            (&ClientState::Login, 0x2) => {
+            ClientboundPacket::LoginSuccess(_) => {
                 self.log_client_bound(packet_id, "Login successful");
             }
-            (&ClientState::Login, 0x3) => {
+            ClientboundPacket::SetCompression(set_compression) => {
                 self.log_client_bound(packet_id, "Enable compression");
-                todo!("Compression is not yet supported")
-            }
-            (&ClientState::Configuration, 0x0) => {
-                self.log_client_bound(packet_id, "Cookie request");
-                let key = String::deserialize(&mut packet.get_deserializer())?;
-                self.log_client_bound(packet_id, &format!("Cookie request key: \"{key}\""));
+                let threshold = set_compression.threshold.0;
+
+                // Everything after this packet is compressed, so the server side must switch right away
+                // whilst the client has to receive the packet itself uncompressed first.
+                self.server_reader.enable_compression(threshold);
+                self.server_writer.enable_compression(threshold);
+
+                self.client_writer.write_packet(set_compression).await?;
+
+                self.client_reader.enable_compression(threshold);
+                self.client_writer.enable_compression(threshold);
+
+                return Ok(true);
             }
-            (&ClientState::Configuration, 0x1) => {
+            ClientboundPacket::ClientboundPluginMessage(clientbound_plugin_message) => {
                 self.log_client_bound(packet_id, "Clientbound Plugin Message");
-                let clientbound_plugin_message = ClientboundPluginMessage::read(packet)?;
                 self.log_client_bound(
                     packet_id,
                     &format!("Plugin message: {clientbound_plugin_message:?}"),
                 );
             }
-            (&ClientState::Configuration, 0x3) => {
+            ClientboundPacket::FinishConfiguration(_) => {
                 self.log_client_bound(packet_id, "Finish configuration");
             }
-            (&ClientState::Configuration, 0x4) => {
+            ClientboundPacket::ClientboundKeepAlive(keep_alive) => {
                 self.log_client_bound(packet_id, "Clientbound keep alive (configuration)");
-                let keep_alive = ClientboundKeepAlive::deserialize(&mut packet.get_deserializer())?;
                 self.log_client_bound(packet_id, &format!("Keep alive request {keep_alive:?}"));
             }
-            (&ClientState::Configuration, 0x7) => {
+            ClientboundPacket::RegistryData(registry_data) => {
                 self.log_client_bound(packet_id, "Registry data packet");
-                let registry_data = RegistryData::deserialize(&mut packet.get_deserializer())?;
                 self.log_client_bound(packet_id, &format!("Registry data: {registry_data:?}"));
             }
-            (&ClientState::Configuration, 0xC) => {
+            ClientboundPacket::FeatureFlags(feature_flags) => {
                 self.log_client_bound(packet_id, "Feature flags");
-                let feature_flags = FeatureFlags::deserialize(&mut packet.get_deserializer())?;
                 self.log_client_bound(packet_id, &format!("Feature flags: {feature_flags:?}"));
             }
-            (&ClientState::Configuration, 0xD) => {
+            ClientboundPacket::UpdateTags(update_tags) => {
                 self.log_client_bound(packet_id, "Update tags");
-                let update_tags = UpdateTags::deserialize(&mut packet.get_deserializer())?;
                 self.log_client_bound(
                     packet_id,
                     &format!(
@@ -445,46 +495,37 @@ impl<'key> ProxyHandler<'key> {
                     ),
                 );
             }
-            (&ClientState::Configuration, 0xE) => {
+            ClientboundPacket::ClientboundKnownPacks(known_packs) => {
                 self.log_client_bound(packet_id, "Clientbound known packs");
-                let known_packs =
-                    ClientboundKnownPacks::deserialize(&mut packet.get_deserializer())?;
                 self.log_client_bound(packet_id, &format!("Server packs: {known_packs:?}"));
             }
-            (&ClientState::Play, 0x0a) => {
+            ClientboundPacket::ChangeDifficulty(change_difficulty) => {
                 self.log_client_bound(packet_id, "Change difficulty");
-                let change_difficulty =
-                    ChangeDifficulty::deserialize(&mut packet.get_deserializer())?;
                 self.log_client_bound(
                     packet_id,
                     &format!("Change difficulty packet: {change_difficulty:?}"),
                 );
             }
-            (&ClientState::Play, 0x30) => {
+            ClientboundPacket::Login(login) => {
                 self.log_client_bound(packet_id, "Login (play)");
-                let login = Login::deserialize(&mut packet.get_deserializer())?;
                 self.log_client_bound(packet_id, &format!("Login packet: {login:?}"));
             }
-            (&ClientState::Play, 0x3E) => {
+            ClientboundPacket::PlayerAbilities(player_abilities) => {
                 self.log_client_bound(packet_id, "Player abilities");
-                let player_abilities =
-                    PlayerAbilities::deserialize(&mut packet.get_deserializer())?;
                 self.log_client_bound(
                     packet_id,
                     &format!("Player abilities: {player_abilities:?}"),
                 );
             }
-            (&ClientState::Play, 0x67) => {
+            ClientboundPacket::SetHeldItem(set_held_item) => {
                 self.log_client_bound(packet_id, "Set held item");
-                let set_held_item = SetHeldItem::deserialize(&mut packet.get_deserializer())?;
                 self.log_client_bound(
                     packet_id,
                     &format!("Set held item packet: {set_held_item:?}"),
                 );
             }
-            (&ClientState::Play, 0x83) => {
+            ClientboundPacket::UpdateRecipes(update_recipes) => {
                 self.log_client_bound(packet_id, "Update recipes");
-                let update_recipes = UpdateRecipes::deserialize(&mut packet.get_deserializer())?;
                 self.log_client_bound(
                     packet_id,
                     &format!(
@@ -494,10 +535,8 @@ impl<'key> ProxyHandler<'key> {
                     ),
                 );
             }
-            (state, id) => {
-                warn!(
-                    "Unsupported packet ID ({id:02x}) for state {state:?} in client-bound packets"
-                );
+            packet => {
+                self.log_client_bound(packet_id, &format!("{} (not inspected)", packet.name()));
             }
         }
 
@@ -527,27 +566,11 @@ impl<'key> ProxyHandler<'key> {
     }
 }
 
-// TODO: Unnecessary allocations, should probably just implement AsyncWrite for NetworkWriter but who can be arsed?
 async fn send_raw_packet(
     writer: &mut NetworkWriter<BufWriter<OwnedWriteHalf>>,
     packet: &RawPacket,
 ) -> Result<(), ProxyError> {
-    let mut buffer = Vec::new();
-
-    let id = VarInt::from(packet.id);
-    buffer.write_var_int(&id)?;
-    io::copy(&mut Cursor::new(packet.data.clone()), &mut buffer)?;
-
-    let total_length: VarInt = buffer.len().try_into().map_err(|err| {
-        error!("Packet length received was too large? error: {err:?}");
-        ProxyError::InvalidPacket
-    })?;
-
-    let mut final_buffer = Vec::new();
-    total_length.encode(&mut final_buffer)?;
-    io::copy(&mut Cursor::new(buffer), &mut final_buffer)?;
-
-    writer.write_data(final_buffer).await?;
+    writer.write_raw_packet(packet.id, &packet.data).await?;
 
     Ok(())
 }
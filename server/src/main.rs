@@ -57,7 +57,7 @@ async fn handle_connection(stream: TcpStream, key_store: &KeyStore) -> eyre::Res
 async fn handle_connection(stream: TcpStream, key_store: &KeyStore) -> eyre::Result<()> {
     use client_handler::client_handler::ClientHandler;
 
-    let mut handler = ClientHandler::new(stream, &key_store);
+    let mut handler = ClientHandler::new(stream, key_store);
 
     handler
         .run()
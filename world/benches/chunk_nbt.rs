@@ -0,0 +1,211 @@
+//! Compares the tree based (NbtTagType) and the streaming NBT serde implementations on the chunks of a
+//! real region file. Run with `cargo bench -p world`, the region file defaults to the 8 chunks in
+//! `benches/data/r.0.0.mca` and can be changed with the `REGION_FILE` env variable.
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    hint::black_box,
+    io::{Cursor, Read},
+    path::{Path, PathBuf},
+};
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use flate2::read::ZlibDecoder;
+use nbt::{
+    nbt_named_tag::NbtNamedTag,
+    nbt_types::NbtString,
+    ser::{
+        deserializer::Deserializer, serializer::to_nbt_tag_type, slice_deserializer::from_slice,
+        stream_deserializer::from_reader, stream_serializer::to_writer,
+    },
+};
+use serde::{Deserialize, Serialize};
+use world::save::chunk_data::{ChunkBlockStates, ChunkData};
+
+const SECTOR_SIZE: usize = 4096;
+
+/// The parts of a chunk that can be both serialized & deserialized, used for the serialization benchmarks.
+#[derive(Serialize, Deserialize)]
+struct BenchChunk {
+    #[serde(rename = "DataVersion")]
+    data_version: i32,
+    #[serde(rename = "xPos")]
+    x_pos: i32,
+    #[serde(rename = "yPos")]
+    y_pos: i32,
+    #[serde(rename = "zPos")]
+    z_pos: i32,
+    #[serde(rename = "Status")]
+    status: String,
+    #[serde(rename = "LastUpdate")]
+    last_update: i64,
+    #[serde(rename = "InhabitedTime")]
+    inhabited_time: i64,
+    sections: Vec<BenchSection>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BenchSection {
+    #[serde(rename = "Y")]
+    y: i8,
+    block_states: BenchPalette<BenchBlock>,
+    biomes: BenchPalette<String>,
+    #[serde(rename = "BlockLight")]
+    block_light: Option<Vec<i8>>,
+    #[serde(rename = "SkyLight")]
+    sky_light: Option<Vec<i8>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BenchPalette<T> {
+    palette: Vec<T>,
+    data: Option<Vec<i64>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct BenchBlock {
+    name: String,
+    properties: Option<BTreeMap<String, String>>,
+}
+
+/// Owned counterpart of [ChunkBlockStates], copying the block state data.
+#[derive(Deserialize)]
+struct OwnedChunkBlockStates {
+    sections: Vec<OwnedSectionBlockStates>,
+}
+
+#[derive(Deserialize)]
+struct OwnedSectionBlockStates {
+    #[serde(rename = "Y")]
+    _y: i8,
+    block_states: BenchPalette<BenchBlock>,
+}
+
+/// Returns the uncompressed NBT of all zlib compressed (the vanilla default) chunks in the region file.
+fn read_region_chunks(path: &Path) -> Vec<Vec<u8>> {
+    let data = fs::read(path)
+        .unwrap_or_else(|err| panic!("Failed to read region file {}: {err}", path.display()));
+
+    (0..1024)
+        .filter_map(|i| {
+            let location = &data[i * 4..i * 4 + 4];
+            let offset = u32::from_be_bytes([0, location[0], location[1], location[2]]) as usize;
+            if offset == 0 {
+                return None;
+            }
+
+            let start = offset * SECTOR_SIZE;
+            let length = u32::from_be_bytes(data[start..start + 4].try_into().unwrap()) as usize;
+            if data[start + 4] != 2 {
+                return None;
+            }
+
+            let mut chunk = Vec::new();
+            ZlibDecoder::new(&data[start + 5..start + 4 + length])
+                .read_to_end(&mut chunk)
+                .ok()?;
+            Some(chunk)
+        })
+        .collect()
+}
+
+fn chunk_benchmarks(c: &mut Criterion) {
+    let path = env::var_os("REGION_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("benches/data/r.0.0.mca"));
+
+    let chunks = read_region_chunks(&path);
+    assert!(!chunks.is_empty(), "No chunks in {}", path.display());
+    let chunk_bytes: usize = chunks.iter().map(Vec::len).sum();
+
+    let mut group = c.benchmark_group("chunk_deserialize");
+    group.throughput(Throughput::Bytes(chunk_bytes as u64));
+    group.bench_function("tree", |b| {
+        b.iter(|| {
+            for chunk in &chunks {
+                let tag = NbtNamedTag::read(&mut Cursor::new(chunk)).unwrap().unwrap();
+                black_box(ChunkData::deserialize(Deserializer::from_nbt_tag(tag.payload)).unwrap());
+            }
+        })
+    });
+    group.bench_function("stream", |b| {
+        b.iter(|| {
+            for chunk in &chunks {
+                black_box(from_reader::<_, ChunkData>(&mut Cursor::new(chunk)).unwrap());
+            }
+        })
+    });
+    group.bench_function("slice", |b| {
+        b.iter(|| {
+            for chunk in &chunks {
+                black_box(from_slice::<ChunkData>(chunk).unwrap());
+            }
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("chunk_block_states");
+    group.throughput(Throughput::Bytes(chunk_bytes as u64));
+    group.bench_function("owned", |b| {
+        b.iter(|| {
+            for chunk in &chunks {
+                let states = from_slice::<OwnedChunkBlockStates>(chunk).unwrap();
+                let data = states
+                    .sections
+                    .iter()
+                    .filter_map(|s| s.block_states.data.as_ref());
+                black_box(data.map(|d| d[0]).sum::<i64>());
+            }
+        })
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| {
+            for chunk in &chunks {
+                let states = ChunkBlockStates::from_slice(chunk).unwrap();
+                let data = states.sections.iter().filter_map(|s| s.block_states.data);
+                black_box(data.map(|d| d.get(0).unwrap()).sum::<i64>());
+            }
+        })
+    });
+    group.finish();
+
+    let bench_chunks: Vec<BenchChunk> = chunks
+        .iter()
+        .map(|chunk| from_reader(&mut Cursor::new(chunk)).unwrap())
+        .collect();
+    let mut buffer = Vec::with_capacity(chunk_bytes);
+    for chunk in &bench_chunks {
+        to_writer(&mut buffer, chunk).unwrap();
+    }
+
+    let mut group = c.benchmark_group("chunk_serialize");
+    group.throughput(Throughput::Bytes(buffer.len() as u64));
+    group.bench_function("tree", |b| {
+        b.iter(|| {
+            buffer.clear();
+            for chunk in &bench_chunks {
+                let tag = NbtNamedTag {
+                    name: NbtString(String::new()),
+                    payload: to_nbt_tag_type(chunk).unwrap().unwrap(),
+                };
+                tag.write(&mut buffer).unwrap();
+            }
+            black_box(&buffer);
+        })
+    });
+    group.bench_function("stream", |b| {
+        b.iter(|| {
+            buffer.clear();
+            for chunk in &bench_chunks {
+                to_writer(&mut buffer, chunk).unwrap();
+            }
+            black_box(&buffer);
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, chunk_benchmarks);
+criterion_main!(benches);
@@ -137,8 +137,44 @@ impl RegionParser {
 
     // TODO: Avoid potential panics when reading data (i.e. not directly indexing into it...).
     fn read_chunk_at(&self, x: usize, z: usize) -> AnvilResult<Option<ChunkMetadata>> {
-        let loc_index = 4 * (x % 32) + ((z % 32) * 32);
-        let ts_index = loc_index + SECTOR_SIZE;
+        let Some((offset, sector_count)) = self.chunk_location(x, z) else {
+            return Ok(None);
+        };
+
+        let ts_index = Self::location_index(x, z) + SECTOR_SIZE;
+        let timestamp = u32::from_be_bytes([
+            self.data[ts_index],
+            self.data[ts_index + 1],
+            self.data[ts_index + 2],
+            self.data[ts_index + 3],
+        ]);
+
+        info!(
+            "Chunk at {x} {z} has offset {offset}, was last modified at {timestamp} and contains {sector_count} sectors"
+        );
+        let mut chunk_datas = vec![];
+        for chunk_buf in self.read_chunk_nbt(offset, sector_count)? {
+            let chunk_data = ChunkData::read(&mut Cursor::new(chunk_buf))?;
+            chunk_datas.push(chunk_data);
+        }
+
+        Ok(Some(ChunkMetadata {
+            x,
+            z,
+            offset,
+            sector_count,
+            timestamp,
+            sectors: chunk_datas,
+        }))
+    }
+
+    fn location_index(x: usize, z: usize) -> usize {
+        4 * (x % 32) + ((z % 32) * 32)
+    }
+
+    /// The offset & sector count of the chunk at [x] [z], `None` if it hasn't been generated.
+    fn chunk_location(&self, x: usize, z: usize) -> Option<(usize, u8)> {
+        let loc_index = Self::location_index(x, z);
 
         let offset = i32::from_be_bytes([
             0,
@@ -149,21 +185,15 @@ impl RegionParser {
         let sector_count = self.data[loc_index + 3];
 
         if offset == 0 && sector_count == 0 {
-            return Ok(None);
+            return None;
         }
 
-        let timestamp = u32::from_be_bytes([
-            self.data[ts_index],
-            self.data[ts_index + 1],
-            self.data[ts_index + 2],
-            self.data[ts_index + 3],
-        ]);
-
-        let mut chunk_datas = vec![];
+        Some((offset, sector_count))
+    }
 
-        info!(
-            "Chunk at {x} {z} has offset {offset}, was last modified at {timestamp} and contains {sector_count} sectors"
-        );
+    /// Read the decompressed, still serialized NBT of every sector of a chunk.
+    fn read_chunk_nbt(&self, offset: usize, sector_count: u8) -> AnvilResult<Vec<Vec<u8>>> {
+        let mut chunk_bufs = vec![];
         for sector in 0..sector_count {
             let sector_start = offset * SECTOR_SIZE;
             let sector_data_length = i32::from_be_bytes([
@@ -205,18 +235,10 @@ impl RegionParser {
                 }
             }
 
-            let chunk_data = ChunkData::read(&mut Cursor::new(chunk_buf))?;
-            chunk_datas.push(chunk_data);
+            chunk_bufs.push(chunk_buf);
         }
 
-        Ok(Some(ChunkMetadata {
-            x,
-            z,
-            offset,
-            sector_count,
-            timestamp,
-            sectors: chunk_datas,
-        }))
+        Ok(chunk_bufs)
     }
 }
 
@@ -233,9 +255,14 @@ struct ChunkMetadata {
 
 #[cfg(test)]
 mod tests {
-    use std::time::Instant;
+    use std::{fs, io::Cursor, path::Path, time::Instant};
+
+    use nbt::{diff::diff, nbt_named_tag::NbtNamedTag};
 
-    use crate::save::anvil::Regions;
+    use crate::save::{
+        anvil::{RegionParser, Regions},
+        chunk_data::ChunkData,
+    };
 
     #[ignore]
     #[test]
@@ -276,6 +303,38 @@ mod tests {
 
         pretty_env_logger::init();
 
-        // let mut regions = read_regions("../test-data/test-world").expect("Failed to read regions");
+        let regions_path = Path::new("../test-data/test-world").join("region");
+        for path in fs::read_dir(regions_path).expect("Failed to read regions") {
+            let path = path.unwrap().path();
+            let region = RegionParser::from_file(&path).expect("Failed to read region");
+
+            for z in 0..32 {
+                for x in 0..32 {
+                    let Some((offset, sector_count)) = region.chunk_location(x, z) else {
+                        continue;
+                    };
+
+                    for chunk_buf in region.read_chunk_nbt(offset, sector_count).unwrap() {
+                        let original = NbtNamedTag::read(&mut Cursor::new(&chunk_buf))
+                            .unwrap()
+                            .unwrap();
+
+                        let chunk = ChunkData::read(&mut Cursor::new(&chunk_buf)).unwrap();
+                        let mut written = Vec::new();
+                        chunk.write(&mut written).unwrap();
+                        let written = NbtNamedTag::read(&mut Cursor::new(&written))
+                            .unwrap()
+                            .unwrap();
+
+                        let changes = diff(&original.payload, &written.payload);
+                        assert!(
+                            changes.is_empty(),
+                            "Chunk {x} {z} in {} changed:\n{changes}",
+                            path.display()
+                        );
+                    }
+                }
+            }
+        }
     }
 }
@@ -1,10 +1,14 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
 use log::warn;
 use nbt::nbt_value::value::NbtValue;
+use nbt::ser::array::{ByteArray, LongArray};
+use nbt::ser::slice_deserializer::{LongArrayView, from_slice};
+use nbt::ser::stream_serializer::to_writer;
 use nbt::{nbt_named_tag::NbtNamedTag, ser::deserializer::Deserializer};
 use serde::de::IntoDeserializer;
-use serde::{Deserialize, de::Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer, de::Visitor};
 
 use crate::save::anvil::{AnvilError, AnvilResult};
 
@@ -12,7 +16,7 @@ use crate::save::anvil::{AnvilError, AnvilResult};
 // Although we should probably also consider this page: https://minecraft.fandom.com/wiki/Anvil_file_format
 // TODO: Implement those that are still NbtValues.
 #[allow(unused)]
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct ChunkData {
     #[serde(rename = "DataVersion")]
     data_version: i32,
@@ -50,32 +54,76 @@ impl ChunkData {
 
         Ok(cd)
     }
+
+    /// Writes the chunk as an uncompressed NBT root tag, the counterpart of [ChunkData::read].
+    pub fn write<W: Write>(&self, writer: &mut W) -> AnvilResult<()> {
+        to_writer(writer, self)?;
+        Ok(())
+    }
 }
 
 /// A section in Minecraft (also known as a sub-chunk), it covers the same 16x16 area but only 16 blocks tall so a total of 4096 blocks.
 /// This means that there are (currently) 24 subchunks per chunk in the overworld of Minecraft.
 #[allow(unused)]
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct Section {
     #[serde(rename = "Y")]
     y: i8,
     block_states: BlockStates,
     biomes: Biomes,
     #[serde(rename = "BlockLight")]
-    block_light: Option<Vec<i8>>, // Always 2048 long but serde only supports arrays of length 0..=32 (each half-byte is one block). Omitted if there is no light that reaches this section.
+    block_light: Option<ByteArray>, // Always 2048 long but serde only supports arrays of length 0..=32 (each half-byte is one block). Omitted if there is no light that reaches this section.
     #[serde(rename = "SkyLight")]
-    sky_light: Option<Vec<i8>>, // Always 2048 long but serde only supports arrays of length 0..=32 (each half-byte is one block). If omitted we should look at the section right above it.
+    sky_light: Option<ByteArray>, // Always 2048 long but serde only supports arrays of length 0..=32 (each half-byte is one block). If omitted we should look at the section right above it.
 }
 
 #[allow(unused)]
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct BlockStates {
     palette: Vec<Block>, // Up to 4096 long in vanilla, longer are supported for other servers / clients.
-    data: Option<Vec<i64>>, // Always 4096 long, points to indices in the palette vector for each block of the section. Omitted if a single blockstate is used for the entire section.
+    data: Option<LongArray>, // Always 4096 long, points to indices in the palette vector for each block of the section. Omitted if a single blockstate is used for the entire section.
 }
 
-#[allow(unused)]
+/// The block states of all sections in a chunk, borrowed from the uncompressed chunk NBT so the packed
+/// block state data (4096 entries per section) is decoded on access instead of being copied.
+#[derive(Deserialize, Debug)]
+pub struct ChunkBlockStates<'a> {
+    /// The block states of each section, bottom to top.
+    #[serde(borrow)]
+    pub sections: Vec<SectionBlockStates<'a>>,
+}
+
+impl<'a> ChunkBlockStates<'a> {
+    /// Reads the block states from the uncompressed NBT of a chunk, e.g. as stored in a region file.
+    pub fn from_slice(bytes: &'a [u8]) -> AnvilResult<Self> {
+        Ok(from_slice(bytes)?)
+    }
+}
+
+/// The block states of a single [Section], the rest of the section is skipped.
 #[derive(Deserialize, Debug)]
+pub struct SectionBlockStates<'a> {
+    /// Y position of the section, in sections (16 blocks) rather than blocks.
+    #[serde(rename = "Y")]
+    pub y: i8,
+    /// The palette & the borrowed packed palette indices of the section.
+    #[serde(borrow)]
+    pub block_states: BlockStatesView<'a>,
+}
+
+/// Same as [BlockStates] but with the data borrowed.
+#[derive(Deserialize, Debug)]
+pub struct BlockStatesView<'a> {
+    /// The distinct blocks in the section.
+    pub palette: Vec<Block>,
+    /// Packed indices into [BlockStatesView::palette] for each of the 4096 blocks, decoded on
+    /// access. Omitted if a single blockstate is used for the entire section.
+    #[serde(borrow)]
+    pub data: Option<LongArrayView<'a>>,
+}
+
+#[allow(unused)]
+#[derive(Deserialize, Serialize, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct Block {
     name: String, // Block resource location.
@@ -114,6 +162,37 @@ pub enum BlockState {
     Unsupported { name: String, value: String },
 }
 
+impl BlockState {
+    /// The name & value of the property as stored in the Properties compound of a block.
+    fn property(&self) -> (&str, String) {
+        match self {
+            BlockState::Age(v) => ("age", v.0.to_string()),
+            BlockState::Axis(v) => ("axis", v.as_str().to_string()),
+            BlockState::Down(v) => ("down", v.0.to_string()),
+            BlockState::Level(v) => ("level", v.0.to_string()),
+            BlockState::Up(v) => ("up", v.0.to_string()),
+            BlockState::West(v) => ("west", v.0.to_string()),
+            BlockState::North(v) => ("north", v.0.to_string()),
+            BlockState::South(v) => ("south", v.0.to_string()),
+            BlockState::East(v) => ("east", v.0.to_string()),
+            BlockState::Lit(v) => ("lit", v.0.to_string()),
+            BlockState::Waterlogged(v) => ("waterlogged", v.0.to_string()),
+            BlockState::Half(v) => ("half", v.as_str().to_string()),
+            BlockState::Persistent(v) => ("persistent", v.0.to_string()),
+            BlockState::Distance(v) => ("distance", v.0.to_string()),
+            BlockState::SegmentAmount(v) => ("segment_amount", v.0.to_string()),
+            BlockState::Facing(v) => ("facing", v.as_str().to_string()),
+            BlockState::Snowy(v) => ("snowy", v.0.to_string()),
+            BlockState::Drag(v) => ("drag", v.0.to_string()),
+            BlockState::Type(v) => ("type", v.as_str().to_string()),
+            BlockState::Shape(v) => ("shape", v.as_str().to_string()),
+            BlockState::Open(v) => ("open", v.0.to_string()),
+            BlockState::Powered(v) => ("powered", v.0.to_string()),
+            BlockState::Unsupported { name, value } => (name, value.clone()),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum BlockType {
@@ -127,6 +206,22 @@ pub enum BlockType {
     Top,
 }
 
+impl BlockType {
+    /// The value of the property as stored in NBT.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlockType::Normal => "normal",
+            BlockType::Sticky => "sticky",
+            BlockType::Left => "left",
+            BlockType::Right => "right",
+            BlockType::Single => "single",
+            BlockType::Bottom => "bottom",
+            BlockType::Double => "double",
+            BlockType::Top => "top",
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum BlockShape {
@@ -149,6 +244,29 @@ pub enum BlockShape {
     Straight,
 }
 
+impl BlockShape {
+    /// The value of the property as stored in NBT.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlockShape::AscendingEast => "ascending_east",
+            BlockShape::AscendingNorth => "ascending_north",
+            BlockShape::AscendingSouth => "ascending_south",
+            BlockShape::AscendingWest => "ascending_west",
+            BlockShape::EastWest => "east_west",
+            BlockShape::NorthSouth => "north_south",
+            BlockShape::NorthEast => "north_east",
+            BlockShape::NorthWest => "north_west",
+            BlockShape::SouthEast => "south_east",
+            BlockShape::SouthWest => "south_west",
+            BlockShape::InnerLeft => "inner_left",
+            BlockShape::InnerRight => "inner_right",
+            BlockShape::OuterLeft => "outer_left",
+            BlockShape::OuterRight => "outer_right",
+            BlockShape::Straight => "straight",
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum Direction {
@@ -160,6 +278,20 @@ pub enum Direction {
     Up,
 }
 
+impl Direction {
+    /// The value of the property as stored in NBT.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Down => "down",
+            Direction::East => "east",
+            Direction::North => "north",
+            Direction::South => "south",
+            Direction::West => "west",
+            Direction::Up => "up",
+        }
+    }
+}
+
 /// A stringified boolean, because sometimes minecraft decides to put booleans in Strings :facepalm:
 #[allow(unused)]
 #[derive(Debug)]
@@ -210,6 +342,17 @@ pub enum BlockAxis {
     Z,
 }
 
+impl BlockAxis {
+    /// The value of the property as stored in NBT.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlockAxis::X => "x",
+            BlockAxis::Y => "y",
+            BlockAxis::Z => "z",
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub enum Half {
@@ -219,6 +362,18 @@ pub enum Half {
     Top,
 }
 
+impl Half {
+    /// The value of the property as stored in NBT.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Half::Upper => "upper",
+            Half::Lower => "lower",
+            Half::Bottom => "bottom",
+            Half::Top => "top",
+        }
+    }
+}
+
 struct PropertiesVisitor;
 
 impl<'de> Visitor<'de> for PropertiesVisitor {
@@ -236,9 +391,7 @@ impl<'de> Visitor<'de> for PropertiesVisitor {
 
         // CRIMES AHOY! But hey, how would you solve it?
         while let Some((field, value)) = map.next_entry::<String, NbtValue>()? {
-            let f = field.clone();
-            let v = value.clone();
-            let val = NbtValue::Compound(vec![(f, v)].into_iter().collect());
+            let val = NbtValue::Compound(vec![(field.clone(), value.clone())]);
 
             let deserializer = val.into_deserializer();
             let block_state: BlockState = match BlockState::deserialize(deserializer) {
@@ -247,10 +400,11 @@ impl<'de> Visitor<'de> for PropertiesVisitor {
                     warn!(
                         "Failed to deserialize property: {{{field}: {value}}} converting to 'Unsupported', err: {err}"
                     );
-                    BlockState::Unsupported {
-                        name: field,
-                        value: value.to_string(),
-                    }
+                    let value = match value {
+                        NbtValue::String(s) => s,
+                        v => v.to_string(),
+                    };
+                    BlockState::Unsupported { name: field, value }
                 }
             };
             properties.push(block_state);
@@ -260,6 +414,20 @@ impl<'de> Visitor<'de> for PropertiesVisitor {
     }
 }
 
+impl Serialize for PropertiesList {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for state in &self.0 {
+            let (name, value) = state.property();
+            map.serialize_entry(name, &value)?;
+        }
+        map.end()
+    }
+}
+
 impl<'de> Deserialize<'de> for PropertiesList {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -270,8 +438,135 @@ impl<'de> Deserialize<'de> for PropertiesList {
 }
 
 #[allow(unused)]
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 struct Biomes {
     palette: Vec<String>, // Will never contain more than 64 entries in vanilla but larger are supported.
-    data: Option<Vec<i64>>, // Contains 64 indices pointing ot the palette, biomes are stored in cells of 4x4x4 blocks. Not provided if only one biome is used for this section.
+    data: Option<LongArray>, // Contains 64 indices pointing ot the palette, biomes are stored in cells of 4x4x4 blocks. Not provided if only one biome is used for this section.
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use nbt::snbt::Snbt;
+
+    use super::*;
+
+    #[test]
+    fn chunk_survives_load_save() {
+        let snbt = Snbt::from(
+            r#"{DataVersion:4189,xPos:1,yPos:-4,zPos:-2,Status:"minecraft:full",LastUpdate:100L,sections:[{Y:-4b,block_states:{palette:[{Name:"minecraft:oak_stairs",Properties:{facing:"east",half:"bottom",shape:"outer_left",waterlogged:"false",unknown:"1"}},{Name:"minecraft:stone"}]},biomes:{palette:["minecraft:plains"]}}],block_entities:[],Heightmaps:{MOTION_BLOCKING:[L;1L,2L,3L],WORLD_SURFACE:[L;4L],OCEAN_FLOOR:[L;]},fluid_ticks:[],block_ticks:[{i:"minecraft:water",x:1,y:2,z:3,t:5,p:0}],InhabitedTime:42L,PostProcessing:[[],[1s,2s]],structures:{starts:{},References:{"minecraft:village":[L;7L]},bytes:[B;1b],ints:[I;]}}"#,
+        );
+        let tag = NbtNamedTag::try_from(&snbt).unwrap();
+        let mut bytes = Vec::new();
+        tag.write(&mut bytes).unwrap();
+
+        let chunk = ChunkData::read(&mut Cursor::new(&bytes)).expect("Failed to read chunk");
+        let mut written = Vec::new();
+        chunk.write(&mut written).expect("Failed to write chunk");
+
+        let read = NbtNamedTag::read(&mut Cursor::new(&written))
+            .unwrap()
+            .unwrap();
+        assert_eq!(read, tag);
+    }
+
+    #[test]
+    fn property_values_match_serde_names() {
+        fn check<'de, T: Deserialize<'de>>(values: &[T], as_str: impl Fn(&T) -> &'static str) {
+            for value in values {
+                let name = as_str(value);
+                let read = T::deserialize(
+                    IntoDeserializer::<serde::de::value::Error>::into_deserializer(name),
+                )
+                .unwrap_or_else(|err| panic!("{name} isn't a valid property value: {err}"));
+                assert_eq!(as_str(&read), name);
+            }
+        }
+
+        use BlockShape::*;
+        check(
+            &[BlockAxis::X, BlockAxis::Y, BlockAxis::Z],
+            BlockAxis::as_str,
+        );
+        check(
+            &[Half::Upper, Half::Lower, Half::Bottom, Half::Top],
+            Half::as_str,
+        );
+        check(
+            &[
+                Direction::Down,
+                Direction::East,
+                Direction::North,
+                Direction::South,
+                Direction::West,
+                Direction::Up,
+            ],
+            Direction::as_str,
+        );
+        check(
+            &[
+                BlockType::Normal,
+                BlockType::Sticky,
+                BlockType::Left,
+                BlockType::Right,
+                BlockType::Single,
+                BlockType::Bottom,
+                BlockType::Double,
+                BlockType::Top,
+            ],
+            BlockType::as_str,
+        );
+        check(
+            &[
+                AscendingEast,
+                AscendingNorth,
+                AscendingSouth,
+                AscendingWest,
+                EastWest,
+                NorthSouth,
+                NorthEast,
+                NorthWest,
+                SouthEast,
+                SouthWest,
+                InnerLeft,
+                InnerRight,
+                OuterLeft,
+                OuterRight,
+                Straight,
+            ],
+            BlockShape::as_str,
+        );
+    }
+
+    fn section_arrays_snbt(data: &str, light: &str) -> Snbt {
+        Snbt::from(format!(
+            r#"{{DataVersion:4189,xPos:0,yPos:-4,zPos:0,Status:"minecraft:full",LastUpdate:0L,sections:[{{Y:0b,block_states:{{palette:[{{Name:"minecraft:air"}},{{Name:"minecraft:stone"}}],data:{data}}},biomes:{{palette:["minecraft:plains","minecraft:desert"],data:{data}}},BlockLight:{light},SkyLight:{light}}}],block_entities:[],Heightmaps:{{}},fluid_ticks:[],block_ticks:[],InhabitedTime:0L,PostProcessing:[],structures:{{}}}}"#
+        ))
+    }
+
+    fn load_save(snbt: &Snbt) -> NbtNamedTag {
+        let mut bytes = Vec::new();
+        NbtNamedTag::try_from(snbt)
+            .unwrap()
+            .write(&mut bytes)
+            .unwrap();
+
+        let chunk = ChunkData::read(&mut Cursor::new(&bytes)).expect("Failed to read chunk");
+        let mut written = Vec::new();
+        chunk.write(&mut written).expect("Failed to write chunk");
+
+        NbtNamedTag::read(&mut Cursor::new(&written))
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn chunk_arrays_keep_their_tag_types() {
+        let arrays = section_arrays_snbt("[L;1L,-2L,3L]", "[B;0b,15b,-1b]");
+        assert_eq!(load_save(&arrays), NbtNamedTag::try_from(&arrays).unwrap());
+
+        let lists = section_arrays_snbt("[1L,-2L,3L]", "[0b,15b,-1b]");
+        assert_eq!(load_save(&lists), NbtNamedTag::try_from(&arrays).unwrap());
+    }
 }
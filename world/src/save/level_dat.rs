@@ -227,8 +227,8 @@ mod test {
         let dat = MinecraftLevelDat::deserialize(deserializer).expect("Failed to deserialize");
         let serialized = to_nbt_tag_type(&dat).expect("Failed to serialize level.dat");
 
-        let ser: Snbt = (&serialized.unwrap()).into();
-        let og: Snbt = (&nbt.payload).into();
+        let ser = Snbt::try_from(&serialized.unwrap()).unwrap();
+        let og = Snbt::try_from(&nbt.payload).unwrap();
 
         assert_eq!(ser, og)
     }
@@ -1,4 +1,6 @@
 pub mod anvil;
 pub mod chunk_data;
+// TODO: Remove once level.dat is actually loaded, the structs are only used by tests for now.
+#[allow(dead_code)]
 pub mod level_dat;
 pub mod player_dat;